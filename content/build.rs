@@ -0,0 +1,21 @@
+// pathfinder/content/build.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate rustc_version;
+
+use rustc_version::Channel;
+
+fn main() {
+    // Benchmarks use the unstable `test` crate, so they are only built on nightly.
+    println!("cargo:rustc-check-cfg=cfg(pf_rustc_nightly)");
+    if rustc_version::version_meta().unwrap().channel == Channel::Nightly {
+        println!("cargo:rustc-cfg=pf_rustc_nightly");
+    }
+}
@@ -8,14 +8,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::outline::{Contour, ContourIterFlags, PointFlags, PushSegmentFlags};
+use crate::fill::FillRule;
+use crate::outline::{Contour, ContourIterFlags, Outline, PointFlags, PushSegmentFlags};
 use crate::segment::{CubicSegment, Segment};
 use arrayvec::ArrayVec;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::util::lerp;
-use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use pathfinder_geometry::vector::{Vector2F, Vector4F, vec2f};
 use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::mem;
 
@@ -254,11 +256,14 @@ where
 
     fn push_segment(&mut self, segment: &Segment) {
         let contour = self.contour_mut();
-        if let Some(last_position) = contour.last_position() {
-            if last_position != segment.baseline.from() {
+        match contour.last_position() {
+            // Start with an endpoint, even if the first segment is a curve.
+            None => contour.push_point(segment.baseline.from(), PointFlags::empty(), true),
+            Some(last_position) if last_position != segment.baseline.from() => {
                 // Add a line to join up segments.
                 contour.push_point(segment.baseline.from(), PointFlags::empty(), true);
             }
+            Some(_) => {}
         }
 
         contour.push_segment(segment, PushSegmentFlags::UPDATE_BOUNDS);
@@ -376,6 +381,305 @@ impl ContourRectClipper {
     }
 }
 
+// General outline clipping and boolean operations in 2D
+
+const OUTLINE_CLIP_PARAMETER_EPSILON: f32 = 0.00001;
+// How far to either side of a piece its midpoint is probed, relative to the size of the outlines.
+const OUTLINE_CLIP_PROBE_DISTANCE: f32 = 0.00001;
+
+/// A boolean operation between the filled regions of two outlines.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BooleanOp {
+    /// The region covered by either outline.
+    Union,
+    /// The region covered by both outlines.
+    Intersection,
+    /// The region covered by the first outline but not the second.
+    Difference,
+    /// The region covered by exactly one of the outlines.
+    Xor,
+}
+
+impl BooleanOp {
+    #[inline]
+    fn apply(self, inside_subject: bool, inside_clip: bool) -> bool {
+        match self {
+            BooleanOp::Union => inside_subject || inside_clip,
+            BooleanOp::Intersection => inside_subject && inside_clip,
+            BooleanOp::Difference => inside_subject && !inside_clip,
+            BooleanOp::Xor => inside_subject != inside_clip,
+        }
+    }
+}
+
+// Combines two flattened outlines, each filled with the given rule.
+//
+// Every edge of both outlines is split at all of its crossings with the other edges. A piece lies
+// on the boundary of the result if a point just to one side of it is inside the result and a point
+// just to the other side is not. Boundary pieces are oriented so that the interior is on their
+// right in a y-down coordinate system, then chained together into contours.
+//
+// This handles concave outlines, holes, and self-intersection uniformly, but it is not fast. With
+// n edges in total and k crossings between them, finding the crossings takes O(n²) time, since
+// every pair of edges is tested, and classifying the resulting O(n + k) pieces takes O((n + k) n),
+// since each probe is tested against every edge.
+pub(crate) struct OutlineClipper {
+    subject_edges: Vec<LineSegment2F>,
+    clip_edges: Vec<LineSegment2F>,
+    op: BooleanOp,
+    fill_rule: FillRule,
+    probe_distance: f32,
+}
+
+impl OutlineClipper {
+    #[inline]
+    pub(crate) fn new(subject: &Outline, clip: &Outline, op: BooleanOp, fill_rule: FillRule)
+                      -> OutlineClipper {
+        let size = subject.bounds().size().max(clip.bounds().size());
+        OutlineClipper {
+            subject_edges: outline_edges(subject),
+            clip_edges: outline_edges(clip),
+            op,
+            fill_rule,
+            probe_distance: OUTLINE_CLIP_PROBE_DISTANCE * f32::max(size.x(), size.y()),
+        }
+    }
+
+    pub(crate) fn clip(self) -> Outline {
+        let mut edges = self.subject_edges.clone();
+        edges.extend_from_slice(&self.clip_edges);
+
+        // Find the points at which each edge must be split, as (t, position) pairs. Positions are
+        // shared between the two edges that meet there so that the pieces join up exactly.
+        let mut splits: Vec<Vec<(f32, Vector2F)>> = edges.iter().map(|edge| {
+            vec![(0.0, edge.from()), (1.0, edge.to())]
+        }).collect();
+        for index_a in 0..edges.len() {
+            for index_b in (index_a + 1)..edges.len() {
+                split_edges(edges[index_a], edges[index_b], index_a, index_b, &mut splits);
+            }
+        }
+
+        let mut pieces = vec![];
+        for mut edge_splits in splits {
+            edge_splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for pair in edge_splits.windows(2) {
+                let (from, to) = (pair[0].1, pair[1].1);
+                if from == to {
+                    continue;
+                }
+                if let Some(piece) = self.classify_piece(LineSegment2F::new(from, to)) {
+                    pieces.push(piece);
+                }
+            }
+        }
+
+        // Edges shared by both outlines can produce the same piece twice.
+        let mut seen = HashSet::new();
+        pieces.retain(|piece| seen.insert(point_key_pair(*piece)));
+
+        chain_pieces(&pieces)
+    }
+
+    // Returns the piece oriented with the result on its right, or `None` if it isn't on the
+    // boundary of the result.
+    fn classify_piece(&self, piece: LineSegment2F) -> Option<LineSegment2F> {
+        let vector = piece.vector();
+        let probe_distance = f32::min(self.probe_distance, vector.length() * 0.25);
+        let normal = vec2f(-vector.y(), vector.x()).normalize() * probe_distance;
+        let midpoint = piece.midpoint();
+        let right_inside = self.is_inside(midpoint + normal);
+        let left_inside = self.is_inside(midpoint - normal);
+        match (left_inside, right_inside) {
+            (false, true) => Some(piece),
+            (true, false) => Some(piece.reversed()),
+            _ => None,
+        }
+    }
+
+    fn is_inside(&self, point: Vector2F) -> bool {
+        self.op.apply(edges_contain_point(&self.subject_edges, point, self.fill_rule),
+                      edges_contain_point(&self.clip_edges, point, self.fill_rule))
+    }
+}
+
+// Flattened contours are filled as though closed, so every contour contributes a closing edge.
+fn outline_edges(outline: &Outline) -> Vec<LineSegment2F> {
+    let mut edges = vec![];
+    for contour in outline.contours() {
+        let point_count = contour.len();
+        for point_index in 0..point_count {
+            let from = contour.position_of(point_index);
+            let to = contour.position_of((point_index + 1) % point_count);
+            if from != to {
+                edges.push(LineSegment2F::new(from, to));
+            }
+        }
+    }
+    edges
+}
+
+// Unlike `Outline::contains_point()`, this has no tolerance for points near the edges, since the
+// probe points are deliberately placed close to them.
+fn edges_contain_point(edges: &[LineSegment2F], point: Vector2F, fill_rule: FillRule) -> bool {
+    let mut winding_number = 0;
+    for edge in edges {
+        let (from, to) = (edge.from(), edge.to());
+        if (from.y() <= point.y()) == (to.y() <= point.y()) {
+            continue;
+        }
+        let side = edge.vector().det(point - from);
+        if from.y() <= point.y() && side > 0.0 {
+            winding_number += 1;
+        } else if to.y() <= point.y() && side < 0.0 {
+            winding_number -= 1;
+        }
+    }
+
+    match fill_rule {
+        FillRule::Winding => winding_number != 0,
+        FillRule::EvenOdd => winding_number % 2 != 0,
+    }
+}
+
+fn split_edges(a: LineSegment2F,
+               b: LineSegment2F,
+               index_a: usize,
+               index_b: usize,
+               splits: &mut [Vec<(f32, Vector2F)>]) {
+    let (vector_a, vector_b) = (a.vector(), b.vector());
+    let offset = b.from() - a.from();
+    let denominator = vector_a.det(vector_b);
+
+    if denominator.abs() <= OUTLINE_CLIP_PARAMETER_EPSILON * vector_a.length() *
+            vector_b.length() {
+        // Parallel. If collinear, split each edge at the endpoints of the other that it contains.
+        if offset.det(vector_a).abs() > OUTLINE_CLIP_PARAMETER_EPSILON * vector_a.length() *
+                offset.length() {
+            return;
+        }
+        for &(point, edge, index) in &[(b.from(), a, index_a),
+                                       (b.to(), a, index_a),
+                                       (a.from(), b, index_b),
+                                       (a.to(), b, index_b)] {
+            let vector = edge.vector();
+            let t = (point - edge.from()).dot(vector) / vector.square_length();
+            if parameter_is_interior(t) {
+                splits[index].push((t, point));
+            }
+        }
+        return;
+    }
+
+    let t = offset.det(vector_b) / denominator;
+    let u = offset.det(vector_a) / denominator;
+    let range = -OUTLINE_CLIP_PARAMETER_EPSILON..=(1.0 + OUTLINE_CLIP_PARAMETER_EPSILON);
+    if !range.contains(&t) || !range.contains(&u) {
+        return;
+    }
+
+    // Prefer an existing endpoint as the split position so that the pieces stay connected.
+    let position = if !parameter_is_interior(t) {
+        if t < 0.5 { a.from() } else { a.to() }
+    } else if !parameter_is_interior(u) {
+        if u < 0.5 { b.from() } else { b.to() }
+    } else {
+        a.sample(t)
+    };
+
+    if parameter_is_interior(t) {
+        splits[index_a].push((t, position));
+    }
+    if parameter_is_interior(u) {
+        splits[index_b].push((u, position));
+    }
+}
+
+#[inline]
+fn parameter_is_interior(t: f32) -> bool {
+    t > OUTLINE_CLIP_PARAMETER_EPSILON && t < 1.0 - OUTLINE_CLIP_PARAMETER_EPSILON
+}
+
+#[inline]
+fn point_key(point: Vector2F) -> (u32, u32) {
+    (point.x().to_bits(), point.y().to_bits())
+}
+
+#[inline]
+fn point_key_pair(segment: LineSegment2F) -> ((u32, u32), (u32, u32)) {
+    (point_key(segment.from()), point_key(segment.to()))
+}
+
+// Links directed pieces end to start into closed contours, merging collinear runs.
+fn chain_pieces(pieces: &[LineSegment2F]) -> Outline {
+    let mut pieces_starting_at: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (piece_index, piece) in pieces.iter().enumerate() {
+        pieces_starting_at.entry(point_key(piece.from())).or_default().push(piece_index);
+    }
+
+    let mut used = vec![false; pieces.len()];
+    let mut outline = Outline::new();
+    for first_piece_index in 0..pieces.len() {
+        if used[first_piece_index] {
+            continue;
+        }
+
+        let mut points = vec![];
+        let mut piece_index = first_piece_index;
+        loop {
+            used[piece_index] = true;
+            push_chain_point(&mut points, pieces[piece_index].from());
+
+            let next_piece_index = pieces_starting_at.get(&point_key(pieces[piece_index].to()))
+                                                     .and_then(|candidates| {
+                candidates.iter().cloned().find(|&candidate| !used[candidate])
+            });
+            match next_piece_index {
+                Some(next_piece_index) => piece_index = next_piece_index,
+                None => break,
+            }
+        }
+
+        // Merge collinear runs across the seam too.
+        while points.len() >= 3 &&
+                points_are_collinear(points[points.len() - 2], points[points.len() - 1], points[0]) {
+            points.pop();
+        }
+        while points.len() >= 3 &&
+                points_are_collinear(points[points.len() - 1], points[0], points[1]) {
+            points.remove(0);
+        }
+        if points.len() < 3 {
+            continue;
+        }
+
+        let mut contour = Contour::with_capacity(points.len());
+        for point in points {
+            contour.push_endpoint(point);
+        }
+        contour.close();
+        outline.push_contour(contour);
+    }
+
+    outline
+}
+
+fn push_chain_point(points: &mut Vec<Vector2F>, point: Vector2F) {
+    let count = points.len();
+    if count >= 2 && points_are_collinear(points[count - 2], points[count - 1], point) {
+        points[count - 1] = point;
+    } else {
+        points.push(point);
+    }
+}
+
+// Returns true if `b` lies on the straight path from `a` to `c`.
+fn points_are_collinear(a: Vector2F, b: Vector2F, c: Vector2F) -> bool {
+    let (ab, bc) = (b - a, c - b);
+    ab.det(bc).abs() <= OUTLINE_CLIP_PARAMETER_EPSILON * ab.length() * bc.length() &&
+        ab.dot(bc) > 0.0
+}
+
 // 3D quad clipping
 
 pub struct PolygonClipper3D {
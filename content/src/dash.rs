@@ -18,42 +18,56 @@ const EPSILON: f32 = 0.0001;
 pub struct OutlineDash<'a> {
     input: &'a Outline,
     output: Outline,
-    state: DashState<'a>,
+    dashes: &'a [f32],
+    offset: f32,
 }
 
 impl<'a> OutlineDash<'a> {
+    /// Creates a dasher for `input`.
+    ///
+    /// `dashes` alternates between the lengths of "on" and "off" intervals, starting with "on";
+    /// odd-length patterns behave as though they were repeated twice. The pattern restarts at
+    /// `offset` for each contour. Patterns that are empty, have negative lengths, or sum to zero
+    /// leave the outline undashed.
     #[inline]
     pub fn new(input: &'a Outline, dashes: &'a [f32], offset: f32) -> OutlineDash<'a> {
-        OutlineDash { input, output: Outline::new(), state: DashState::new(dashes, offset) }
+        OutlineDash { input, output: Outline::new(), dashes, offset }
     }
 
     pub fn dash(&mut self) {
         for contour in &self.input.contours {
-            ContourDash::new(contour, &mut self.output, &mut self.state).dash()
+            let dashed_contours = ContourDash::new(contour, self.dashes, self.offset).dash();
+            self.output.extend(dashed_contours);
         }
     }
 
-    pub fn into_outline(mut self) -> Outline {
-        if self.state.is_on() {
-            self.output.push_contour(self.state.output);
-        }
+    #[inline]
+    pub fn into_outline(self) -> Outline {
         self.output
     }
 }
 
-struct ContourDash<'a, 'b, 'c> {
+struct ContourDash<'a> {
     input: &'a Contour,
-    output: &'b mut Outline,
-    state: &'c mut DashState<'a>,
+    output: Vec<Contour>,
+    state: DashState<'a>,
 }
 
-impl<'a, 'b, 'c> ContourDash<'a, 'b, 'c> {
-    fn new(input: &'a Contour, output: &'b mut Outline, state: &'c mut DashState<'a>)
-           -> ContourDash<'a, 'b, 'c> {
-        ContourDash { input, output, state }
+impl<'a> ContourDash<'a> {
+    fn new(input: &'a Contour, dashes: &'a [f32], offset: f32) -> ContourDash<'a> {
+        ContourDash { input, output: vec![], state: DashState::new(dashes, offset) }
     }
 
-    fn dash(&mut self) {
+    fn dash(mut self) -> Vec<Contour> {
+        if self.input.is_empty() {
+            return vec![];
+        }
+        if pattern_is_degenerate(self.state.dashes) {
+            return vec![self.input.clone()];
+        }
+
+        let starts_on = self.state.is_on();
+
         let mut iterator = self.input.iter(ContourIterFlags::empty());
         let mut queued_segment = None;
         loop {
@@ -77,23 +91,40 @@ impl<'a, 'b, 'c> ContourDash<'a, 'b, 'c> {
             }
 
             if self.state.is_on() {
-                self.state.output.push_segment(&current_segment, PushSegmentFlags::empty());
+                let mut flags = PushSegmentFlags::UPDATE_BOUNDS;
+                if self.state.output.is_empty() {
+                    flags.insert(PushSegmentFlags::INCLUDE_FROM_POINT);
+                }
+                self.state.output.push_segment(&current_segment, flags);
             }
 
             self.state.distance_left -= distance;
             if self.state.distance_left < EPSILON {
                 if self.state.is_on() {
-                    self.output.push_contour(mem::replace(&mut self.state.output, Contour::new()));
+                    self.output.push(mem::replace(&mut self.state.output, Contour::new()));
                 }
+                self.state.next_dash();
+            }
+        }
 
-                self.state.current_dash_index += 1;
-                if self.state.current_dash_index == self.state.dashes.len() {
-                    self.state.current_dash_index = 0;
+        if self.state.is_on() && !self.state.output.is_empty() {
+            let mut last_dash = self.state.output;
+            if self.input.is_closed() && starts_on {
+                // The last dash runs over the start of the contour, so join it to the first.
+                if self.output.is_empty() {
+                    return vec![self.input.clone()];
+                }
+                let first_dash = self.output.remove(0);
+                for point_index in 1..first_dash.len() {
+                    last_dash.push_point(first_dash.position_of(point_index),
+                                         first_dash.flags[point_index as usize],
+                                         true);
                 }
-
-                self.state.distance_left = self.state.dashes[self.state.current_dash_index];
             }
+            self.output.push(last_dash);
         }
+
+        self.output
     }
 }
 
@@ -106,25 +137,63 @@ struct DashState<'a> {
 
 impl<'a> DashState<'a> {
     fn new(dashes: &'a [f32], mut offset: f32) -> DashState<'a> {
-        let total: f32 = dashes.iter().cloned().sum();
-        offset %= total;
+        let mut state = DashState {
+            output: Contour::new(),
+            dashes,
+            current_dash_index: 0,
+            distance_left: 0.0,
+        };
+        if pattern_is_degenerate(dashes) {
+            return state;
+        }
 
-        let mut current_dash_index = 0;
-        while current_dash_index < dashes.len() {
-            let dash = dashes[current_dash_index];
+        let period = state.period();
+        offset %= period;
+        if offset < 0.0 {
+            offset += period;
+        }
+
+        while state.current_dash_index + 1 < state.dash_count() {
+            let dash = state.current_dash();
             if offset < dash {
                 break;
             }
             offset -= dash;
-            current_dash_index += 1;
+            state.current_dash_index += 1;
         }
 
-        DashState {
-            output: Contour::new(),
-            dashes,
-            current_dash_index,
-            distance_left: offset,
+        state.distance_left = state.current_dash() - offset;
+        state
+    }
+
+    // Odd-length patterns are repeated so that "on" and "off" intervals alternate.
+    #[inline]
+    fn dash_count(&self) -> usize {
+        if self.dashes.len() % 2 == 1 {
+            self.dashes.len() * 2
+        } else {
+            self.dashes.len()
+        }
+    }
+
+    #[inline]
+    fn period(&self) -> f32 {
+        let total: f32 = self.dashes.iter().sum();
+        total * (self.dash_count() / self.dashes.len()) as f32
+    }
+
+    #[inline]
+    fn current_dash(&self) -> f32 {
+        self.dashes[self.current_dash_index % self.dashes.len()]
+    }
+
+    #[inline]
+    fn next_dash(&mut self) {
+        self.current_dash_index += 1;
+        if self.current_dash_index == self.dash_count() {
+            self.current_dash_index = 0;
         }
+        self.distance_left = self.current_dash();
     }
 
     #[inline]
@@ -132,3 +201,93 @@ impl<'a> DashState<'a> {
         self.current_dash_index % 2 == 0
     }
 }
+
+/// Dashes a single contour, returning one open contour per "on" interval.
+///
+/// See `OutlineDash::new()` for the meaning of `dashes` and `offset`.
+pub(crate) fn dash_contour(input: &Contour, dashes: &[f32], offset: f32) -> Vec<Contour> {
+    ContourDash::new(input, dashes, offset).dash()
+}
+
+fn pattern_is_degenerate(dashes: &[f32]) -> bool {
+    dashes.iter().any(|&dash| dash < 0.0 || !dash.is_finite()) ||
+        dashes.iter().sum::<f32>() <= 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+    fn assert_points_eq(contour: &Contour, expected: &[Vector2F]) {
+        assert_eq!(contour.len() as usize, expected.len(), "{:?}", contour);
+        for (point_index, &expected_point) in expected.iter().enumerate() {
+            let point = contour.position_of(point_index as u32);
+            assert!((point - expected_point).length() < 0.001, "{:?}", contour);
+        }
+    }
+
+    #[test]
+    fn test_dash_line() {
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+
+        let dashes = line.dash(&[2.0, 2.0], 0.0);
+        assert_eq!(dashes.len(), 3);
+        for (dash, &start) in dashes.iter().zip(&[0.0, 4.0, 8.0]) {
+            assert!(!dash.is_closed());
+            assert_points_eq(dash, &[vec2f(start, 0.0), vec2f(start + 2.0, 0.0)]);
+        }
+
+        let dashes = line.dash(&[2.0, 2.0], 1.0);
+        assert_eq!(dashes.len(), 3);
+        assert_points_eq(&dashes[0], &[vec2f(0.0, 0.0), vec2f(1.0, 0.0)]);
+        assert_points_eq(&dashes[2], &[vec2f(7.0, 0.0), vec2f(9.0, 0.0)]);
+
+        // Odd-length patterns repeat so that the third dash is an "off" interval.
+        let dashes = line.dash(&[3.0], 0.0);
+        assert_eq!(dashes.len(), 2);
+        assert_points_eq(&dashes[1], &[vec2f(6.0, 0.0), vec2f(9.0, 0.0)]);
+
+        for pattern in &[&[][..], &[0.0, 0.0][..], &[1.0, -1.0][..]] {
+            let dashes = line.dash(pattern, 0.0);
+            assert_eq!(dashes.len(), 1);
+            assert_eq!(format!("{:?}", dashes[0]), format!("{:?}", line));
+        }
+    }
+
+    #[test]
+    fn test_dash_closed_contour() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        assert!(square.is_closed());
+
+        // The last dash wraps around the start of the contour and joins the first.
+        let dashes = square.dash(&[6.0, 4.0], 3.0);
+        assert_eq!(dashes.len(), 4);
+        assert_points_eq(&dashes[0], &[vec2f(7.0, 0.0), vec2f(10.0, 0.0), vec2f(10.0, 3.0)]);
+        assert_points_eq(&dashes[3], &[vec2f(0.0, 3.0), vec2f(0.0, 0.0), vec2f(3.0, 0.0)]);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        circle.close();
+        let dashes = circle.dash(&[1.0, 1.0], 0.0);
+        // The partial dash at the end joins the first dash.
+        assert_eq!(dashes.len(), (circle.length() / 2.0).floor() as usize);
+        for dash in &dashes[..dashes.len() - 1] {
+            assert!((dash.length() - 1.0).abs() < 0.01);
+        }
+
+        let undashed = square.dash(&[100.0, 1.0], 0.0);
+        assert_eq!(undashed.len(), 1);
+        assert!(undashed[0].is_closed());
+
+        let mut outline = Outline::new();
+        outline.push_contour(square);
+        let dashed = outline.dash(&[6.0, 4.0], 3.0);
+        assert_eq!(dashed.contours().len(), 4);
+        assert_eq!(dashed.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+    }
+}
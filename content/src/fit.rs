@@ -0,0 +1,232 @@
+// pathfinder/content/src/fit.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fitting of cubic Bézier curves to sampled points.
+
+use crate::outline::Contour;
+use pathfinder_geometry::vector::Vector2F;
+
+// How many times to refine the parameters of the points with Newton's method before giving up
+// on a curve and splitting it.
+const MAX_REPARAMETERIZATIONS: u32 = 4;
+
+// Fits a contour of cubic curves to the points with the algorithm from Philip J. Schneider, "An
+// Algorithm for Automatically Fitting Digitized Curves", Graphics Gems (1990).
+//
+// The points are first cut into runs at every point where the direction turns by more than
+// `corner_angle`. Each run is fit with one cubic by least squares; if some point lies farther than
+// `error` from the cubic, the run is split at the worst point and each half is fit in turn.
+pub(crate) fn fit_cubics(points: &[Vector2F], error: f32, corner_angle: f32) -> Contour {
+    let mut points = points.to_vec();
+    points.dedup();
+
+    let mut contour = Contour::with_capacity(points.len());
+    match points.first() {
+        None => return contour,
+        Some(&first_point) => contour.push_endpoint(first_point),
+    }
+
+    let mut fitter = CurveFitter { points: &points, square_error: error * error, contour };
+    let mut run_start = 0;
+    for point_index in 1..points.len() {
+        let is_corner = point_index + 1 < points.len() &&
+            turning_angle(points[point_index - 1],
+                          points[point_index],
+                          points[point_index + 1]) > corner_angle;
+        if is_corner || point_index + 1 == points.len() {
+            fitter.fit_run(run_start, point_index);
+            run_start = point_index;
+        }
+    }
+    fitter.contour
+}
+
+struct CurveFitter<'a> {
+    points: &'a [Vector2F],
+    square_error: f32,
+    contour: Contour,
+}
+
+impl<'a> CurveFitter<'a> {
+    fn fit_run(&mut self, first: usize, last: usize) {
+        let left_tangent = direction(self.points[first], self.points[first + 1]);
+        let right_tangent = direction(self.points[last], self.points[last - 1]);
+        self.fit_cubic(first, last, left_tangent, right_tangent);
+    }
+
+    // Fits the points from `first` to `last` inclusive, leaving the first point along
+    // `left_tangent` and arriving at the last point from the direction of `right_tangent`.
+    fn fit_cubic(&mut self,
+                 first: usize,
+                 last: usize,
+                 left_tangent: Vector2F,
+                 right_tangent: Vector2F) {
+        let (from, to) = (self.points[first], self.points[last]);
+        if last - first == 1 {
+            let handle_length = (to - from).length() / 3.0;
+            self.contour.push_cubic(from + left_tangent * handle_length,
+                                    to + right_tangent * handle_length,
+                                    to);
+            return;
+        }
+
+        let mut params = self.chord_length_params(first, last);
+        let mut curve = self.least_squares_cubic(first, last, &params, left_tangent, right_tangent);
+        let (mut max_error, mut split_index) = self.max_error(first, last, &curve, &params);
+
+        // If the fit is close, try to improve it before resorting to a split.
+        if max_error >= self.square_error && max_error < self.square_error * 4.0 {
+            for _ in 0..MAX_REPARAMETERIZATIONS {
+                for (param_index, param) in params.iter_mut().enumerate() {
+                    *param = newton_raphson_root(&curve, self.points[first + param_index], *param);
+                }
+                curve = self.least_squares_cubic(first, last, &params, left_tangent, right_tangent);
+                let (new_max_error, new_split_index) = self.max_error(first, last, &curve, &params);
+                max_error = new_max_error;
+                split_index = new_split_index;
+                if max_error < self.square_error {
+                    break;
+                }
+            }
+        }
+
+        if max_error < self.square_error {
+            self.contour.push_cubic(curve[1], curve[2], curve[3]);
+            return;
+        }
+
+        let mut center_tangent = direction(self.points[split_index + 1],
+                                           self.points[split_index - 1]);
+        if center_tangent == Vector2F::zero() {
+            center_tangent = direction(self.points[split_index + 1], self.points[split_index]);
+        }
+        self.fit_cubic(first, split_index, left_tangent, center_tangent);
+        self.fit_cubic(split_index, last, -center_tangent, right_tangent);
+    }
+
+    fn chord_length_params(&self, first: usize, last: usize) -> Vec<f32> {
+        let mut params = Vec::with_capacity(last - first + 1);
+        let mut distance = 0.0;
+        params.push(0.0);
+        for point_index in (first + 1)..=last {
+            distance += (self.points[point_index] - self.points[point_index - 1]).length();
+            params.push(distance);
+        }
+        for param in &mut params {
+            *param /= distance;
+        }
+        params
+    }
+
+    // Finds the handle lengths along the given tangents that minimize the squared distances from
+    // the points to the curve at their parameters.
+    fn least_squares_cubic(&self,
+                           first: usize,
+                           last: usize,
+                           params: &[f32],
+                           left_tangent: Vector2F,
+                           right_tangent: Vector2F)
+                           -> [Vector2F; 4] {
+        let (from, to) = (self.points[first], self.points[last]);
+        let (mut c00, mut c01, mut c11) = (0.0, 0.0, 0.0);
+        let (mut x0, mut x1) = (0.0, 0.0);
+        for (param_index, &t) in params.iter().enumerate() {
+            let [b0, b1, b2, b3] = bernstein(t);
+            let (a0, a1) = (left_tangent * b1, right_tangent * b2);
+            c00 += a0.dot(a0);
+            c01 += a0.dot(a1);
+            c11 += a1.dot(a1);
+            let residual = self.points[first + param_index] - (from * (b0 + b1) + to * (b2 + b3));
+            x0 += a0.dot(residual);
+            x1 += a1.dot(residual);
+        }
+
+        let det = c00 * c11 - c01 * c01;
+        let (mut left_length, mut right_length) = if det == 0.0 {
+            (0.0, 0.0)
+        } else {
+            ((x0 * c11 - x1 * c01) / det, (c00 * x1 - c01 * x0) / det)
+        };
+
+        // Negative or tiny handles give loops or cusps, so fall back to a third of the chord.
+        let chord_length = (to - from).length();
+        let min_length = 1.0e-6 * chord_length;
+        if !(left_length >= min_length && right_length >= min_length) {
+            left_length = chord_length / 3.0;
+            right_length = left_length;
+        }
+        [from, from + left_tangent * left_length, to + right_tangent * right_length, to]
+    }
+
+    // Returns the largest squared distance from an interior point to the curve, and the index of
+    // that point.
+    fn max_error(&self, first: usize, last: usize, curve: &[Vector2F; 4], params: &[f32])
+                 -> (f32, usize) {
+        let (mut max_error, mut split_index) = (0.0, (first + last) / 2);
+        for point_index in (first + 1)..last {
+            let point = self.points[point_index];
+            let error = (evaluate(curve, params[point_index - first]) - point).square_length();
+            if error >= max_error {
+                max_error = error;
+                split_index = point_index;
+            }
+        }
+        (max_error, split_index)
+    }
+}
+
+// Improves the parameter `t` of `point` on `curve` with one step of Newton's method.
+fn newton_raphson_root(curve: &[Vector2F; 4], point: Vector2F, t: f32) -> f32 {
+    let first_derivative = [
+        (curve[1] - curve[0]) * 3.0,
+        (curve[2] - curve[1]) * 3.0,
+        (curve[3] - curve[2]) * 3.0,
+    ];
+    let second_derivative = [
+        (first_derivative[1] - first_derivative[0]) * 2.0,
+        (first_derivative[2] - first_derivative[1]) * 2.0,
+    ];
+
+    let offset = evaluate(curve, t) - point;
+    let s = 1.0 - t;
+    let velocity = first_derivative[0] * (s * s) + first_derivative[1] * (2.0 * s * t) +
+        first_derivative[2] * (t * t);
+    let acceleration = second_derivative[0] * s + second_derivative[1] * t;
+    let denominator = velocity.dot(velocity) + offset.dot(acceleration);
+    if denominator == 0.0 {
+        return t;
+    }
+    (t - offset.dot(velocity) / denominator).clamp(0.0, 1.0)
+}
+
+fn evaluate(curve: &[Vector2F; 4], t: f32) -> Vector2F {
+    let [b0, b1, b2, b3] = bernstein(t);
+    curve[0] * b0 + curve[1] * b1 + curve[2] * b2 + curve[3] * b3
+}
+
+fn bernstein(t: f32) -> [f32; 4] {
+    let s = 1.0 - t;
+    [s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t]
+}
+
+fn direction(from: Vector2F, to: Vector2F) -> Vector2F {
+    let vector = to - from;
+    let length = vector.length();
+    if length == 0.0 {
+        Vector2F::zero()
+    } else {
+        vector * (1.0 / length)
+    }
+}
+
+fn turning_angle(prev: Vector2F, point: Vector2F, next: Vector2F) -> f32 {
+    let (incoming, outgoing) = (point - prev, next - point);
+    f32::atan2(incoming.det(outgoing), incoming.dot(outgoing)).abs()
+}
@@ -12,10 +12,14 @@
 //!
 //! This module also contains various path utilities.
 
+#![cfg_attr(all(test, pf_rustc_nightly), feature(test))]
+
 #[macro_use]
 extern crate bitflags;
 #[macro_use]
 extern crate log;
+#[cfg(all(test, pf_rustc_nightly))]
+extern crate test;
 
 pub mod clip;
 pub mod dash;
@@ -24,6 +28,7 @@ pub mod fill;
 pub mod gradient;
 pub mod orientation;
 pub mod outline;
+pub mod path_data;
 pub mod pattern;
 pub mod render_target;
 pub mod segment;
@@ -32,4 +37,7 @@ pub mod stroke;
 pub mod transform;
 
 mod dilation;
+mod fit;
+mod rasterize;
+mod triangulate;
 mod util;
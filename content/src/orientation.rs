@@ -33,7 +33,7 @@ impl Orientation {
         Orientation::from_area(area)
     }
 
-    fn from_area(area: f32) -> Orientation {
+    pub(crate) fn from_area(area: f32) -> Orientation {
         if area <= 0.0 {
             Orientation::Ccw
         } else {
@@ -10,19 +10,47 @@
 
 //! A compressed in-memory representation of paths.
 
-use crate::clip::{self, ContourPolygonClipper, ContourRectClipper};
+use arrayvec::ArrayVec;
+use crate::clip::{self, BooleanOp, ContourPolygonClipper, ContourRectClipper, OutlineClipper};
+use crate::dash::{self, OutlineDash};
 use crate::dilation::ContourDilator;
+use crate::fit;
+use crate::fill::FillRule;
 use crate::orientation::Orientation;
-use crate::segment::{Segment, SegmentFlags, SegmentKind};
+use crate::path_data::{PathDataParser, PathDataWriter, PathParseError};
+use crate::rasterize;
+use crate::segment::{self, Segment, SegmentFlags, SegmentKind};
+use crate::stroke::{ContourOffsetter, LineJoin};
+use crate::triangulate;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
-use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::unit_vector::UnitVector;
-use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_geometry::util::EPSILON;
+use pathfinder_geometry::vector::{IntoVector2F, Vector2F, vec2f};
+use std::collections::HashMap;
+use std::error::Error;
 use std::f32::consts::PI;
-use std::fmt::{self, Debug, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter;
 use std::mem;
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use serde::de::Error as DeserializeError;
+
+#[cfg(feature = "kurbo")]
+use kurbo::{BezPath, PathEl, Point};
+#[cfg(feature = "lyon")]
+use lyon_path::math::{self as lyon_math, Point as LyonPoint};
+#[cfg(feature = "lyon")]
+use lyon_path::{Path as LyonPath, PathEvent};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 #[derive(Clone)]
 pub struct Outline {
@@ -34,6 +62,8 @@ pub struct Outline {
 pub struct Contour {
     pub(crate) points: Vec<Vector2F>,
     pub(crate) flags: Vec<PointFlags>,
+    // Pushing points only ever grows this, so anything that moves or removes points must call
+    // `recompute_bounds()` afterward.
     pub(crate) bounds: RectF,
     pub(crate) closed: bool,
 }
@@ -61,13 +91,31 @@ impl Outline {
         }
     }
 
+    /// Builds an outline from a sequence of segments.
+    ///
+    /// The iterator's size hint is used to preallocate the points of the first contour. Use
+    /// `from_segments_with_capacity()` to supply more precise hints.
     #[inline]
     pub fn from_segments<I>(segments: I) -> Outline
     where
         I: Iterator<Item = Segment>,
     {
-        let mut outline = Outline::new();
-        let mut current_contour = Contour::new();
+        let point_hint = segments.size_hint().0;
+        Outline::from_segments_with_capacity(segments, 0, point_hint)
+    }
+
+    /// Builds an outline from a sequence of segments, reserving space for `contour_hint`
+    /// contours and for `point_hint` points in the first contour.
+    pub fn from_segments_with_capacity<I>(segments: I, contour_hint: usize, point_hint: usize)
+                                          -> Outline
+    where
+        I: Iterator<Item = Segment>,
+    {
+        let mut outline = Outline {
+            contours: Vec::with_capacity(contour_hint),
+            bounds: RectF::default(),
+        };
+        let mut current_contour = Contour::with_capacity(point_hint);
 
         for segment in segments {
             if segment.flags.contains(SegmentFlags::FIRST_IN_SUBPATH) {
@@ -110,6 +158,36 @@ impl Outline {
         outline
     }
 
+    /// Parses SVG path data, as found in the `d` attribute of a `<path>` element.
+    ///
+    /// Closed subpaths become closed contours.
+    #[inline]
+    pub fn from_svg_path_data(data: &str) -> Result<Outline, PathParseError> {
+        PathDataParser::new(data).parse()
+    }
+
+    /// Writes this outline as compact SVG path data, the inverse of `from_svg_path_data()`.
+    ///
+    /// Coordinates are rounded to `precision` digits after the decimal point, with trailing zeros
+    /// dropped. If `relative` is true, the lowercase commands, relative to the current point, are
+    /// used, which are usually shorter. Command letters that would only repeat the previous
+    /// command are left out.
+    pub fn to_svg_path_data(&self, precision: usize, relative: bool) -> String {
+        PathDataWriter::new(precision, relative).write(self)
+    }
+
+    /// Creates an outline from the glyph with the given ID in a font, or returns `None` if the
+    /// glyph has no outline.
+    ///
+    /// The result is in font units, with y pointing up.
+    #[cfg(feature = "ttf-parser")]
+    pub fn from_ttf_glyph(font: &ttf_parser::Font, glyph_id: ttf_parser::GlyphId)
+                          -> Option<Outline> {
+        let mut builder = GlyphOutlineBuilder::new();
+        font.outline_glyph(glyph_id, &mut builder)?;
+        Some(builder.build())
+    }
+
     #[inline]
     pub fn from_rect(rect: RectF) -> Outline {
         let mut outline = Outline::new();
@@ -117,11 +195,50 @@ impl Outline {
         outline
     }
 
+    /// Creates an outline consisting of a rectangle with rounded corners. See
+    /// `Contour::from_rounded_rect()`.
+    #[inline]
+    pub fn from_rounded_rect(rect: RectF, radii: Vector2F) -> Outline {
+        let mut outline = Outline::new();
+        outline.push_contour(Contour::from_rounded_rect(rect, radii));
+        outline
+    }
+
+    /// Creates an outline consisting of an axis-aligned ellipse. See `Contour::from_ellipse()`.
+    #[inline]
+    pub fn from_ellipse(center: Vector2F, radii: Vector2F) -> Outline {
+        let mut outline = Outline::new();
+        outline.push_contour(Contour::from_ellipse(center, radii));
+        outline
+    }
+
+    /// Creates an outline consisting of a circle.
+    #[inline]
+    pub fn from_circle(center: Vector2F, radius: f32) -> Outline {
+        Outline::from_ellipse(center, Vector2F::splat(radius))
+    }
+
     #[inline]
     pub fn bounds(&self) -> RectF {
         self.bounds
     }
 
+    /// Returns the smallest rectangle that contains the curves of this outline.
+    ///
+    /// `bounds()` includes control points, which may lie outside the curves. This method instead
+    /// finds the extrema of each curve, so it is slower.
+    pub fn tight_bounds(&self) -> RectF {
+        let mut bounds: Option<RectF> = None;
+        for contour in &self.contours {
+            let contour_bounds = contour.tight_bounds();
+            bounds = Some(match bounds {
+                None => contour_bounds,
+                Some(bounds) => bounds.union_rect(contour_bounds),
+            });
+        }
+        bounds.unwrap_or_default()
+    }
+
     #[inline]
     pub fn contours(&self) -> &[Contour] {
         &self.contours
@@ -132,6 +249,61 @@ impl Outline {
         self.contours
     }
 
+    /// Returns true if this outline has no points: either it has no contours or they are all
+    /// empty.
+    pub fn is_empty(&self) -> bool {
+        self.contours.iter().all(|contour| contour.is_empty())
+    }
+
+    /// Returns the total number of points in the contours of this outline, including control
+    /// points.
+    pub fn point_count(&self) -> usize {
+        self.contours.iter().map(|contour| contour.points.len()).sum()
+    }
+
+    /// Returns the total number of endpoints in the contours of this outline. See
+    /// `Contour::endpoint_count()`.
+    pub fn endpoint_count(&self) -> u32 {
+        self.contours.iter().map(|contour| contour.endpoint_count()).sum()
+    }
+
+    /// Returns the total number of segments in the contours of this outline. See
+    /// `Contour::segment_count()`.
+    pub fn segment_count(&self) -> u32 {
+        self.contours.iter().map(|contour| contour.segment_count()).sum()
+    }
+
+    /// Iterates over every point of every contour in turn, including control points, along with
+    /// its index packed into a `PointIndex`.
+    ///
+    /// A `PointIndex` can only address the first 4096 contours and the first 1,048,576 points of
+    /// each, so the iterator panics when it reaches a point beyond those limits rather than
+    /// producing an index that refers to the wrong point.
+    pub fn iter_point_indices(&self) -> impl Iterator<Item = (PointIndex, Vector2F)> + '_ {
+        self.contours.iter().enumerate().flat_map(|(contour_index, contour)| {
+            contour.points.iter().enumerate().map(move |(point_index, &point)| {
+                let index = PointIndex::try_new(contour_index as u32, point_index as u32);
+                (index.unwrap_or_else(|error| panic!("can't index the point: {}", error)), point)
+            })
+        })
+    }
+
+    /// Iterates over the y-monotonic segments of every contour in turn, without modifying the
+    /// outline. See `Contour::monotonic_segments()`.
+    pub fn monotonic_segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.contours.iter().flat_map(|contour| contour.monotonic_segments())
+    }
+
+    /// Returns a copy of this outline in which every curve is monotonic in y. See
+    /// `Contour::to_monotonic()`.
+    pub fn to_monotonic(&self) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.to_monotonic());
+        }
+        outline
+    }
+
     /// Removes all contours from this outline.
     #[inline]
     pub fn clear(&mut self) {
@@ -153,6 +325,24 @@ impl Outline {
         self.contours.push(contour);
     }
 
+    /// Returns true if every point in this outline has finite coordinates.
+    pub fn is_finite(&self) -> bool {
+        self.contours.iter().all(|contour| contour.is_finite())
+    }
+
+    /// Removes points with NaN or infinite coordinates and recomputes the bounds.
+    ///
+    /// See `Contour::sanitize()` for how curves containing such points are handled. Contours that
+    /// become empty are dropped.
+    pub fn sanitize(&mut self) {
+        let contours = mem::take(&mut self.contours);
+        self.bounds = RectF::default();
+        self.extend(contours.into_iter().map(|mut contour| {
+            contour.sanitize();
+            contour
+        }));
+    }
+
     pub fn pop_contour(&mut self) -> Option<Contour> {
         let last_contour = self.contours.pop();
 
@@ -165,763 +355,6776 @@ impl Outline {
         last_contour
     }
 
-    pub fn transform(&mut self, transform: &Transform2F) {
-        if transform.is_identity() {
-            return;
-        }
+    /// Removes the contours for which `f` returns false, such as slivers left over from clipping,
+    /// and recomputes the bounds from the remaining ones.
+    pub fn retain_contours<F>(&mut self, f: F) where F: FnMut(&Contour) -> bool {
+        self.contours.retain(f);
 
         let mut new_bounds = None;
-        for contour in &mut self.contours {
-            contour.transform(transform);
+        for contour in &self.contours {
             contour.update_bounds(&mut new_bounds);
         }
-        self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
+        self.bounds = new_bounds.unwrap_or_default();
     }
 
-    pub fn apply_perspective(&mut self, perspective: &Perspective) {
-        let mut new_bounds = None;
-        for contour in &mut self.contours {
-            contour.apply_perspective(perspective);
-            contour.update_bounds(&mut new_bounds);
+    /// Joins open contours whose endpoints lie within `tolerance` of each other into longer
+    /// contours, such as those left with tiny gaps by boolean operations.
+    ///
+    /// Starting from each open contour in turn, the nearest free endpoint is repeatedly welded on
+    /// at either end, reversing contours where necessary, and the chain is closed if its own ends
+    /// then meet. Closed contours are left alone. Endpoints are found with a hash grid of cells
+    /// `tolerance` wide.
+    pub fn weld_contours(&mut self, tolerance: f32) {
+        let mut grid = EndpointGrid::new(tolerance);
+        for (contour_index, contour) in self.contours.iter().enumerate() {
+            if let (false, Some(last_position)) = (contour.closed, contour.last_position()) {
+                grid.insert(contour_index * 2, contour.position_of(0));
+                grid.insert(contour_index * 2 + 1, last_position);
+            }
         }
-        self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
-    }
 
-    pub fn dilate(&mut self, amount: Vector2F) {
-        let orientation = Orientation::from_outline(self);
-        self.contours
-            .iter_mut()
-            .for_each(|contour| contour.dilate(amount, orientation));
-        self.bounds = self.bounds.dilate(amount);
-    }
+        let mut old_contours = mem::take(&mut self.contours);
+        let mut used = vec![false; old_contours.len()];
+        let mut contours = Vec::with_capacity(old_contours.len());
+        for contour_index in 0..old_contours.len() {
+            if used[contour_index] {
+                continue;
+            }
+            used[contour_index] = true;
+            let mut chain = mem::replace(&mut old_contours[contour_index], Contour::new());
+            if chain.closed || chain.is_empty() {
+                contours.push(chain);
+                continue;
+            }
 
-    pub fn prepare_for_tiling(&mut self, view_box: RectF) {
-        self.contours
-            .iter_mut()
-            .for_each(|contour| contour.prepare_for_tiling(view_box));
-        self.bounds = self
-            .bounds
-            .intersection(view_box)
-            .unwrap_or_else(|| RectF::default());
-    }
+            // Extend the end of the chain, then its start by way of reversing it twice.
+            for _ in 0..2 {
+                while let Some(endpoint) = grid.nearest(chain.last_position().unwrap(), &used) {
+                    used[endpoint / 2] = true;
+                    let mut other = mem::replace(&mut old_contours[endpoint / 2], Contour::new());
+                    if endpoint % 2 == 1 {
+                        other.reverse();
+                    }
+                    chain.join(&other, tolerance);
+                }
+                chain.reverse();
+            }
 
-    pub fn is_outside_polygon(&self, clip_polygon: &[Vector2F]) -> bool {
-        clip::rect_is_outside_polygon(self.bounds, clip_polygon)
-    }
+            let (first_position, last_position) = (chain.position_of(0), chain.last_position());
+            if let Some(last_position) = last_position {
+                if chain.len() > 2 && (last_position - first_position).length() <= tolerance {
+                    let last_index = chain.len() - 1;
+                    chain.points[last_index as usize] = first_position;
+                    chain.recompute_bounds();
+                    chain.close();
+                }
+            }
+            contours.push(chain);
+        }
 
-    fn is_inside_polygon(&self, clip_polygon: &[Vector2F]) -> bool {
-        clip::rect_is_inside_polygon(self.bounds, clip_polygon)
+        self.bounds = RectF::default();
+        self.extend(contours);
     }
 
-    pub fn clip_against_polygon(&mut self, clip_polygon: &[Vector2F]) {
-        // Quick check.
-        if self.is_inside_polygon(clip_polygon) {
+    /// Moves all contours from `other` into this outline, unioning the bounds of the two.
+    pub fn append(&mut self, other: Outline) {
+        if other.contours.is_empty() {
             return;
         }
 
-        for contour in mem::replace(&mut self.contours, vec![]) {
-            self.push_contour(ContourPolygonClipper::new(clip_polygon, contour).clip());
+        if self.contours.is_empty() {
+            *self = other;
+            return;
         }
+
+        self.bounds = self.bounds.union_rect(other.bounds);
+        self.contours.extend(other.contours);
     }
 
-    pub fn clip_against_rect(&mut self, clip_rect: RectF) {
-        if clip_rect.contains_rect(self.bounds) {
-            return;
+    /// Adds contours to this outline, updating the bounds incrementally.
+    pub fn extend<I>(&mut self, contours: I) where I: IntoIterator<Item = Contour> {
+        let mut new_bounds = if self.contours.is_empty() { None } else { Some(self.bounds) };
+        for contour in contours {
+            if contour.is_empty() {
+                continue;
+            }
+            contour.update_bounds(&mut new_bounds);
+            self.contours.push(contour);
         }
+        self.bounds = new_bounds.unwrap_or_default();
+    }
 
-        for contour in mem::replace(&mut self.contours, vec![]) {
-            self.push_contour(ContourRectClipper::new(clip_rect, contour).clip());
+    pub fn transform(&mut self, transform: &Transform2F) {
+        if transform.is_identity() {
+            return;
         }
-    }
 
-    #[inline]
-    pub fn close_all_contours(&mut self) {
-        self.contours.iter_mut().for_each(|contour| contour.close());
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.transform(transform);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
     }
-}
 
-impl Debug for Outline {
-    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        for (contour_index, contour) in self.contours.iter().enumerate() {
-            if contour_index > 0 {
-                write!(formatter, " ")?;
-            }
-            contour.fmt(formatter)?;
+    /// Like `transform()`, but transforms the contours in parallel on the rayon thread pool.
+    ///
+    /// The result is identical to that of `transform()`. This is only worthwhile for outlines
+    /// with many contours, such as a page of text.
+    #[cfg(feature = "rayon")]
+    pub fn par_transform(&mut self, transform: &Transform2F) {
+        if transform.is_identity() {
+            return;
         }
-        Ok(())
+
+        self.bounds = self.contours.par_iter_mut().map(|contour| {
+            contour.transform(transform);
+            contour.bounds
+        }).reduce_with(|a, b| a.union_rect(b)).unwrap_or_default();
     }
-}
 
-impl Contour {
-    #[inline]
-    pub fn new() -> Contour {
-        Contour {
-            points: vec![],
-            flags: vec![],
-            bounds: RectF::default(),
-            closed: false,
+    /// Returns a transformed copy of this outline, leaving this one untouched.
+    pub fn transformed(&self, transform: &Transform2F) -> Outline {
+        let mut contours = Vec::with_capacity(self.contours.len());
+        let mut new_bounds = None;
+        for contour in &self.contours {
+            let contour = contour.transformed(transform);
+            contour.update_bounds(&mut new_bounds);
+            contours.push(contour);
         }
+        Outline { contours, bounds: new_bounds.unwrap_or_default() }
     }
 
-    #[inline]
-    pub fn with_capacity(length: usize) -> Contour {
-        Contour {
-            points: Vec::with_capacity(length),
-            flags: Vec::with_capacity(length),
-            bounds: RectF::default(),
-            closed: false,
-        }
+    /// Applies the affine matrix `[a, b, c, d, e, f]`, laid out as in SVG and the canvas API: each
+    /// point (x, y) moves to (a·x + c·y + e, b·x + d·y + f).
+    pub fn transform_matrix(&mut self, m: &[f32; 6]) {
+        let [a, b, c, d, e, f] = *m;
+        self.transform(&Transform2F {
+            matrix: Matrix2x2F::row_major(a, c, b, d),
+            vector: vec2f(e, f),
+        });
     }
 
-    #[inline]
-    pub fn from_rect(rect: RectF) -> Contour {
-        let mut contour = Contour::new();
-        contour.push_point(rect.origin(), PointFlags::empty(), false);
-        contour.push_point(rect.upper_right(), PointFlags::empty(), false);
-        contour.push_point(rect.lower_right(), PointFlags::empty(), false);
-        contour.push_point(rect.lower_left(), PointFlags::empty(), false);
-        contour.close();
-        contour.bounds = rect;
-        contour
+    /// Applies `transform` with `pivot` as the origin: the outline is translated by `-pivot`,
+    /// transformed, and translated back.
+    pub fn transform_about(&mut self, transform: &Transform2F, pivot: Vector2F) {
+        let transform = Transform2F::from_translation(pivot) *
+            *transform *
+            Transform2F::from_translation(-pivot);
+        self.transform(&transform)
     }
 
-    // Replaces this contour with a new one, with arrays preallocated to match `self`.
+    /// Rotates this outline by `theta` radians about `pivot`.
     #[inline]
-    pub(crate) fn take(&mut self) -> Contour {
-        let length = self.len() as usize;
-        mem::replace(
-            self,
-            Contour {
-                points: Vec::with_capacity(length),
-                flags: Vec::with_capacity(length),
-                bounds: RectF::default(),
-                closed: false,
-            },
-        )
+    pub fn rotate_about(&mut self, theta: f32, pivot: Vector2F) {
+        self.transform_about(&Transform2F::from_rotation(theta), pivot)
     }
 
-    /// restore self to the state of Contour::new(), but keep the points buffer allocated
+    /// Mirrors this outline across the y axis by negating every x coordinate.
+    ///
+    /// Mirroring reverses the orientation of every contour, negating its signed area. This does not
+    /// change how the outline fills under either fill rule; call `reverse()` afterward if the
+    /// original orientation matters.
     #[inline]
-    pub fn clear(&mut self) {
-        self.points.clear();
-        self.flags.clear();
-        self.bounds = RectF::default();
-        self.closed = false;
+    pub fn flip_x(&mut self) {
+        self.scale(vec2f(-1.0, 1.0))
     }
 
+    /// Mirrors this outline across the x axis by negating every y coordinate. See `flip_x()`.
     #[inline]
-    pub fn iter(&self, flags: ContourIterFlags) -> ContourIter {
-        ContourIter {
-            contour: self,
-            index: 1,
-            flags,
-        }
+    pub fn flip_y(&mut self) {
+        self.scale(vec2f(1.0, -1.0))
     }
 
+    /// Mirrors this outline across the vertical line at `axis_x`. See `flip_x()`.
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.points.is_empty()
+    pub fn flip_about_x(&mut self, axis_x: f32) {
+        self.transform_about(&Transform2F::from_scale(vec2f(-1.0, 1.0)), vec2f(axis_x, 0.0))
     }
 
+    /// Mirrors this outline across the horizontal line at `axis_y`. See `flip_x()`.
     #[inline]
-    pub fn len(&self) -> u32 {
-        self.points.len() as u32
+    pub fn flip_about_y(&mut self, axis_y: f32) {
+        self.transform_about(&Transform2F::from_scale(vec2f(1.0, -1.0)), vec2f(0.0, axis_y))
     }
 
+    /// Rotates this outline by `theta` radians about the origin.
     #[inline]
-    pub fn bounds(&self) -> RectF {
-        self.bounds
+    pub fn rotate(&mut self, theta: f32) {
+        self.transform(&Transform2F::from_rotation(theta))
     }
 
+    /// Scales this outline about the origin.
     #[inline]
-    pub fn is_closed(&self) -> bool {
-        self.closed
+    pub fn scale<S>(&mut self, scale: S) where S: IntoVector2F {
+        self.transform(&Transform2F::from_scale(scale))
     }
 
     #[inline]
-    pub fn position_of(&self, index: u32) -> Vector2F {
-        self.points[index as usize]
+    pub fn translate(&mut self, vector: Vector2F) {
+        self.transform(&Transform2F::from_translation(vector))
     }
 
-    #[inline]
-    pub fn last_position(&self) -> Option<Vector2F> {
-        self.points.last().cloned()
+    /// Scales and translates this outline so that its bounds fill `target`.
+    ///
+    /// If `preserve_aspect` is true, both axes are scaled by the same amount, the largest that
+    /// still fits, and the outline is centered in `target`. An outline with no width or height is
+    /// only moved along that axis. An empty outline is left alone.
+    pub fn fit_to_rect(&mut self, target: RectF, preserve_aspect: bool) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (bounds_size, target_size) = (self.bounds.size(), target.size());
+        let axis_scale = |target_length: f32, length: f32| {
+            if length > 0.0 { target_length / length } else { f32::INFINITY }
+        };
+        let (mut scale_x, mut scale_y) = (axis_scale(target_size.x(), bounds_size.x()),
+                                          axis_scale(target_size.y(), bounds_size.y()));
+        if preserve_aspect {
+            scale_x = scale_x.min(scale_y);
+            scale_y = scale_x;
+        }
+        let finite_or_one = |scale: f32| if scale.is_finite() { scale } else { 1.0 };
+        let scale = vec2f(finite_or_one(scale_x), finite_or_one(scale_y));
+
+        let transform = Transform2F::from_translation(target.center()) *
+            Transform2F::from_scale(scale) *
+            Transform2F::from_translation(-self.bounds.center());
+        self.transform(&transform);
     }
 
-    #[inline]
-    pub(crate) fn position_of_last(&self, index: u32) -> Vector2F {
-        self.points[self.points.len() - index as usize]
+    pub fn apply_perspective(&mut self, perspective: &Perspective) {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.apply_perspective(perspective);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_else(|| RectF::default());
     }
 
-    #[inline]
-    pub fn push_endpoint(&mut self, point: Vector2F) {
-        self.push_point(point, PointFlags::empty(), true);
+    /// Undoes `apply_perspective()` with the same perspective, mapping window coordinates back to
+    /// the original points.
+    ///
+    /// Returns false and leaves this outline unchanged if the perspective can't be inverted. See
+    /// `Perspective::inverse()`.
+    pub fn apply_perspective_inverse(&mut self, perspective: &Perspective) -> bool {
+        match perspective.inverse() {
+            None => false,
+            Some(inverse) => {
+                self.apply_perspective(&inverse);
+                true
+            }
+        }
     }
 
-    #[inline]
-    pub fn push_quadratic(&mut self, ctrl: Vector2F, point: Vector2F) {
-        self.push_point(ctrl, PointFlags::CONTROL_POINT_0, true);
-        self.push_point(point, PointFlags::empty(), true);
+    /// Rounds the coordinates of every point in this outline to the nearest multiple of `grid`.
+    /// See `Contour::quantize()`.
+    pub fn quantize(&mut self, grid: f32) {
+        if !(grid > 0.0 && grid.is_finite()) {
+            return;
+        }
+        self.map_points_mut(|point| quantize_point(point, grid));
     }
 
-    #[inline]
-    pub fn push_cubic(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, point: Vector2F) {
-        self.push_point(ctrl0, PointFlags::CONTROL_POINT_0, true);
-        self.push_point(ctrl1, PointFlags::CONTROL_POINT_1, true);
-        self.push_point(point, PointFlags::empty(), true);
+    /// Replaces every point of this outline, including control points, with the result of
+    /// calling `f` on it, and updates the bounds to match. See `Contour::map_points_mut()`.
+    pub fn map_points_mut<F>(&mut self, mut f: F) where F: FnMut(Vector2F) -> Vector2F {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.map_points_mut(&mut f);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_default();
     }
 
-    #[inline]
-    pub fn close(&mut self) {
-        self.closed = true;
+    /// Returns the signed area of this outline: the sum of the signed areas of its contours.
+    ///
+    /// See `Contour::area()` for the sign convention.
+    pub fn area(&self) -> f32 {
+        self.contours.iter().map(|contour| contour.area()).sum()
     }
 
-    #[inline]
-    pub(crate) fn push_point(&mut self,
-                             point: Vector2F,
-                             flags: PointFlags,
-                             update_bounds: bool) {
-        debug_assert!(!point.x().is_nan() && !point.y().is_nan());
+    /// Returns the centroid of the region enclosed by this outline.
+    ///
+    /// Contour centroids are weighted by their signed areas, so holes that wind opposite to their
+    /// enclosing contours are subtracted. If the total area is zero, the average of the endpoints
+    /// is returned instead.
+    pub fn centroid(&self) -> Vector2F {
+        let (mut area, mut moment) = (0.0, Vector2F::zero());
+        for contour in &self.contours {
+            let (contour_area, contour_moment) = contour.area_and_first_moment();
+            area += contour_area;
+            moment += contour_moment;
+        }
+        if area.abs() > EPSILON * EPSILON {
+            return moment * (1.0 / area);
+        }
 
-        if update_bounds {
-            let first = self.is_empty();
-            union_rect(&mut self.bounds, point, first);
-        }
+        average_of_endpoints(self.contours.iter())
+    }
 
-        self.points.push(point);
-        self.flags.push(flags);
+    /// Returns the total length of all contours in this outline.
+    pub fn length(&self) -> f32 {
+        self.contours.iter().map(|contour| contour.length()).sum()
     }
 
-    #[inline]
-    pub(crate) fn push_segment(&mut self, segment: &Segment, flags: PushSegmentFlags) {
-        if segment.is_none() {
-            return;
+    /// Returns the part of this outline between `start` and `end`, given as fractions of its total
+    /// length, as open contours. This is the "trim paths" effect of animation tools.
+    ///
+    /// The contours are laid end to end in order, and each one that overlaps the range contributes
+    /// the piece inside it. If `start` is greater than `end`, the range wraps around from the end
+    /// of the outline to its start; a closed contour that holds both ends of the range yields a
+    /// single piece running across its first point.
+    pub fn trim(&self, start: f32, end: f32) -> Outline {
+        let mut outline = Outline::new();
+        let total_length = self.length();
+        if total_length <= 0.0 {
+            return outline;
         }
 
-        let update_bounds = flags.contains(PushSegmentFlags::UPDATE_BOUNDS);
-        self.push_point(segment.baseline.from(), PointFlags::empty(), update_bounds);
+        let start = start.clamp(0.0, 1.0) * total_length;
+        let end = end.clamp(0.0, 1.0) * total_length;
+        let ranges = if start <= end {
+            vec![(start, end)]
+        } else {
+            vec![(start, total_length), (0.0, end)]
+        };
 
-        if !segment.is_line() {
-            self.push_point(
-                segment.ctrl.from(),
-                PointFlags::CONTROL_POINT_0,
-                update_bounds,
-            );
-            if !segment.is_quadratic() {
-                self.push_point(
-                    segment.ctrl.to(),
-                    PointFlags::CONTROL_POINT_1,
-                    update_bounds,
-                );
+        let mut contour_start = 0.0;
+        for contour in &self.contours {
+            let contour_end = contour_start + contour.length();
+            let mut pieces = vec![];
+            for &(from, to) in &ranges {
+                if from < to && from < contour_end && to > contour_start {
+                    pieces.push(contour.trimmed(from - contour_start, to - contour_start));
+                }
+            }
+            // The first piece runs to the end of the contour and the second from its start.
+            if pieces.len() == 2 && contour.closed {
+                let head = pieces.pop().unwrap();
+                pieces[0].join(&head, EPSILON);
             }
+            for piece in pieces {
+                if !piece.is_empty() {
+                    outline.push_contour(piece);
+                }
+            }
+            contour_start = contour_end;
         }
-
-        self.push_point(segment.baseline.to(), PointFlags::empty(), update_bounds);
+        outline
     }
 
-    pub fn push_arc(&mut self,
-                    transform: &Transform2F,
-                    start_angle: f32,
-                    end_angle: f32,
-                    direction: ArcDirection) {
-        if end_angle - start_angle >= PI * 2.0 {
-            self.push_ellipse(transform);
-        } else {
-            let start = vec2f(start_angle.cos(), start_angle.sin());
-            let end   = vec2f(end_angle.cos(),   end_angle.sin());
-            self.push_arc_from_unit_chord(transform, LineSegment2F::new(start, end), direction);
+    /// Returns a copy of this outline with every curve replaced by line segments that deviate from
+    /// it by no more than `tolerance`.
+    pub fn flatten(&self, tolerance: f32) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            let mut flattened_contour = Contour::with_capacity(contour.len() as usize);
+            contour.flatten_into(tolerance, &mut flattened_contour);
+            outline.push_contour(flattened_contour);
         }
+        outline
     }
 
-    pub fn push_arc_from_unit_chord(&mut self,
-                                    transform: &Transform2F,
-                                    mut chord: LineSegment2F,
-                                    direction: ArcDirection) {
-        let mut direction_transform = Transform2F::default();
-        if direction == ArcDirection::CCW {
-            chord *= vec2f(1.0, -1.0);
-            direction_transform = Transform2F::from_scale(vec2f(1.0, -1.0));
-        }
-
-        let (mut vector, end_vector) = (UnitVector(chord.from()), UnitVector(chord.to()));
-        for segment_index in 0..4 {
-            debug!("push_arc_from_unit_chord(): loop segment index {}", segment_index);
-
-            let mut sweep_vector = end_vector.rev_rotate_by(vector);
-            let last = sweep_vector.0.x() >= -EPSILON && sweep_vector.0.y() >= -EPSILON;
-            debug!("... end_vector={:?} vector={:?} sweep_vector={:?} last={:?}",
-                   end_vector,
-                   vector,
-                   sweep_vector,
-                   last);
-
-            let mut segment;
-            if !last {
-                sweep_vector = UnitVector(vec2f(0.0, 1.0));
-                segment = Segment::quarter_circle_arc();
-            } else {
-                segment = Segment::arc_from_cos(sweep_vector.0.x());
-            }
-
-            let half_sweep_vector = sweep_vector.halve_angle();
-            let rotation = Transform2F::from_rotation_vector(half_sweep_vector.rotate_by(vector));
-            segment = segment.transform(&(*transform * direction_transform * rotation));
-
-            let mut push_segment_flags = PushSegmentFlags::UPDATE_BOUNDS;
-            if segment_index == 0 {
-                push_segment_flags.insert(PushSegmentFlags::INCLUDE_FROM_POINT);
-            }
-            self.push_segment(&segment, push_segment_flags);
-
-            if last {
-                break;
-            }
-
-            vector = vector.rotate_by(sweep_vector);
+    /// Returns a copy of this outline with every quadratic curve elevated to a cubic, keeping lines
+    /// as they are. See `Contour::to_cubic()`.
+    pub fn to_cubic(&self) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.to_cubic());
         }
-
-        const EPSILON: f32 = 0.001;
+        outline
     }
 
-    pub fn push_ellipse(&mut self, transform: &Transform2F) {
-        let segment = Segment::quarter_circle_arc();
-        let mut rotation;
-        self.push_segment(&segment.transform(transform),
-                          PushSegmentFlags::UPDATE_BOUNDS | PushSegmentFlags::INCLUDE_FROM_POINT);
-        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f( 0.0,  1.0)));
-        self.push_segment(&segment.transform(&(*transform * rotation)),
-                          PushSegmentFlags::UPDATE_BOUNDS);
-        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f(-1.0,  0.0)));
-        self.push_segment(&segment.transform(&(*transform * rotation)),
-                          PushSegmentFlags::UPDATE_BOUNDS);
-        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f( 0.0, -1.0)));
-        self.push_segment(&segment.transform(&(*transform * rotation)),
-                          PushSegmentFlags::UPDATE_BOUNDS);
+    /// Returns a copy of this outline in which every segment, lines included, is a cubic curve. See
+    /// `Contour::to_cubics()`.
+    pub fn to_cubics(&self) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.to_cubics());
+        }
+        outline
     }
 
-    #[inline]
-    pub fn segment_after(&self, point_index: u32) -> Segment {
-        debug_assert!(self.point_is_endpoint(point_index));
-
-        let mut segment = Segment::none();
-        segment.baseline.set_from(self.position_of(point_index));
-
-        let point1_index = self.add_to_point_index(point_index, 1);
-        if self.point_is_endpoint(point1_index) {
-            segment.baseline.set_to(self.position_of(point1_index));
-            segment.kind = SegmentKind::Line;
-        } else {
-            segment.ctrl.set_from(self.position_of(point1_index));
-
-            let point2_index = self.add_to_point_index(point_index, 2);
-            if self.point_is_endpoint(point2_index) {
-                segment.baseline.set_to(self.position_of(point2_index));
-                segment.kind = SegmentKind::Quadratic;
-            } else {
-                segment.ctrl.set_to(self.position_of(point2_index));
-                segment.kind = SegmentKind::Cubic;
-
-                let point3_index = self.add_to_point_index(point_index, 3);
-                segment.baseline.set_to(self.position_of(point3_index));
-            }
+    /// Returns a copy of this outline in which every segment has been split until no piece is
+    /// longer than `max_length`. See `Contour::subdivide_to_max_length()`.
+    pub fn subdivide_to_max_length(&self, max_length: f32) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.subdivide_to_max_length(max_length));
         }
-
-        segment
+        outline
     }
 
-    #[inline]
-    pub fn hull_segment_after(&self, prev_point_index: u32) -> LineSegment2F {
-        let next_point_index = self.next_point_index_of(prev_point_index);
-        LineSegment2F::new(
-            self.points[prev_point_index as usize],
-            self.points[next_point_index as usize],
-        )
+    /// Returns a copy of this outline with every cubic curve approximated by quadratics. See
+    /// `Contour::to_quadratic()`.
+    pub fn to_quadratic(&self, tolerance: f32) -> Outline {
+        let mut outline = Outline::new();
+        for contour in &self.contours {
+            outline.push_contour(contour.to_quadratic(tolerance));
+        }
+        outline
     }
 
-    #[inline]
-    pub fn point_is_endpoint(&self, point_index: u32) -> bool {
-        !self.flags[point_index as usize]
-            .intersects(PointFlags::CONTROL_POINT_0 | PointFlags::CONTROL_POINT_1)
+    /// Cuts every contour of this outline into dashes. See `Contour::dash()`.
+    pub fn dash(&self, pattern: &[f32], phase: f32) -> Outline {
+        let mut dash = OutlineDash::new(self, pattern, phase);
+        dash.dash();
+        dash.into_outline()
     }
 
-    #[inline]
-    pub fn add_to_point_index(&self, point_index: u32, addend: u32) -> u32 {
-        let (index, limit) = (point_index + addend, self.len());
-        if index >= limit {
-            index - limit
-        } else {
-            index
+    /// Returns the convex hull of the endpoints of this outline as a closed contour that winds
+    /// clockwise in a y-down coordinate system, using Andrew's monotone chain algorithm.
+    ///
+    /// If `include_control_points` is true, control points are included too, so the hull contains
+    /// every curve. If the points don't span a polygon, because there are fewer than three
+    /// distinct points or they are all collinear, the extreme points are returned as an open
+    /// contour instead. Points with infinite or NaN coordinates are ignored.
+    pub fn convex_hull(&self, include_control_points: bool) -> Contour {
+        let mut points: Vec<Vector2F> = self.contours.iter().flat_map(|contour| {
+            contour.points.iter().zip(contour.flags.iter()).filter(|&(point, flags)| {
+                (include_control_points || flags.is_empty()) &&
+                    point.x().is_finite() && point.y().is_finite()
+            }).map(|(&point, _)| point)
+        }).collect();
+        points.sort_by(|a, b| a.x().total_cmp(&b.x()).then(a.y().total_cmp(&b.y())));
+        points.dedup();
+
+        let mut hull = Contour::new();
+        if points.len() < 3 {
+            points.into_iter().for_each(|point| hull.push_endpoint(point));
+            return hull;
         }
-    }
 
-    #[inline]
-    pub fn point_is_logically_above(&self, a: u32, b: u32) -> bool {
-        let (a_y, b_y) = (self.points[a as usize].y(), self.points[b as usize].y());
-        a_y < b_y || (a_y == b_y && a < b)
-    }
+        // Build the lower hull left to right, then the upper hull right to left. Each ends where
+        // the other begins, so drop the last point of each.
+        let mut hull_points: Vec<Vector2F> = Vec::with_capacity(points.len() + 1);
+        extend_convex_chain(&mut hull_points, points.iter().cloned());
+        hull_points.pop();
+        extend_convex_chain(&mut hull_points, points.iter().rev().cloned());
+        hull_points.pop();
 
-    #[inline]
-    pub fn prev_endpoint_index_of(&self, mut point_index: u32) -> u32 {
-        loop {
-            point_index = self.prev_point_index_of(point_index);
-            if self.point_is_endpoint(point_index) {
-                return point_index;
-            }
+        let is_degenerate = hull_points.len() < 3;
+        hull_points.into_iter().for_each(|point| hull.push_endpoint(point));
+        if !is_degenerate {
+            hull.close();
         }
+        hull
     }
 
-    #[inline]
-    pub fn next_endpoint_index_of(&self, mut point_index: u32) -> u32 {
-        loop {
-            point_index = self.next_point_index_of(point_index);
-            if self.point_is_endpoint(point_index) {
-                return point_index;
+    /// Returns the center and radius of the smallest circle containing every point of this
+    /// outline, including control points, so that it contains the curves too.
+    ///
+    /// This uses Welzl's algorithm. The points are shuffled with a fixed seed, so identical
+    /// outlines always produce identical circles. An empty outline returns a circle of radius
+    /// zero at the origin.
+    pub fn bounding_circle(&self) -> (Vector2F, f32) {
+        let mut points: Vec<Vector2F> = self.contours.iter().flat_map(|contour| {
+            contour.points.iter().cloned()
+        }).collect();
+        if points.is_empty() {
+            return (Vector2F::zero(), 0.0);
+        }
+
+        // Fisher-Yates with a xorshift generator, which keeps the expected running time linear
+        // without making the result depend on a random seed.
+        let mut state: u32 = 0x9e37_79b9;
+        for point_index in (1..points.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            points.swap(point_index, state as usize % (point_index + 1));
+        }
+
+        let mut circle = (points[0], 0.0);
+        for i in 1..points.len() {
+            if circle_contains_point(circle, points[i]) {
+                continue;
+            }
+            circle = (points[i], 0.0);
+            for j in 0..i {
+                if circle_contains_point(circle, points[j]) {
+                    continue;
+                }
+                circle = circle_from_diameter(points[i], points[j]);
+                for k in 0..j {
+                    if !circle_contains_point(circle, points[k]) {
+                        circle = circle_through_points(points[i], points[j], points[k]);
+                    }
+                }
             }
         }
+        circle
     }
 
-    #[inline]
-    pub fn prev_point_index_of(&self, point_index: u32) -> u32 {
-        if point_index == 0 {
-            self.len() - 1
-        } else {
-            point_index - 1
+    /// Finds the point on this outline closest to `query`.
+    ///
+    /// Returns the index of the contour and of the endpoint that starts the nearest segment, the
+    /// nearest point itself, and its distance from `query`, or `None` if the outline is empty.
+    /// Contours whose bounds are farther away than the best point so far are skipped.
+    pub fn nearest_point(&self, query: Vector2F) -> Option<(PointIndex, Vector2F, f32)> {
+        let mut nearest: Option<(PointIndex, Vector2F, f32)> = None;
+        for (contour_index, contour) in self.contours.iter().enumerate() {
+            if let Some((_, _, best_distance)) = nearest {
+                if distance_to_rect(query, contour.bounds) >= best_distance {
+                    continue;
+                }
+            }
+            if let Some((point_index, point, distance)) = contour.nearest_point(query) {
+                let is_nearer = match nearest {
+                    None => true,
+                    Some((_, _, best_distance)) => distance < best_distance,
+                };
+                if is_nearer {
+                    let point_index = PointIndex::new(contour_index as u32, point_index);
+                    nearest = Some((point_index, point, distance));
+                }
+            }
         }
+        nearest
     }
 
-    #[inline]
-    pub fn next_point_index_of(&self, point_index: u32) -> u32 {
-        if point_index == self.len() - 1 {
-            0
-        } else {
-            point_index + 1
+    /// Returns true if the given point is inside this outline when filled with the given rule.
+    ///
+    /// Points lying on the outline itself are considered to be inside.
+    pub fn contains_point(&self, point: Vector2F, fill_rule: FillRule) -> bool {
+        if !self.bounds.contains_point(point) {
+            return false;
         }
-    }
 
-    pub fn transform(&mut self, transform: &Transform2F) {
-        if transform.is_identity() {
-            return;
+        let mut winding_number = 0;
+        for contour in &self.contours {
+            match contour.winding_number(point) {
+                None => return true,
+                Some(contour_winding_number) => winding_number += contour_winding_number,
+            }
         }
 
-        for (point_index, point) in self.points.iter_mut().enumerate() {
-            *point = *transform * *point;
-            union_rect(&mut self.bounds, *point, point_index == 0);
+        match fill_rule {
+            FillRule::Winding => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
         }
     }
 
-    pub fn apply_perspective(&mut self, perspective: &Perspective) {
-        for (point_index, point) in self.points.iter_mut().enumerate() {
-            *point = *perspective * *point;
-            union_rect(&mut self.bounds, *point, point_index == 0);
+    /// Returns the distance from `point` to the nearest point on this outline, negated if `point`
+    /// is inside the outline under the nonzero fill rule.
+    ///
+    /// This is a signed distance field, suitable for checking the coverage that a rasterizer
+    /// computes near edges. Points on the outline itself are at distance zero. An empty outline is
+    /// infinitely far away.
+    pub fn signed_distance(&self, point: Vector2F) -> f32 {
+        let distance = match self.nearest_point(point) {
+            None => return f32::INFINITY,
+            Some((_, _, distance)) => distance,
+        };
+        if self.contains_point(point, FillRule::Winding) {
+            -distance
+        } else {
+            distance
         }
     }
 
-    pub fn dilate(&mut self, amount: Vector2F, orientation: Orientation) {
-        ContourDilator::new(self, amount, orientation).dilate();
-        self.bounds = self.bounds.dilate(amount);
+    /// Reverses the direction of every contour in this outline.
+    ///
+    /// This flips the sign of the signed area of each contour, which turns holes into solids and
+    /// vice versa under the nonzero fill rule. The bounds are unchanged.
+    pub fn reverse(&mut self) {
+        self.contours.iter_mut().for_each(|contour| contour.reverse());
     }
 
-    fn prepare_for_tiling(&mut self, view_box: RectF) {
-        // Snap points to the view box bounds. This mops up floating point error from the clipping
-        // process.
-        let (mut last_endpoint_index, mut contour_is_monotonic) = (None, true);
-        for point_index in 0..(self.points.len() as u32) {
-            if contour_is_monotonic {
-                if self.point_is_endpoint(point_index) {
-                    if let Some(last_endpoint_index) = last_endpoint_index {
-                        if !self.curve_with_endpoints_is_monotonic(last_endpoint_index,
-                                                                   point_index) {
-                            contour_is_monotonic = false;
-                        }
-                    }
-                    last_endpoint_index = Some(point_index);
-                }
+    /// Reverses contours as necessary so that outer contours wind in the `outer` direction and
+    /// contours nested inside them wind in the opposite direction.
+    ///
+    /// A contour nested inside an odd number of other contours is treated as a hole. The result
+    /// fills the same region under the nonzero fill rule as the original did under the even-odd
+    /// rule.
+    pub fn fix_winding(&mut self, outer: Orientation) {
+        let depths = self.nesting_depths();
+        for (contour, depth) in self.contours.iter_mut().zip(depths) {
+            if contour.area() == 0.0 {
+                continue;
+            }
+            let wants_outer = depth % 2 == 0;
+            if (contour.orientation() == outer) != wants_outer {
+                contour.reverse();
             }
         }
+    }
 
-        // Convert to monotonic, if necessary.
-        if !contour_is_monotonic {
-            self.make_monotonic();
-        }
+    pub fn dilate(&mut self, amount: Vector2F) {
+        let orientation = Orientation::from_outline(self);
+        self.contours
+            .iter_mut()
+            .for_each(|contour| contour.dilate(amount, orientation));
+        self.bounds = self.bounds.dilate(amount);
+    }
 
-        // Update bounds.
+    pub fn prepare_for_tiling(&mut self, view_box: RectF) {
+        // Reuse one scratch contour for all the contours so that monotonic conversion doesn't
+        // allocate fresh arrays each time.
+        let mut scratch = Contour::new();
+        self.contours
+            .iter_mut()
+            .for_each(|contour| contour.prepare_for_tiling(view_box, &mut scratch));
         self.bounds = self
             .bounds
             .intersection(view_box)
             .unwrap_or_else(|| RectF::default());
     }
 
-    fn make_monotonic(&mut self) {
-        debug!("--- make_monotonic() ---");
-
-        let contour = self.take();
-        self.bounds = contour.bounds;
+    pub fn is_outside_polygon(&self, clip_polygon: &[Vector2F]) -> bool {
+        clip::rect_is_outside_polygon(self.bounds, clip_polygon)
+    }
 
-        let mut last_endpoint_index = None;
-        let input_point_count = contour.points.len() as u32;
-        for point_index in 0..(input_point_count + 1) {
-            if point_index < input_point_count && !contour.point_is_endpoint(point_index) {
-                continue;
-            }
+    fn is_inside_polygon(&self, clip_polygon: &[Vector2F]) -> bool {
+        clip::rect_is_inside_polygon(self.bounds, clip_polygon)
+    }
 
-            if let Some(last_endpoint_index) = last_endpoint_index {
-                let position_index = if point_index == input_point_count {
-                    0
-                } else {
-                    point_index
-                };
-                let baseline = LineSegment2F::new(
-                    contour.points[last_endpoint_index as usize],
-                    contour.points[position_index as usize],
-                );
-                let point_count = point_index - last_endpoint_index + 1;
-                if point_count == 3 {
-                    let ctrl_point_index = last_endpoint_index as usize + 1;
-                    let ctrl_position = &contour.points[ctrl_point_index];
-                    handle_cubic(
-                        self,
-                        &Segment::quadratic(baseline, *ctrl_position).to_cubic(),
-                    );
-                } else if point_count == 4 {
-                    let first_ctrl_point_index = last_endpoint_index as usize + 1;
-                    let ctrl_position_0 = &contour.points[first_ctrl_point_index + 0];
-                    let ctrl_position_1 = &contour.points[first_ctrl_point_index + 1];
-                    let ctrl = LineSegment2F::new(*ctrl_position_0, *ctrl_position_1);
-                    handle_cubic(self, &Segment::cubic(baseline, ctrl));
-                }
+    /// Clips this outline to the convex polygon `clip_polygon`, whose points must run in the same
+    /// direction as those of `from_rect()`.
+    ///
+    /// Each contour keeps its orientation, so holes remain holes.
+    ///
+    /// Contours that end up entirely outside are removed, as are closed contours that are reduced
+    /// to slivers with an area below `EPSILON`, such as those that only touch an edge.
+    pub fn clip_against_polygon(&mut self, clip_polygon: &[Vector2F]) {
+        // Quick check.
+        if self.is_inside_polygon(clip_polygon) {
+            return;
+        }
 
-                self.push_point(
-                    contour.points[position_index as usize],
-                    PointFlags::empty(),
-                    false,
-                );
-            }
+        for contour in mem::replace(&mut self.contours, vec![]) {
+            self.push_clipped_contour(ContourPolygonClipper::new(clip_polygon, contour).clip());
+        }
+    }
 
-            last_endpoint_index = Some(point_index);
+    /// Clips this outline to `clip_rect`, removing empty contours and slivers as
+    /// `clip_against_polygon()` does.
+    pub fn clip_against_rect(&mut self, clip_rect: RectF) {
+        if clip_rect.contains_rect(self.bounds) {
+            return;
         }
 
-        fn handle_cubic(contour: &mut Contour, segment: &Segment) {
-            debug!("handle_cubic({:?})", segment);
+        for contour in mem::replace(&mut self.contours, vec![]) {
+            self.push_clipped_contour(ContourRectClipper::new(clip_rect, contour).clip());
+        }
+    }
 
-            match segment.as_cubic_segment().y_extrema() {
-                (Some(t0), Some(t1)) => {
-                    let (segments_01, segment_2) = segment.as_cubic_segment().split(t1);
-                    let (segment_0, segment_1) = segments_01.as_cubic_segment().split(t0 / t1);
-                    contour.push_segment(&segment_0, PushSegmentFlags::empty());
-                    contour.push_segment(&segment_1, PushSegmentFlags::empty());
-                    contour.push_segment(&segment_2, PushSegmentFlags::empty());
-                }
-                (Some(t0), None) | (None, Some(t0)) => {
-                    let (segment_0, segment_1) = segment.as_cubic_segment().split(t0);
-                    contour.push_segment(&segment_0, PushSegmentFlags::empty());
-                    contour.push_segment(&segment_1, PushSegmentFlags::empty());
-                }
-                (None, None) => contour.push_segment(segment, PushSegmentFlags::empty()),
-            }
+    fn push_clipped_contour(&mut self, contour: Contour) {
+        if !contour.closed || contour.area().abs() >= EPSILON {
+            self.push_contour(contour);
         }
     }
 
-    fn curve_with_endpoints_is_monotonic(
-        &self,
-        start_endpoint_index: u32,
-        end_endpoint_index: u32,
-    ) -> bool {
-        let start_position = self.points[start_endpoint_index as usize];
-        let end_position = self.points[end_endpoint_index as usize];
+    /// Replaces this outline with its intersection with `clip`.
+    ///
+    /// Unlike `clip_against_polygon()`, the clip outline may be concave, self-intersecting, or
+    /// have holes. `fill_rule` determines the interior of both outlines. Curves are flattened
+    /// first, so the result consists only of lines, oriented so that the intersection is filled
+    /// under either fill rule.
+    pub fn clip_against_outline(&mut self, clip: &Outline, fill_rule: FillRule) {
+        if self.contours.is_empty() {
+            return;
+        }
+        if clip.contours.is_empty() || self.bounds.intersection(clip.bounds).is_none() {
+            self.clear();
+            return;
+        }
 
-        if start_position.x() <= end_position.x() {
-            for point_index in start_endpoint_index..end_endpoint_index {
-                if self.points[point_index as usize].x() > self.points[point_index as usize + 1].x()
-                {
-                    return false;
-                }
-            }
-        } else {
-            for point_index in start_endpoint_index..end_endpoint_index {
-                if self.points[point_index as usize].x() < self.points[point_index as usize + 1].x()
-                {
-                    return false;
-                }
-            }
+        *self = self.boolean(clip, BooleanOp::Intersection, fill_rule);
+    }
+
+    /// Returns the result of combining the filled regions of this outline and `other`.
+    ///
+    /// `fill_rule` determines the interior of both outlines. Curves are flattened to the default
+    /// tolerance; see `boolean_with_tolerance()`. Holes in the result wind opposite to the
+    /// contours around them, so it fills correctly under either fill rule.
+    ///
+    /// Every pair of flattened edges is tested for crossings, so this takes time quadratic in the
+    /// number of edges and is best suited to outlines of modest complexity.
+    pub fn boolean(&self, other: &Outline, op: BooleanOp, fill_rule: FillRule) -> Outline {
+        self.boolean_with_tolerance(other, op, fill_rule, BOOLEAN_FLATTENING_TOLERANCE)
+    }
+
+    /// Like `boolean()`, but flattens curves so that they deviate by no more than `tolerance`.
+    pub fn boolean_with_tolerance(&self,
+                                  other: &Outline,
+                                  op: BooleanOp,
+                                  fill_rule: FillRule,
+                                  tolerance: f32)
+                                  -> Outline {
+        let subject = self.flatten(tolerance);
+        let other = other.flatten(tolerance);
+        OutlineClipper::new(&subject, &other, op, fill_rule).clip()
+    }
+
+    /// Splits the filled region of this outline into triangles, for filling on the GPU without
+    /// tiling.
+    ///
+    /// `fill_rule` determines the interior, including holes. Curves are flattened to the default
+    /// tolerance, and self-intersections are resolved first, so any outline can be triangulated.
+    /// Every triangle winds the same way as the positive-area contours of `from_rect()`.
+    pub fn triangulate(&self, fill_rule: FillRule) -> Vec<[Vector2F; 3]> {
+        if self.contours.is_empty() {
+            return vec![];
         }
+        let simple = self.boolean(&Outline::new(), BooleanOp::Union, fill_rule);
+        triangulate::triangulate_simple_outline(&simple)
+    }
 
-        if start_position.y() <= end_position.y() {
-            for point_index in start_endpoint_index..end_endpoint_index {
-                if self.points[point_index as usize].y() > self.points[point_index as usize + 1].y()
-                {
-                    return false;
+    /// Writes this outline as PDF path construction operators: `m` to start each contour, `l` for
+    /// lines, `c` for curves, and `h` to close, one operator per line. Quadratic curves become
+    /// cubics, since PDF has no quadratic operator.
+    ///
+    /// Coordinates are written with `precision` digits after the decimal point. PDF user space is
+    /// y-up, unlike outlines, which are usually y-down; pass the height of the page as
+    /// `flip_height` to write `flip_height - y` in place of each y coordinate. The same operators
+    /// work in PostScript if spelled out as `moveto`, `lineto`, `curveto`, and `closepath`.
+    pub fn to_postscript_path(&self, precision: usize, flip_height: Option<f32>) -> String {
+        let mut path = String::new();
+        let write_point = |path: &mut String, point: Vector2F| {
+            let y = match flip_height {
+                None => point.y(),
+                Some(height) => height - point.y(),
+            };
+            push_postscript_number(path, point.x(), precision);
+            path.push(' ');
+            push_postscript_number(path, y, precision);
+            path.push(' ');
+        };
+
+        for contour in &self.contours {
+            let first_position = match contour.points.first() {
+                None => continue,
+                Some(&first_position) => first_position,
+            };
+            write_point(&mut path, first_position);
+            path.push_str("m\n");
+
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                match segment.kind {
+                    SegmentKind::None => {}
+                    SegmentKind::Line => {
+                        write_point(&mut path, segment.baseline.to());
+                        path.push_str("l\n");
+                    }
+                    SegmentKind::Quadratic | SegmentKind::Cubic => {
+                        let segment = segment.to_cubic();
+                        write_point(&mut path, segment.ctrl.from());
+                        write_point(&mut path, segment.ctrl.to());
+                        write_point(&mut path, segment.baseline.to());
+                        path.push_str("c\n");
+                    }
                 }
             }
-        } else {
-            for point_index in start_endpoint_index..end_endpoint_index {
-                if self.points[point_index as usize].y() < self.points[point_index as usize + 1].y()
-                {
-                    return false;
-                }
+
+            if contour.closed {
+                path.push_str("h\n");
             }
         }
+        path
+    }
 
-        true
+    /// Computes how much of each pixel of a `width` by `height` image this outline covers when
+    /// filled with the given rule, with antialiasing.
+    ///
+    /// Pixel (x, y) is the unit square with its upper left corner at (x, y), and the result holds
+    /// the coverage of each row in turn, from 0.0 to 1.0. This is a simple reference rasterizer
+    /// on the CPU, meant for testing rather than speed. Open contours are filled as though they
+    /// were closed, and anything outside the image is ignored.
+    pub fn rasterize_coverage(&self, width: u32, height: u32, fill_rule: FillRule) -> Vec<f32> {
+        rasterize::rasterize_coverage(self, width, height, fill_rule)
     }
 
-    // Use this function to keep bounds up to date when mutating paths. See `Outline::transform()`
-    // for an example of use.
-    pub(crate) fn update_bounds(&self, bounds: &mut Option<RectF>) {
-        *bounds = Some(match *bounds {
-            None => self.bounds,
-            Some(bounds) => bounds.union_rect(self.bounds),
-        })
+    /// Samples the signed distance to this outline at the center of each pixel of a `width` by
+    /// `height` image, for use as a distance field texture.
+    ///
+    /// Pixels are laid out as in `rasterize_coverage()`. Distances are divided by `spread` and
+    /// clamped to the range from -1.0 to 1.0, negative inside; see `signed_distance()`. Pixels
+    /// whose centers are at least `spread` away from the bounds of the outline are known to be
+    /// 1.0 without measuring.
+    ///
+    /// This panics if `spread` is not positive, including if it is NaN.
+    pub fn to_sdf(&self, width: u32, height: u32, spread: f32) -> Vec<f32> {
+        assert!(spread > 0.0, "the spread of a distance field must be positive");
+        let mut sdf = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let point = vec2f(x as f32 + 0.5, y as f32 + 0.5);
+                if distance_to_rect(point, self.bounds) >= spread {
+                    sdf.push(1.0);
+                } else {
+                    sdf.push((self.signed_distance(point) / spread).clamp(-1.0, 1.0));
+                }
+            }
+        }
+        sdf
     }
-}
 
-impl Debug for Contour {
-    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        for (segment_index, segment) in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
-                                            .enumerate() {
-            if segment_index == 0 {
-                write!(
-                    formatter,
-                    "M {} {}",
-                    segment.baseline.from_x(),
-                    segment.baseline.from_y()
-                )?;
+    /// Writes this outline as a GeoJSON `Polygon` geometry, or a `MultiPolygon` if it has several
+    /// outer rings, with curves flattened to `tolerance`.
+    ///
+    /// Which rings are holes is decided by nesting, as in `fix_winding()`: contours inside an odd
+    /// number of others are holes in the innermost contour around them. Following RFC 7946, outer
+    /// rings run counterclockwise and holes clockwise, with y pointing up, and each ring repeats
+    /// its first position at the end. Rings with fewer than three distinct positions are left out.
+    /// Points with infinite or NaN coordinates, which JSON can't represent, are skipped.
+    pub fn to_geojson_polygon(&self, tolerance: f32) -> String {
+        let flattened = self.flatten(tolerance);
+        let rings: Vec<Vec<Vector2F>> = flattened.contours.iter().map(|contour| {
+            let mut ring: Vec<Vector2F> = contour.points.iter().cloned().filter(|point| {
+                point.x().is_finite() && point.y().is_finite()
+            }).collect();
+            ring.dedup();
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
             }
+            ring
+        }).collect();
+        let depths = flattened.nesting_depths();
+        let is_hole = |ring_index: usize| depths[ring_index] % 2 == 1;
 
-            match segment.kind {
-                SegmentKind::None => {}
-                SegmentKind::Line => {
-                    write!(
-                        formatter,
-                        " L {} {}",
-                        segment.baseline.to_x(),
-                        segment.baseline.to_y()
-                    )?;
+        // Gather the outer rings, each followed by its holes.
+        let mut polygons: Vec<Vec<usize>> = vec![];
+        let mut polygon_of_ring = vec![None; rings.len()];
+        for (ring_index, ring) in rings.iter().enumerate() {
+            if ring.len() >= 3 && !is_hole(ring_index) {
+                polygon_of_ring[ring_index] = Some(polygons.len());
+                polygons.push(vec![ring_index]);
+            }
+        }
+        for (ring_index, ring) in rings.iter().enumerate() {
+            if ring.len() < 3 || !is_hole(ring_index) {
+                continue;
+            }
+            let inner = &flattened.contours[ring_index];
+            let parent = (0..rings.len()).find(|&parent_index| {
+                depths[parent_index] + 1 == depths[ring_index] &&
+                    flattened.contours[parent_index].encloses(inner)
+            });
+            if let Some(polygon_index) = parent.and_then(|parent| polygon_of_ring[parent]) {
+                polygons[polygon_index].push(ring_index);
+            }
+        }
+
+        let write_polygon = |json: &mut String, polygon: &[usize]| {
+            json.push('[');
+            for (ring_position, &ring_index) in polygon.iter().enumerate() {
+                let ring = &rings[ring_index];
+                let area: f32 = (0..ring.len()).map(|point_index| {
+                    ring[point_index].det(ring[(point_index + 1) % ring.len()])
+                }).sum();
+                // Outer rings have positive area with y up, and holes negative.
+                let reverse = (area < 0.0) == (ring_position == 0);
+                if ring_position > 0 {
+                    json.push(',');
                 }
-                SegmentKind::Quadratic => {
-                    write!(
-                        formatter,
-                        " Q {} {} {} {}",
-                        segment.ctrl.from_x(),
-                        segment.ctrl.from_y(),
-                        segment.baseline.to_x(),
-                        segment.baseline.to_y()
-                    )?;
+                json.push('[');
+                for point_index in 0..=ring.len() {
+                    let mut point_index = point_index % ring.len();
+                    if reverse {
+                        point_index = (ring.len() - point_index) % ring.len();
+                    }
+                    if json.ends_with(']') {
+                        json.push(',');
+                    }
+                    let point = ring[point_index];
+                    json.push_str(&format!("[{},{}]", point.x(), point.y()));
                 }
-                SegmentKind::Cubic => {
-                    write!(
-                        formatter,
-                        " C {} {} {} {} {} {}",
-                        segment.ctrl.from_x(),
-                        segment.ctrl.from_y(),
-                        segment.ctrl.to_x(),
-                        segment.ctrl.to_y(),
-                        segment.baseline.to_x(),
-                        segment.baseline.to_y()
-                    )?;
+                json.push(']');
+            }
+            json.push(']');
+        };
+
+        let mut json = String::new();
+        if polygons.len() == 1 {
+            json.push_str("{\"type\":\"Polygon\",\"coordinates\":");
+            write_polygon(&mut json, &polygons[0]);
+        } else {
+            json.push_str("{\"type\":\"MultiPolygon\",\"coordinates\":[");
+            for (polygon_index, polygon) in polygons.iter().enumerate() {
+                if polygon_index > 0 {
+                    json.push(',');
                 }
+                write_polygon(&mut json, polygon);
             }
+            json.push(']');
         }
+        json.push('}');
+        json
+    }
 
-        if self.closed {
-            write!(formatter, " z")?;
+    // Returns the number of other contours that enclose each contour.
+    fn nesting_depths(&self) -> Vec<usize> {
+        self.contours.iter().enumerate().map(|(contour_index, contour)| {
+            self.contours.iter().enumerate().filter(|&(other_index, other)| {
+                other_index != contour_index && other.encloses(contour)
+            }).count()
+        }).collect()
+    }
+
+    /// Returns lines for drawing this outline as a wireframe while debugging: first the edges of
+    /// the curves flattened to a tolerance of 0.1, then the handles joining each curve's endpoints
+    /// to their control points.
+    ///
+    /// A quadratic has two handles that meet at its control point, and a cubic has one handle at
+    /// each end.
+    pub fn debug_wireframe(&self) -> (Vec<LineSegment2F>, Vec<LineSegment2F>) {
+        let (mut edges, mut handles) = (vec![], vec![]);
+        for contour in &self.contours {
+            edges.extend(contour.edges(DEBUG_WIREFRAME_TOLERANCE));
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                let (from, to) = (segment.baseline.from(), segment.baseline.to());
+                match segment.kind {
+                    SegmentKind::None | SegmentKind::Line => {}
+                    SegmentKind::Quadratic => {
+                        handles.push(LineSegment2F::new(from, segment.ctrl.from()));
+                        handles.push(LineSegment2F::new(segment.ctrl.from(), to));
+                    }
+                    SegmentKind::Cubic => {
+                        handles.push(LineSegment2F::new(from, segment.ctrl.from()));
+                        handles.push(LineSegment2F::new(segment.ctrl.to(), to));
+                    }
+                }
+            }
         }
+        (edges, handles)
+    }
 
-        Ok(())
+    /// Returns true if this outline has the same structure as `other` and every point differs
+    /// from its counterpart by no more than `epsilon` in each coordinate.
+    pub fn approx_eq(&self, other: &Outline, epsilon: f32) -> bool {
+        self.contours.len() == other.contours.len() &&
+            self.contours.iter().zip(other.contours.iter()).all(|(contour, other_contour)| {
+                contour.approx_eq(other_contour, epsilon)
+            })
     }
-}
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
-pub struct PointIndex(u32);
+    /// Returns the contours of this outline as a stream of lyon path events.
+    ///
+    /// Each contour starts with a `Begin` event and finishes with an `End` event whose `close`
+    /// flag says whether the contour is closed.
+    #[cfg(feature = "lyon")]
+    pub fn iter_lyon_events(&self) -> impl Iterator<Item = PathEvent> + '_ {
+        self.contours.iter().filter(|contour| !contour.is_empty()).flat_map(|contour| {
+            let (first, last) = (contour.position_of(0), contour.position_of_last(1));
+            let segments = contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT);
+            iter::once(PathEvent::Begin { at: vector_to_lyon_point(first) })
+                .chain(segments.filter_map(|segment| segment_to_lyon_event(&segment)))
+                .chain(iter::once(PathEvent::End {
+                    last: vector_to_lyon_point(last),
+                    first: vector_to_lyon_point(first),
+                    close: contour.closed,
+                }))
+        })
+    }
 
-impl PointIndex {
     #[inline]
-    pub fn new(contour: u32, point: u32) -> PointIndex {
-        debug_assert!(contour <= 0xfff);
-        debug_assert!(point <= 0x000f_ffff);
-        PointIndex((contour << 20) | point)
+    pub fn close_all_contours(&mut self) {
+        self.contours.iter_mut().for_each(|contour| contour.close());
     }
+}
 
+impl PartialEq for Outline {
+    // The bounds are derived from the points, so they need not be compared.
     #[inline]
-    pub fn contour(self) -> u32 {
-        self.0 >> 20
+    fn eq(&self, other: &Outline) -> bool {
+        self.contours == other.contours
     }
+}
 
+impl Eq for Outline {}
+
+impl Hash for Outline {
     #[inline]
-    pub fn point(self) -> u32 {
-        self.0 & 0x000f_ffff
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.contours.hash(state)
     }
 }
 
-pub struct ContourIter<'a> {
-    contour: &'a Contour,
-    index: u32,
-    flags: ContourIterFlags,
+impl Debug for Outline {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        for (contour_index, contour) in self.contours.iter().enumerate() {
+            if contour_index > 0 {
+                write!(formatter, " ")?;
+            }
+            contour.fmt(formatter)?;
+        }
+        Ok(())
+    }
 }
 
-impl<'a> Iterator for ContourIter<'a> {
-    type Item = Segment;
+impl Contour {
+    #[inline]
+    pub fn new() -> Contour {
+        Contour {
+            points: vec![],
+            flags: vec![],
+            bounds: RectF::default(),
+            closed: false,
+        }
+    }
 
     #[inline]
-    fn next(&mut self) -> Option<Segment> {
-        let contour = self.contour;
+    pub fn with_capacity(length: usize) -> Contour {
+        Contour {
+            points: Vec::with_capacity(length),
+            flags: Vec::with_capacity(length),
+            bounds: RectF::default(),
+            closed: false,
+        }
+    }
 
-        let include_close_segment = self.contour.closed &&
-            !self.flags.contains(ContourIterFlags::IGNORE_CLOSE_SEGMENT);
-        if (self.index == contour.len() && !include_close_segment) ||
-                self.index == contour.len() + 1 {
-            return None;
+    #[inline]
+    pub fn from_rect(rect: RectF) -> Contour {
+        let mut contour = Contour::new();
+        contour.push_point(rect.origin(), PointFlags::empty(), false);
+        contour.push_point(rect.upper_right(), PointFlags::empty(), false);
+        contour.push_point(rect.lower_right(), PointFlags::empty(), false);
+        contour.push_point(rect.lower_left(), PointFlags::empty(), false);
+        contour.close();
+        contour.bounds = rect;
+        contour
+    }
+
+    /// Creates a closed contour tracing `rect` with each corner replaced by a quarter of an
+    /// ellipse with the given horizontal and vertical radii.
+    ///
+    /// The radii are clamped to half the size of the rectangle. The contour winds the same way as
+    /// `from_rect()`.
+    pub fn from_rounded_rect(rect: RectF, radii: Vector2F) -> Contour {
+        let radii = radii.min(rect.size() * 0.5);
+        if radii.x() <= 0.0 || radii.y() <= 0.0 {
+            return Contour::from_rect(rect);
         }
 
-        let point0_index = self.index - 1;
-        let point0 = contour.position_of(point0_index);
-        if self.index == contour.len() {
-            let point1 = contour.position_of(0);
-            self.index += 1;
-            return Some(Segment::line(LineSegment2F::new(point0, point1)));
+        // Start just before the upper right corner so that the contour ends with a line, which
+        // the close takes care of.
+        let (origin, lower_right) = (rect.origin(), rect.lower_right());
+        let (radius_x, radius_y) = (vec2f(radii.x(), 0.0), vec2f(0.0, radii.y()));
+        let (handle_x, handle_y) = (radius_x * CIRCLE_CUBIC_HANDLE, radius_y * CIRCLE_CUBIC_HANDLE);
+        let mut contour = Contour::with_capacity(16);
+        contour.push_point(rect.upper_right() - radius_x, PointFlags::empty(), false);
+        contour.push_unbounded_cubic(rect.upper_right() - radius_x + handle_x,
+                                     rect.upper_right() + radius_y - handle_y,
+                                     rect.upper_right() + radius_y);
+        contour.push_point(lower_right - radius_y, PointFlags::empty(), false);
+        contour.push_unbounded_cubic(lower_right - radius_y + handle_y,
+                                     lower_right - radius_x + handle_x,
+                                     lower_right - radius_x);
+        contour.push_point(rect.lower_left() + radius_x, PointFlags::empty(), false);
+        contour.push_unbounded_cubic(rect.lower_left() + radius_x - handle_x,
+                                     rect.lower_left() - radius_y + handle_y,
+                                     rect.lower_left() - radius_y);
+        contour.push_point(origin + radius_y, PointFlags::empty(), false);
+        contour.push_unbounded_cubic(origin + radius_y - handle_y,
+                                     origin + radius_x - handle_x,
+                                     origin + radius_x);
+        contour.close();
+        contour.bounds = rect;
+        contour
+    }
+
+    /// Creates a closed contour tracing an axis-aligned ellipse with four cubic Bézier curves.
+    ///
+    /// The control points lie on the bounding box of the ellipse, so the bounds of the contour
+    /// are exactly that box. The contour winds the same way as `from_rect()`.
+    pub fn from_ellipse(center: Vector2F, radii: Vector2F) -> Contour {
+        let (radius_x, radius_y) = (vec2f(radii.x(), 0.0), vec2f(0.0, radii.y()));
+        let (handle_x, handle_y) = (radius_x * CIRCLE_CUBIC_HANDLE, radius_y * CIRCLE_CUBIC_HANDLE);
+        let (right, bottom) = (center + radius_x, center + radius_y);
+        let (left, top) = (center - radius_x, center - radius_y);
+        let mut contour = Contour::with_capacity(13);
+        contour.push_point(right, PointFlags::empty(), false);
+        contour.push_unbounded_cubic(right + handle_y, bottom + handle_x, bottom);
+        contour.push_unbounded_cubic(bottom - handle_x, left + handle_y, left);
+        contour.push_unbounded_cubic(left - handle_y, top - handle_x, top);
+        contour.push_unbounded_cubic(top + handle_x, right - handle_y, right);
+        contour.close();
+        contour.bounds = RectF::new(center - radii, radii * 2.0);
+        contour
+    }
+
+    /// Creates a contour of cubic Bézier curves that passes through each of the given points in
+    /// turn, following a Catmull-Rom spline.
+    ///
+    /// `tension` controls how tightly the curve bends at each point: 0.0 gives a standard
+    /// Catmull-Rom spline and 1.0 gives straight lines. The tangent at each end of an open contour
+    /// points toward its neighbor, as though there were an extra point mirrored past the end. A
+    /// closed contour wraps around and ends with a curve back to the first point.
+    pub fn from_catmull_rom(points: &[Vector2F], tension: f32, closed: bool) -> Contour {
+        let point_count = points.len();
+        let segment_count = match point_count {
+            0 | 1 => 0,
+            _ if closed => point_count,
+            _ => point_count - 1,
+        };
+        let mut contour = Contour::with_capacity(1 + segment_count * 3);
+        if let Some(&first_point) = points.first() {
+            contour.push_endpoint(first_point);
         }
 
-        let point1_index = self.index;
-        self.index += 1;
-        let point1 = contour.position_of(point1_index);
-        if contour.point_is_endpoint(point1_index) {
-            return Some(Segment::line(LineSegment2F::new(point0, point1)));
+        let point_at = |index: isize| -> Vector2F {
+            let last_index = point_count as isize - 1;
+            if closed {
+                points[index.rem_euclid(point_count as isize) as usize]
+            } else if index < 0 {
+                points[0] * 2.0 - points[1]
+            } else if index > last_index {
+                points[last_index as usize] * 2.0 - points[last_index as usize - 1]
+            } else {
+                points[index as usize]
+            }
+        };
+
+        let handle_scale = (1.0 - tension) / 6.0;
+        for segment_index in 0..(segment_count as isize) {
+            let (prev, from) = (point_at(segment_index - 1), point_at(segment_index));
+            let (to, next) = (point_at(segment_index + 1), point_at(segment_index + 2));
+            contour.push_cubic(from + (to - prev) * handle_scale,
+                               to - (next - from) * handle_scale,
+                               to);
         }
 
-        let point2_index = self.index;
-        let point2 = contour.position_of(point2_index);
-        self.index += 1;
-        if contour.point_is_endpoint(point2_index) {
-            return Some(Segment::quadratic(LineSegment2F::new(point0, point2), point1));
+        if closed {
+            contour.close();
         }
+        contour
+    }
 
-        let point3_index = self.index;
-        let point3 = contour.position_of(point3_index);
-        self.index += 1;
-        debug_assert!(contour.point_is_endpoint(point3_index));
-        return Some(Segment::cubic(
-            LineSegment2F::new(point0, point3),
-            LineSegment2F::new(point1, point2),
-        ));
+    /// Creates an open contour of cubic Bézier curves that passes within `error` of each of the
+    /// given points, which are assumed to be sampled in order along a smooth curve.
+    ///
+    /// This is the curve fitting algorithm of Schneider: one cubic is fit to all the points by
+    /// least squares, and wherever it misses a point by more than `error` the points are split
+    /// there and each half is fit again. The curve is kept smooth at the splits. See
+    /// `fit_to_points_with_corners()` to allow sharp corners.
+    pub fn fit_to_points(points: &[Vector2F], error: f32) -> Contour {
+        fit::fit_cubics(points, error, PI)
     }
-}
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum ArcDirection {
-    CW,
-    CCW,
-}
+    /// Like `fit_to_points()`, but the curve starts afresh, with a sharp corner, at every point
+    /// where the direction of travel turns by more than `corner_angle` radians.
+    pub fn fit_to_points_with_corners(points: &[Vector2F], error: f32, corner_angle: f32)
+                                      -> Contour {
+        fit::fit_cubics(points, error, corner_angle)
+    }
 
-bitflags! {
-    pub struct ContourIterFlags: u8 {
-        const IGNORE_CLOSE_SEGMENT = 1;
+    #[inline]
+    fn push_unbounded_cubic(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, point: Vector2F) {
+        self.push_point(ctrl0, PointFlags::CONTROL_POINT_0, false);
+        self.push_point(ctrl1, PointFlags::CONTROL_POINT_1, false);
+        self.push_point(point, PointFlags::empty(), false);
     }
-}
 
-#[inline]
-pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
-    if first {
-        *bounds = RectF::from_points(new_point, new_point);
-    } else {
-        *bounds = bounds.union_point(new_point)
+    // Replaces this contour with a new one, with arrays preallocated to match `self`.
+    #[inline]
+    pub(crate) fn take(&mut self) -> Contour {
+        let length = self.len() as usize;
+        mem::replace(
+            self,
+            Contour {
+                points: Vec::with_capacity(length),
+                flags: Vec::with_capacity(length),
+                bounds: RectF::default(),
+                closed: false,
+            },
+        )
+    }
+
+    /// restore self to the state of Contour::new(), but keep the points buffer allocated
+    #[inline]
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.flags.clear();
+        self.bounds = RectF::default();
+        self.closed = false;
+    }
+
+    #[inline]
+    pub fn iter(&self, flags: ContourIterFlags) -> ContourIter {
+        ContourIter {
+            contour: self,
+            index: 1,
+            flags,
+        }
+    }
+
+    /// Like `iter()` with no flags, but also yields the index of the endpoint that starts each
+    /// segment. The closing segment of a closed contour starts at the last endpoint.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (u32, Segment)> + '_ {
+        let mut iter = self.iter(ContourIterFlags::empty());
+        iter::from_fn(move || {
+            let start_index = iter.index - 1;
+            iter.next().map(|segment| (start_index, segment))
+        })
+    }
+
+    /// Iterates over the control points of this contour, yielding the index and position of each
+    /// along with the index of the endpoint that its handle is attached to.
+    ///
+    /// The first control point of a curve is attached to the endpoint before it and the second
+    /// to the endpoint after it. The single control point of a quadratic curve counts as a first
+    /// control point.
+    pub fn control_handles(&self) -> impl Iterator<Item = (u32, Vector2F, u32)> + '_ {
+        let points = self.flags.iter().zip(self.points.iter()).enumerate();
+        points.filter_map(move |(point_index, (flags, &position))| {
+            let point_index = point_index as u32;
+            if flags.contains(PointFlags::CONTROL_POINT_0) {
+                Some((point_index, position, self.prev_endpoint_index_of(point_index)))
+            } else if flags.contains(PointFlags::CONTROL_POINT_1) {
+                Some((point_index, position, self.next_endpoint_index_of(point_index)))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Iterates over the segments of this contour, splitting curves at their vertical extrema so
+    /// that every segment is monotonic in y.
+    ///
+    /// Unlike `make_monotonic()`, this leaves the contour untouched. Curves are yielded as cubics.
+    #[inline]
+    pub fn monotonic_segments(&self) -> MonotonicSegmentIter<'_> {
+        MonotonicSegmentIter {
+            iter: self.iter(ContourIterFlags::empty()),
+            pending: ArrayVec::new(),
+        }
+    }
+
+    /// Returns a copy of this contour with curves split at their vertical extrema, so that every
+    /// segment is monotonic in y.
+    ///
+    /// The copy is built directly from the pieces rather than by cloning and splitting, and this
+    /// contour is left untouched. Curves that need splitting come out as cubics.
+    pub fn to_monotonic(&self) -> Contour {
+        let mut contour = Contour::with_capacity(self.points.len());
+        if let Some(&first_position) = self.points.first() {
+            contour.push_endpoint(first_position);
+        }
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let pieces = match segment.kind {
+                SegmentKind::None | SegmentKind::Line => {
+                    contour.push_segment(&segment, PushSegmentFlags::UPDATE_BOUNDS);
+                    continue;
+                }
+                SegmentKind::Quadratic | SegmentKind::Cubic => {
+                    split_cubic_at_y_extrema(&segment.to_cubic())
+                }
+            };
+            if pieces.len() == 1 {
+                contour.push_segment(&segment, PushSegmentFlags::UPDATE_BOUNDS);
+                continue;
+            }
+            for piece in pieces {
+                contour.push_segment(&piece, PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        }
+        contour.closed = self.closed;
+        contour
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> u32 {
+        self.points.len() as u32
+    }
+
+    /// Returns the number of on-curve points in this contour. Unlike `len()`, this doesn't count
+    /// control points.
+    pub fn endpoint_count(&self) -> u32 {
+        self.flags.iter().filter(|flags| flags.is_empty()).count() as u32
+    }
+
+    /// Returns the number of segments that `iter()` yields for this contour without building them:
+    /// one per endpoint if the contour is closed, counting the closing segment, and one fewer if
+    /// it is open.
+    pub fn segment_count(&self) -> u32 {
+        let endpoint_count = self.endpoint_count();
+        if self.closed {
+            endpoint_count
+        } else {
+            endpoint_count.saturating_sub(1)
+        }
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> RectF {
+        self.bounds
+    }
+
+    /// Returns the smallest rectangle that contains the curves of this contour.
+    ///
+    /// See `Outline::tight_bounds()`.
+    pub fn tight_bounds(&self) -> RectF {
+        if self.is_empty() {
+            return RectF::default();
+        }
+
+        let first_point = self.position_of(0);
+        let mut bounds = RectF::from_points(first_point, first_point);
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            bounds = bounds.union_rect(segment.tight_bounds());
+        }
+        bounds
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns true if every point in this contour has finite coordinates.
+    pub fn is_finite(&self) -> bool {
+        self.points.iter().all(|point| point_is_finite(*point))
+    }
+
+    /// Removes points with NaN or infinite coordinates and recomputes the bounds.
+    ///
+    /// A non-finite endpoint is dropped along with the control points leading up to it. A curve
+    /// with a non-finite control point becomes a line to its endpoint.
+    pub fn sanitize(&mut self) {
+        if self.is_finite() {
+            return;
+        }
+
+        let input = self.take();
+        self.closed = input.closed;
+
+        let (mut first_ctrl_index, mut ctrls_are_finite) = (0, true);
+        for (point_index, (&point, &flags)) in input.points.iter()
+                                                    .zip(input.flags.iter())
+                                                    .enumerate() {
+            if !flags.is_empty() {
+                ctrls_are_finite = ctrls_are_finite && point_is_finite(point);
+                continue;
+            }
+
+            if point_is_finite(point) {
+                if ctrls_are_finite && !self.is_empty() {
+                    for ctrl_index in first_ctrl_index..point_index {
+                        self.push_point(input.points[ctrl_index], input.flags[ctrl_index], true);
+                    }
+                }
+                self.push_point(point, PointFlags::empty(), true);
+            }
+
+            first_ctrl_index = point_index + 1;
+            ctrls_are_finite = true;
+        }
+    }
+
+    /// Marks this contour as closed or open.
+    ///
+    /// Closed contours have an implicit line segment from their last point back to their first,
+    /// which iterators yield unless asked not to. Open contours are still filled as though they
+    /// were closed, but they are not closed when stroked.
+    #[inline]
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed;
+    }
+
+    /// Returns every point of this contour, including control points, in order.
+    ///
+    /// The flags at the same index in `flags()` tell endpoints apart from control points.
+    #[inline]
+    pub fn points(&self) -> &[Vector2F] {
+        &self.points
+    }
+
+    /// Returns the flags of every point of this contour, in the same order as `points()`.
+    #[inline]
+    pub fn flags(&self) -> &[PointFlags] {
+        &self.flags
+    }
+
+    #[inline]
+    pub fn position_of(&self, index: u32) -> Vector2F {
+        self.points[index as usize]
+    }
+
+    /// Moves the point at `point_index`, which may be an endpoint or a control point, to
+    /// `position`, and updates the bounds to match.
+    ///
+    /// The bounds are only rebuilt from scratch if the point was on their edge, since only then
+    /// can moving it shrink them.
+    pub fn set_position(&mut self, point_index: u32, position: Vector2F) {
+        let old_position = mem::replace(&mut self.points[point_index as usize], position);
+        let (lower_right, origin) = (self.bounds.lower_right(), self.bounds.origin());
+        if old_position.x() > origin.x() && old_position.y() > origin.y() &&
+                old_position.x() < lower_right.x() && old_position.y() < lower_right.y() {
+            self.bounds = self.bounds.union_point(position);
+        } else {
+            self.recompute_bounds();
+        }
+    }
+
+    #[inline]
+    pub fn last_position(&self) -> Option<Vector2F> {
+        self.points.last().cloned()
+    }
+
+    #[inline]
+    pub(crate) fn position_of_last(&self, index: u32) -> Vector2F {
+        self.points[self.points.len() - index as usize]
+    }
+
+    #[inline]
+    pub fn push_endpoint(&mut self, point: Vector2F) {
+        self.push_point(point, PointFlags::empty(), true);
+    }
+
+    #[inline]
+    pub fn push_quadratic(&mut self, ctrl: Vector2F, point: Vector2F) {
+        self.push_point(ctrl, PointFlags::CONTROL_POINT_0, true);
+        self.push_point(point, PointFlags::empty(), true);
+    }
+
+    #[inline]
+    pub fn push_cubic(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, point: Vector2F) {
+        self.push_point(ctrl0, PointFlags::CONTROL_POINT_0, true);
+        self.push_point(ctrl1, PointFlags::CONTROL_POINT_1, true);
+        self.push_point(point, PointFlags::empty(), true);
+    }
+
+    #[inline]
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    #[inline]
+    pub(crate) fn push_point(&mut self,
+                             point: Vector2F,
+                             flags: PointFlags,
+                             update_bounds: bool) {
+        debug_assert!(!point.x().is_nan() && !point.y().is_nan());
+
+        if update_bounds {
+            let first = self.is_empty();
+            union_rect(&mut self.bounds, point, first);
+        }
+
+        self.points.push(point);
+        self.flags.push(flags);
+    }
+
+    /// Appends many points at once, along with their flags, and grows the bounds to include them.
+    ///
+    /// This is equivalent to pushing the points one by one, but reserves space only once. The
+    /// slices must have the same length.
+    pub fn push_points(&mut self, points: &[Vector2F], flags: &[PointFlags]) {
+        debug_assert_eq!(points.len(), flags.len());
+        let count = points.len().min(flags.len());
+        let (points, flags) = (&points[..count], &flags[..count]);
+
+        let mut first = self.is_empty();
+        for &point in points {
+            debug_assert!(!point.x().is_nan() && !point.y().is_nan());
+            union_rect(&mut self.bounds, point, first);
+            first = false;
+        }
+
+        self.points.extend_from_slice(points);
+        self.flags.extend_from_slice(flags);
+    }
+
+    #[inline]
+    pub(crate) fn push_segment(&mut self, segment: &Segment, flags: PushSegmentFlags) {
+        if segment.is_none() {
+            return;
+        }
+
+        let update_bounds = flags.contains(PushSegmentFlags::UPDATE_BOUNDS);
+        if flags.contains(PushSegmentFlags::INCLUDE_FROM_POINT) {
+            self.push_point(segment.baseline.from(), PointFlags::empty(), update_bounds);
+        }
+
+        if !segment.is_line() {
+            self.push_point(
+                segment.ctrl.from(),
+                PointFlags::CONTROL_POINT_0,
+                update_bounds,
+            );
+            if !segment.is_quadratic() {
+                self.push_point(
+                    segment.ctrl.to(),
+                    PointFlags::CONTROL_POINT_1,
+                    update_bounds,
+                );
+            }
+        }
+
+        self.push_point(segment.baseline.to(), PointFlags::empty(), update_bounds);
+    }
+
+    pub fn push_arc(&mut self,
+                    transform: &Transform2F,
+                    start_angle: f32,
+                    end_angle: f32,
+                    direction: ArcDirection) {
+        if end_angle - start_angle >= PI * 2.0 {
+            self.push_ellipse(transform);
+        } else {
+            let start = vec2f(start_angle.cos(), start_angle.sin());
+            let end   = vec2f(end_angle.cos(),   end_angle.sin());
+            self.push_arc_from_unit_chord(transform, LineSegment2F::new(start, end), direction);
+        }
+    }
+
+    pub fn push_arc_from_unit_chord(&mut self,
+                                    transform: &Transform2F,
+                                    chord: LineSegment2F,
+                                    direction: ArcDirection) {
+        self.push_unit_arc(transform, chord, direction, true)
+    }
+
+    /// Pushes an elliptical arc from the last point of this contour to `to`, as the SVG `A` path
+    /// command does.
+    ///
+    /// `x_axis_rotation` is in radians. `large_arc` selects which of the two arcs that fit the
+    /// endpoints and radii is taken, and `direction` selects the direction of travel, with
+    /// `ArcDirection::CW` corresponding to a sweep flag of 1. Radii that are too small to span the
+    /// endpoints are scaled up; if either radius is zero, a line is pushed instead. If this
+    /// contour is empty, `to` becomes its first point.
+    ///
+    /// The arc is approximated with the curves that `segment::arc_to_cubics()` produces.
+    pub fn push_svg_arc(&mut self,
+                        radii: Vector2F,
+                        x_axis_rotation: f32,
+                        large_arc: bool,
+                        direction: ArcDirection,
+                        to: Vector2F) {
+        let from = match self.last_position() {
+            None => return self.push_endpoint(to),
+            Some(from) => from,
+        };
+        let segments =
+            segment::arc_to_cubics(from, to, radii, x_axis_rotation, large_arc, direction);
+        for segment in segments {
+            self.push_segment(&segment, PushSegmentFlags::UPDATE_BOUNDS);
+        }
+    }
+
+    fn push_unit_arc(&mut self,
+                     transform: &Transform2F,
+                     chord: LineSegment2F,
+                     direction: ArcDirection,
+                     include_from_point: bool) {
+        let segments = segment::unit_arc_to_cubics(transform, chord, direction);
+        for (segment_index, segment) in segments.iter().enumerate() {
+            let mut push_segment_flags = PushSegmentFlags::UPDATE_BOUNDS;
+            if segment_index == 0 && include_from_point {
+                push_segment_flags.insert(PushSegmentFlags::INCLUDE_FROM_POINT);
+            }
+            self.push_segment(segment, push_segment_flags);
+        }
+    }
+
+    pub fn push_ellipse(&mut self, transform: &Transform2F) {
+        let segment = Segment::quarter_circle_arc();
+        let mut rotation;
+        self.push_segment(&segment.transform(transform),
+                          PushSegmentFlags::UPDATE_BOUNDS | PushSegmentFlags::INCLUDE_FROM_POINT);
+        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f( 0.0,  1.0)));
+        self.push_segment(&segment.transform(&(*transform * rotation)),
+                          PushSegmentFlags::UPDATE_BOUNDS);
+        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f(-1.0,  0.0)));
+        self.push_segment(&segment.transform(&(*transform * rotation)),
+                          PushSegmentFlags::UPDATE_BOUNDS);
+        rotation = Transform2F::from_rotation_vector(UnitVector(vec2f( 0.0, -1.0)));
+        self.push_segment(&segment.transform(&(*transform * rotation)),
+                          PushSegmentFlags::UPDATE_BOUNDS);
+    }
+
+    #[inline]
+    pub fn segment_after(&self, point_index: u32) -> Segment {
+        debug_assert!(self.point_is_endpoint(point_index));
+
+        let mut segment = Segment::none();
+        segment.baseline.set_from(self.position_of(point_index));
+
+        let point1_index = self.add_to_point_index(point_index, 1);
+        if self.point_is_endpoint(point1_index) {
+            segment.baseline.set_to(self.position_of(point1_index));
+            segment.kind = SegmentKind::Line;
+        } else {
+            segment.ctrl.set_from(self.position_of(point1_index));
+
+            let point2_index = self.add_to_point_index(point_index, 2);
+            if self.point_is_endpoint(point2_index) {
+                segment.baseline.set_to(self.position_of(point2_index));
+                segment.kind = SegmentKind::Quadratic;
+            } else {
+                segment.ctrl.set_to(self.position_of(point2_index));
+                segment.kind = SegmentKind::Cubic;
+
+                let point3_index = self.add_to_point_index(point_index, 3);
+                segment.baseline.set_to(self.position_of(point3_index));
+            }
+        }
+
+        segment
+    }
+
+    #[inline]
+    pub fn hull_segment_after(&self, prev_point_index: u32) -> LineSegment2F {
+        let next_point_index = self.next_point_index_of(prev_point_index);
+        LineSegment2F::new(
+            self.points[prev_point_index as usize],
+            self.points[next_point_index as usize],
+        )
+    }
+
+    #[inline]
+    pub fn point_is_endpoint(&self, point_index: u32) -> bool {
+        !self.flags[point_index as usize]
+            .intersects(PointFlags::CONTROL_POINT_0 | PointFlags::CONTROL_POINT_1)
+    }
+
+    #[inline]
+    pub fn add_to_point_index(&self, point_index: u32, addend: u32) -> u32 {
+        let (index, limit) = (point_index + addend, self.len());
+        if index >= limit {
+            index - limit
+        } else {
+            index
+        }
+    }
+
+    #[inline]
+    pub fn point_is_logically_above(&self, a: u32, b: u32) -> bool {
+        let (a_y, b_y) = (self.points[a as usize].y(), self.points[b as usize].y());
+        a_y < b_y || (a_y == b_y && a < b)
+    }
+
+    /// Returns the index of the endpoint before `point_index`, wrapping around.
+    ///
+    /// The contour must not be empty; see `checked_prev_endpoint_index_of()`.
+    #[inline]
+    pub fn prev_endpoint_index_of(&self, mut point_index: u32) -> u32 {
+        loop {
+            point_index = self.prev_point_index_of(point_index);
+            if self.point_is_endpoint(point_index) {
+                return point_index;
+            }
+        }
+    }
+
+    /// Returns the index of the endpoint after `point_index`, wrapping around.
+    ///
+    /// The contour must not be empty; see `checked_next_endpoint_index_of()`.
+    #[inline]
+    pub fn next_endpoint_index_of(&self, mut point_index: u32) -> u32 {
+        loop {
+            point_index = self.next_point_index_of(point_index);
+            if self.point_is_endpoint(point_index) {
+                return point_index;
+            }
+        }
+    }
+
+    /// Returns the index of the point before `point_index`, wrapping around.
+    ///
+    /// The contour must not be empty; see `checked_prev_point_index_of()`.
+    #[inline]
+    pub fn prev_point_index_of(&self, point_index: u32) -> u32 {
+        debug_assert!(!self.is_empty(), "prev_point_index_of() called on an empty contour");
+        if point_index == 0 {
+            self.len() - 1
+        } else {
+            point_index - 1
+        }
+    }
+
+    /// Returns the index of the point after `point_index`, wrapping around.
+    ///
+    /// The contour must not be empty; see `checked_next_point_index_of()`.
+    #[inline]
+    pub fn next_point_index_of(&self, point_index: u32) -> u32 {
+        debug_assert!(!self.is_empty(), "next_point_index_of() called on an empty contour");
+        if point_index == self.len() - 1 {
+            0
+        } else {
+            point_index + 1
+        }
+    }
+
+    /// Like `prev_endpoint_index_of()`, but returns `None` if the contour is empty.
+    #[inline]
+    pub fn checked_prev_endpoint_index_of(&self, point_index: u32) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.prev_endpoint_index_of(point_index))
+        }
+    }
+
+    /// Like `next_endpoint_index_of()`, but returns `None` if the contour is empty.
+    #[inline]
+    pub fn checked_next_endpoint_index_of(&self, point_index: u32) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.next_endpoint_index_of(point_index))
+        }
+    }
+
+    /// Like `prev_point_index_of()`, but returns `None` if the contour is empty.
+    #[inline]
+    pub fn checked_prev_point_index_of(&self, point_index: u32) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.prev_point_index_of(point_index))
+        }
+    }
+
+    /// Like `next_point_index_of()`, but returns `None` if the contour is empty.
+    #[inline]
+    pub fn checked_next_point_index_of(&self, point_index: u32) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.next_point_index_of(point_index))
+        }
+    }
+
+    pub fn transform(&mut self, transform: &Transform2F) {
+        if transform.is_identity() {
+            return;
+        }
+
+        transform_points(&mut self.points, transform);
+        self.recompute_bounds();
+    }
+
+    /// Returns a transformed copy of this contour, leaving this one untouched.
+    pub fn transformed(&self, transform: &Transform2F) -> Contour {
+        let mut bounds = RectF::default();
+        let points = self.points.iter().enumerate().map(|(point_index, &point)| {
+            let point = *transform * point;
+            union_rect(&mut bounds, point, point_index == 0);
+            point
+        }).collect();
+        Contour { points, flags: self.flags.clone(), bounds, closed: self.closed }
+    }
+
+    pub fn apply_perspective(&mut self, perspective: &Perspective) {
+        for (point_index, point) in self.points.iter_mut().enumerate() {
+            *point = *perspective * *point;
+            union_rect(&mut self.bounds, *point, point_index == 0);
+        }
+    }
+
+    /// Rounds the coordinates of every point in this contour, including control points, to the
+    /// nearest multiple of `grid`, and updates the bounds to match.
+    ///
+    /// Points that are closer together than half of `grid` may end up identical. Nothing happens
+    /// if `grid` is zero, negative, or not finite.
+    pub fn quantize(&mut self, grid: f32) {
+        if !(grid > 0.0 && grid.is_finite()) {
+            return;
+        }
+        self.map_points_mut(|point| quantize_point(point, grid));
+    }
+
+    /// Replaces every point of this contour, including control points, with the result of
+    /// calling `f` on it, and updates the bounds to match.
+    ///
+    /// This allows arbitrary deformations. Control points are moved like any other point, so
+    /// curves are only approximately deformed; flatten the contour first for more accuracy.
+    pub fn map_points_mut<F>(&mut self, mut f: F) where F: FnMut(Vector2F) -> Vector2F {
+        for (point_index, point) in self.points.iter_mut().enumerate() {
+            *point = f(*point);
+            union_rect(&mut self.bounds, *point, point_index == 0);
+        }
+    }
+
+    /// Returns the signed area enclosed by this contour, integrated exactly over its curves.
+    ///
+    /// The area is positive for contours that wind clockwise in a y-down coordinate system and
+    /// negative for those that wind counterclockwise. Open contours are treated as though they
+    /// were closed.
+    pub fn area(&self) -> f32 {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+
+        let mut double_area = 0.0;
+        let mut point_index = 0;
+        loop {
+            double_area += segment_double_area(&self.segment_after(point_index));
+            point_index = self.next_endpoint_index_of(point_index);
+            if point_index == 0 {
+                break;
+            }
+        }
+        double_area * 0.5
+    }
+
+    /// Returns the total angle, in radians, that the direction of this contour turns through
+    /// along its length, including the turns at its corners.
+    ///
+    /// This is `2π` or `-2π` for a simple closed contour, with the same sign as its `area()`.
+    /// Other values reveal a contour that crosses itself, such as a figure eight, which turns
+    /// through zero. Open contours are measured from their first point to their last without
+    /// turning back to the start.
+    pub fn total_turning(&self) -> f32 {
+        let mut directions = vec![];
+        for segment in self.iter(ContourIterFlags::empty()) {
+            if segment.is_line() {
+                directions.push(segment.baseline.vector());
+                continue;
+            }
+            for sample_index in 0..=TOTAL_TURNING_CURVE_SAMPLES {
+                let t = sample_index as f32 / TOTAL_TURNING_CURVE_SAMPLES as f32;
+                directions.push(segment.derivative_at(t));
+            }
+        }
+        directions.retain(|direction| direction.square_length() >= EPSILON * EPSILON);
+        if self.closed {
+            if let Some(&first) = directions.first() {
+                directions.push(first);
+            }
+        }
+
+        directions.windows(2).map(|pair| f32::atan2(pair[0].det(pair[1]), pair[0].dot(pair[1])))
+                             .sum()
+    }
+
+    /// Finds the point on this contour closest to `query`.
+    ///
+    /// Returns the index of the endpoint that starts the nearest segment, the nearest point, and
+    /// its distance from `query`, or `None` if the contour is empty. The closing segment is only
+    /// considered if the contour is closed.
+    pub fn nearest_point(&self, query: Vector2F) -> Option<(u32, Vector2F, f32)> {
+        match self.points.len() {
+            0 => return None,
+            1 => return Some((0, self.points[0], (self.points[0] - query).length())),
+            _ => {}
+        }
+
+        let mut nearest: Option<(u32, Vector2F, f32)> = None;
+        let mut point_index = 0;
+        loop {
+            let next_point_index = self.next_endpoint_index_of(point_index);
+            if next_point_index == 0 && !self.closed {
+                break;
+            }
+
+            let segment = self.segment_after(point_index);
+            let point = segment.sample(segment.nearest_t(query));
+            let distance = (point - query).length();
+            let is_nearer = match nearest {
+                None => true,
+                Some((_, _, best_distance)) => distance < best_distance,
+            };
+            if is_nearer {
+                nearest = Some((point_index, point, distance));
+            }
+
+            if next_point_index == 0 {
+                break;
+            }
+            point_index = next_point_index;
+        }
+        nearest
+    }
+
+    /// Finds the points at which non-adjacent segments of this contour cross.
+    ///
+    /// Each result holds the indices of the endpoints that start the two segments, with the first
+    /// less than the second, and the crossing point. Segments whose bounds don't overlap are
+    /// skipped, but this is still quadratic in the number of segments.
+    pub fn find_self_intersections(&self) -> Vec<(u32, u32, Vector2F)> {
+        let mut segments = vec![];
+        if self.points.len() >= 2 {
+            let mut point_index = 0;
+            loop {
+                let next_point_index = self.next_endpoint_index_of(point_index);
+                if next_point_index == 0 && !self.closed {
+                    break;
+                }
+                let segment = self.segment_after(point_index);
+                segments.push((point_index, segment, segment.control_bounds()));
+                if next_point_index == 0 {
+                    break;
+                }
+                point_index = next_point_index;
+            }
+        }
+
+        let mut intersections = vec![];
+        let segment_count = segments.len();
+        for (index_a, &(point_index_a, segment_a, bounds_a)) in segments.iter().enumerate() {
+            let bounds_a = bounds_a.dilate(EPSILON);
+            // The last segment of a closed contour is adjacent to the first.
+            let end = if self.closed && index_a == 0 { segment_count - 1 } else { segment_count };
+            let candidates = segments.iter().take(end).skip(index_a + 2);
+            for &(point_index_b, segment_b, bounds_b) in candidates {
+                if !bounds_a.intersects(bounds_b) {
+                    continue;
+                }
+                for (t, _) in segment_a.intersect(&segment_b) {
+                    intersections.push((point_index_a, point_index_b, segment_a.sample(t)));
+                }
+            }
+        }
+        intersections
+    }
+
+    /// Returns true if this contour winds clockwise in a y-down coordinate system, that is, if its
+    /// signed area is positive.
+    #[inline]
+    pub fn is_clockwise(&self) -> bool {
+        self.area() > 0.0
+    }
+
+    /// Returns the direction in which this contour winds, based on the sign of its area.
+    ///
+    /// Degenerate contours with zero area are reported as counterclockwise.
+    #[inline]
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_area(self.area())
+    }
+
+    /// Returns the centroid of the region enclosed by this contour, integrated exactly over its
+    /// curves. Open contours are treated as though they were closed.
+    ///
+    /// If the contour encloses no area, the average of its endpoints is returned instead.
+    pub fn centroid(&self) -> Vector2F {
+        let (area, moment) = self.area_and_first_moment();
+        if area.abs() > EPSILON * EPSILON {
+            moment * (1.0 / area)
+        } else {
+            average_of_endpoints(Some(self).into_iter())
+        }
+    }
+
+    // Returns the signed area and the first moment of area, the integral of the position over
+    // the enclosed region.
+    //
+    // By Green's theorem, the moments are the integrals of x²/2 dy and -y²/2 dx around the
+    // boundary. These are polynomials of degree at most 8 in t, which five-point Gauss-Legendre
+    // quadrature integrates exactly.
+    fn area_and_first_moment(&self) -> (f32, Vector2F) {
+        let (mut area, mut moment) = (0.0, Vector2F::zero());
+        for segment in self.fill_segments() {
+            if segment.is_none() {
+                continue;
+            }
+            for &(t, weight) in &GAUSS_LEGENDRE_NODES {
+                let (point, derivative) = (segment.sample(t), segment.derivative_at(t));
+                area += weight * 0.5 * point.det(derivative);
+                moment += vec2f(point.x() * point.x() * derivative.y(),
+                                -point.y() * point.y() * derivative.x()) * (weight * 0.5);
+            }
+        }
+        (area, moment)
+    }
+
+    /// Returns the length of this contour, including the closing segment if it is closed.
+    pub fn length(&self) -> f32 {
+        self.iter(ContourIterFlags::empty()).map(|segment| segment.arc_length()).sum()
+    }
+
+    // Returns the part of this contour between the distances `start` and `end` along it, as an
+    // open contour.
+    fn trimmed(&self, start: f32, end: f32) -> Contour {
+        let mut contour = Contour::new();
+        let mut segment_start = 0.0;
+        for segment in self.iter(ContourIterFlags::empty()) {
+            let length = segment.arc_length();
+            let segment_end = segment_start + length;
+            if length > 0.0 && segment_start < end && segment_end > start {
+                let piece = segment.slice(segment.time_for_distance(start - segment_start),
+                                          segment.time_for_distance(end - segment_start));
+                let mut flags = PushSegmentFlags::UPDATE_BOUNDS;
+                if contour.is_empty() {
+                    flags |= PushSegmentFlags::INCLUDE_FROM_POINT;
+                }
+                contour.push_segment(&piece, flags);
+            }
+            segment_start = segment_end;
+        }
+        contour
+    }
+
+    /// Cuts this contour into open contours, one per "on" interval of the dash pattern.
+    ///
+    /// `pattern` alternates between the arc lengths of "on" and "off" intervals, starting with
+    /// "on", and `phase` is the distance into the pattern at which the start of the contour lies.
+    /// Dashes continue across the start of closed contours. If the pattern is empty, contains
+    /// negative lengths, or sums to zero, the contour is returned unchanged.
+    pub fn dash(&self, pattern: &[f32], phase: f32) -> Vec<Contour> {
+        dash::dash_contour(self, pattern, phase)
+    }
+
+    /// Returns points spaced `spacing` apart along this contour by arc length, starting with the
+    /// first point.
+    ///
+    /// The spacing carries across segment boundaries. Each curve is measured with a table of
+    /// chord lengths at evenly spaced values of t, refined until the length it gives for the curve
+    /// is within `tolerance` of the true arc length, so each point lies within about `tolerance`
+    /// of its ideal distance from the start of its segment. For closed contours the closing
+    /// segment is walked too, but its end is not emitted, since it coincides with the start. Empty
+    /// and zero-length contours produce no points, as do spacings that are not positive.
+    pub fn sample_uniform(&self, spacing: f32, tolerance: f32) -> Vec<Vector2F> {
+        let mut points = vec![];
+        if self.is_empty() || spacing.is_nan() || spacing <= 0.0 {
+            return points;
+        }
+
+        let mut segments = vec![];
+        let mut total_length = 0.0;
+        for segment in self.iter(ContourIterFlags::empty()) {
+            let table = arc_length_table(&segment, tolerance);
+            total_length += table[table.len() - 1];
+            segments.push((segment, table));
+        }
+        if total_length <= 0.0 {
+            return points;
+        }
+
+        points.push(self.position_of(0));
+        let end_distance = if self.closed {
+            total_length - EPSILON
+        } else {
+            total_length + EPSILON
+        };
+
+        let (mut next_distance, mut segment_start_distance) = (spacing, 0.0);
+        for (segment, table) in &segments {
+            let segment_length = table[table.len() - 1];
+            while next_distance <= end_distance &&
+                    next_distance <= segment_start_distance + segment_length + EPSILON {
+                let distance = f32::min(next_distance - segment_start_distance, segment_length);
+                points.push(segment.sample(time_for_distance_in_table(table, distance)));
+                next_distance += spacing;
+            }
+            segment_start_distance += segment_length;
+        }
+
+        points
+    }
+
+    /// Removes points from runs of line segments using the Douglas-Peucker algorithm, so that the
+    /// simplified lines deviate from the originals by no more than `tolerance`.
+    ///
+    /// Curves are left untouched, and their endpoints are always kept. For a closed contour
+    /// consisting only of lines, the first point is kept and the closing segment takes part in the
+    /// simplification; otherwise, the first and last points are kept.
+    pub fn simplify(&mut self, tolerance: f32) {
+        if self.points.len() < 3 {
+            return;
+        }
+
+        let input = self.take();
+        self.closed = input.closed;
+        let point_count = input.points.len();
+
+        if input.closed && input.flags.iter().all(|flags| flags.is_empty()) {
+            let mut polyline = input.points.clone();
+            polyline.push(input.points[0]);
+            let keep = simplify_polyline(&polyline, tolerance);
+            for (&point, _) in input.points.iter().zip(keep).filter(|&(_, keep)| keep) {
+                self.push_point(point, PointFlags::empty(), true);
+            }
+            return;
+        }
+
+        let mut point_index = 0;
+        while point_index < point_count {
+            // Gather the run of lines starting at this endpoint.
+            let mut run_end = point_index;
+            while run_end + 1 < point_count && input.flags[run_end + 1].is_empty() {
+                run_end += 1;
+            }
+            let keep = simplify_polyline(&input.points[point_index..=run_end], tolerance);
+            for (offset, keep) in keep.into_iter().enumerate() {
+                if keep {
+                    self.push_point(input.points[point_index + offset], PointFlags::empty(), true);
+                }
+            }
+
+            // Copy the control points of the curve that follows. Its endpoint starts the next run.
+            point_index = run_end + 1;
+            while point_index < point_count && !input.flags[point_index].is_empty() {
+                self.push_point(input.points[point_index], input.flags[point_index], true);
+                point_index += 1;
+            }
+        }
+    }
+
+    /// Removes endpoints that duplicate the preceding endpoint, or that lie within `epsilon` of the
+    /// straight line between their neighbors, as long as the segments on either side are lines.
+    ///
+    /// Control points are never removed, so curves keep their shape. The first point is always
+    /// kept. The bounds are recomputed.
+    pub fn remove_degenerate_points(&mut self, epsilon: f32) {
+        if self.points.len() < 2 {
+            return;
+        }
+
+        let input = self.take();
+        self.closed = input.closed;
+
+        let mut points: Vec<Vector2F> = Vec::with_capacity(input.points.len());
+        let mut flags: Vec<PointFlags> = Vec::with_capacity(input.flags.len());
+        for (&point, &point_flags) in input.points.iter().zip(input.flags.iter()) {
+            if point_flags.is_empty() && flags.last() == Some(&PointFlags::empty()) &&
+                    (point - *points.last().unwrap()).length() <= epsilon {
+                continue;
+            }
+
+            points.push(point);
+            flags.push(point_flags);
+            while points.len() >= 3 && flags[flags.len() - 3..].iter().all(|f| f.is_empty()) {
+                let count = points.len();
+                if !point_is_between(points[count - 3], points[count - 2], points[count - 1],
+                                     epsilon) {
+                    break;
+                }
+                points.remove(count - 2);
+                flags.remove(count - 2);
+            }
+        }
+
+        // The closing segment of a closed contour is a line, so apply the same rules across it.
+        if self.closed {
+            while points.len() >= 2 && flags[flags.len() - 1].is_empty() &&
+                    flags[flags.len() - 2].is_empty() &&
+                    (points[points.len() - 1] - points[0]).length() <= epsilon {
+                points.pop();
+                flags.pop();
+            }
+            while points.len() >= 3 && flags[flags.len() - 2..].iter().all(|f| f.is_empty()) &&
+                    point_is_between(points[points.len() - 2],
+                                     points[points.len() - 1],
+                                     points[0],
+                                     epsilon) {
+                points.pop();
+                flags.pop();
+            }
+        }
+
+        for (point, point_flags) in points.into_iter().zip(flags) {
+            self.push_point(point, point_flags, true);
+        }
+    }
+
+    /// Removes segments whose points, including control points, all lie within `epsilon` of their
+    /// start; see `Segment::is_degenerate()`.
+    ///
+    /// The segment after each one removed is stretched to start where the segment before it
+    /// ended, so that the contour stays connected. Unlike `remove_degenerate_points()`, this also
+    /// removes curves that have collapsed to a point, but it leaves collinear points alone. The
+    /// bounds are recomputed if anything is removed.
+    ///
+    /// Returns the number of segments removed.
+    pub fn remove_degenerate_segments(&mut self, epsilon: f32) -> u32 {
+        let segments = self.segments_for_editing();
+        let removed_count =
+            segments.iter().filter(|segment| segment.is_degenerate(epsilon)).count();
+        if removed_count == 0 {
+            return 0;
+        }
+
+        let start = self.points[0];
+        let mut kept_segments: Vec<Segment> = Vec::with_capacity(segments.len());
+        for mut segment in segments {
+            if segment.is_degenerate(epsilon) {
+                continue;
+            }
+            let from = kept_segments.last().map_or(start, |last| last.baseline.to());
+            segment.baseline.set_from(from);
+            kept_segments.push(segment);
+        }
+        if self.closed {
+            if let Some(last_segment) = kept_segments.last_mut() {
+                last_segment.baseline.set_to(start);
+            }
+        }
+        self.rebuild_from_segments(start, &kept_segments);
+        removed_count as u32
+    }
+
+    /// Returns a copy of this contour with every quadratic curve elevated to the equivalent cubic.
+    ///
+    /// Lines are kept as they are; use `to_cubics()` to convert them as well.
+    pub fn to_cubic(&self) -> Contour {
+        self.elevate_to_cubic(false)
+    }
+
+    /// Returns a copy of this contour in which every segment is a cubic curve with the same shape,
+    /// for consumers that handle only cubics.
+    ///
+    /// This is `to_cubic()`, except that lines are converted too, as `Segment::to_cubic()` does,
+    /// with their control points a third and two thirds of the way along them. If the contour is
+    /// closed, its closing line becomes a cubic back to the first point too, which leaves the
+    /// implicit closing segment with zero length.
+    pub fn to_cubics(&self) -> Contour {
+        self.elevate_to_cubic(true)
+    }
+
+    // Shared implementation of `to_cubic()` and `to_cubics()`.
+    fn elevate_to_cubic(&self, convert_lines: bool) -> Contour {
+        let capacity = if convert_lines { self.points.len() * 3 } else { self.points.len() };
+        let mut contour = Contour::with_capacity(capacity);
+        if let Some(&first_position) = self.points.first() {
+            contour.push_endpoint(first_position);
+        }
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line if !convert_lines => {
+                    contour.push_endpoint(segment.baseline.to())
+                }
+                _ => contour.push_segment(&segment.to_cubic(), PushSegmentFlags::UPDATE_BOUNDS),
+            }
+        }
+        let last_position = self.last_position();
+        if let (true, true, Some(last_position)) = (convert_lines, self.closed, last_position) {
+            let first_position = self.position_of(0);
+            if last_position != first_position {
+                let closing_segment = Segment::line(LineSegment2F::new(last_position,
+                                                                       first_position));
+                contour.push_segment(&closing_segment.to_cubic(),
+                                     PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        }
+        contour.closed = self.closed;
+        contour
+    }
+
+    /// Returns a copy of this contour in which every segment longer than `max_length` has been
+    /// split in half, repeatedly, until no piece is.
+    ///
+    /// Unlike flattening, this keeps the kind of each segment, so curves stay curved. The closing
+    /// line of a closed contour is split too, with its last piece left as the new closing line. If
+    /// `max_length` is not positive, the contour is returned unchanged.
+    pub fn subdivide_to_max_length(&self, max_length: f32) -> Contour {
+        if max_length.is_nan() || max_length <= 0.0 {
+            return self.clone();
+        }
+
+        let mut contour = Contour::with_capacity(self.points.len());
+        if let Some(&first_position) = self.points.first() {
+            contour.push_endpoint(first_position);
+        }
+        let mut pieces = vec![];
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            subdivide_segment(&segment, max_length, 0, &mut pieces);
+            for piece in pieces.drain(..) {
+                contour.push_segment(&piece, PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        }
+        if let (true, Some(last_position)) = (self.closed, self.last_position()) {
+            let closing_segment = Segment::line(LineSegment2F::new(last_position,
+                                                                   self.position_of(0)));
+            subdivide_segment(&closing_segment, max_length, 0, &mut pieces);
+            pieces.pop();
+            for piece in pieces.drain(..) {
+                contour.push_segment(&piece, PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        }
+        contour.closed = self.closed;
+        contour
+    }
+
+    /// Returns a copy of this contour with every cubic curve replaced by quadratics that deviate
+    /// from it by no more than `tolerance`. See `Segment::to_quadratics()`.
+    pub fn to_quadratic(&self, tolerance: f32) -> Contour {
+        self.map_curves(|segment, contour| {
+            for quadratic in segment.to_quadratics(tolerance) {
+                contour.push_segment(&quadratic, PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        })
+    }
+
+    // Rebuilds this contour, keeping lines and letting `push_curve` push replacements for the
+    // curves.
+    fn map_curves<F>(&self, mut push_curve: F) -> Contour where F: FnMut(&Segment, &mut Contour) {
+        let mut contour = Contour::with_capacity(self.points.len());
+        if let Some(&first_position) = self.points.first() {
+            contour.push_endpoint(first_position);
+        }
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => contour.push_endpoint(segment.baseline.to()),
+                SegmentKind::Quadratic | SegmentKind::Cubic => push_curve(&segment, &mut contour),
+            }
+        }
+        contour.closed = self.closed;
+        contour
+    }
+
+    /// Returns the straight edges of this contour, including the closing edge if it is closed.
+    ///
+    /// Curves are replaced with edges that deviate from them by no more than `tolerance`, as in
+    /// `flatten_into()`. No new contour is built: curves are subdivided only as the iterator
+    /// advances, so stopping early skips the remaining work.
+    pub fn edges(&self, tolerance: f32) -> impl Iterator<Item = LineSegment2F> + '_ {
+        self.iter(ContourIterFlags::empty()).flat_map(move |segment| {
+            let mut stack = match segment.kind {
+                SegmentKind::None => vec![],
+                SegmentKind::Line => vec![(segment, 0)],
+                SegmentKind::Quadratic | SegmentKind::Cubic => vec![(segment.to_cubic(), 0)],
+            };
+            iter::from_fn(move || {
+                loop {
+                    let (segment, depth) = stack.pop()?;
+                    if segment.is_line() || depth == MAX_FLATTENING_SUBDIVISIONS ||
+                            segment.as_cubic_segment().is_flat(tolerance) {
+                        return Some(segment.baseline);
+                    }
+                    let (before, after) = segment.as_cubic_segment().split(0.5);
+                    stack.push((after, depth + 1));
+                    stack.push((before, depth + 1));
+                }
+            })
+        })
+    }
+
+    /// Replaces the contents of `output` with a copy of this contour in which every curve has been
+    /// replaced with line segments that deviate from it by no more than `tolerance`.
+    ///
+    /// Curves are adaptively subdivided, so nearly-straight curves produce few points. The buffers
+    /// of `output` are reused.
+    pub fn flatten_into(&self, tolerance: f32, output: &mut Contour) {
+        output.clear();
+        if let Some(&first_position) = self.points.first() {
+            output.push_endpoint(first_position);
+        }
+
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => output.push_endpoint(segment.baseline.to()),
+                SegmentKind::Quadratic | SegmentKind::Cubic => {
+                    flatten_cubic(&segment.to_cubic(), tolerance, 0, output);
+                }
+            }
+        }
+
+        output.closed = self.closed;
+
+        fn flatten_cubic(segment: &Segment, tolerance: f32, depth: u32, output: &mut Contour) {
+            let cubic = segment.as_cubic_segment();
+            if depth == MAX_FLATTENING_SUBDIVISIONS || cubic.is_flat(tolerance) {
+                output.push_endpoint(segment.baseline.to());
+                return;
+            }
+
+            let (before, after) = cubic.split(0.5);
+            flatten_cubic(&before, tolerance, depth + 1, output);
+            flatten_cubic(&after, tolerance, depth + 1, output);
+        }
+    }
+
+    // Iterates over the segments of this contour the way they are filled: open contours are
+    // implicitly closed with a line back to the first point.
+    pub(crate) fn fill_segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        let close_segment = match (self.closed, self.points.first(), self.points.last()) {
+            (false, Some(&first), Some(&last)) if first != last => {
+                Some(Segment::line(LineSegment2F::new(last, first)))
+            }
+            _ => None,
+        };
+        self.iter(ContourIterFlags::empty()).chain(close_segment)
+    }
+
+    // Returns true if the first point of `other` lies inside this contour, which is taken to mean
+    // that all of `other` does.
+    fn encloses(&self, other: &Contour) -> bool {
+        let sample_point = match other.points.first() {
+            None => return false,
+            Some(&point) => point,
+        };
+        self.bounds.contains_point(sample_point) &&
+            matches!(self.winding_number(sample_point), Some(winding) if winding != 0)
+    }
+
+    // Returns the winding number of this contour around the given point by casting a ray to the
+    // right of it, or `None` if the point lies on the contour.
+    pub(crate) fn winding_number(&self, point: Vector2F) -> Option<i32> {
+        let mut winding_number = 0;
+        for segment in self.fill_segments() {
+            winding_number += segment_winding_number(&segment, point)?;
+        }
+        Some(winding_number)
+    }
+
+    /// Reverses the direction of this contour.
+    ///
+    /// The reversed contour starts at the former last point. Control points stay attached to
+    /// their segments, so the set of points is unchanged and the bounds need not be recomputed.
+    pub fn reverse(&mut self) {
+        if self.points.len() < 2 {
+            return;
+        }
+
+        self.points.reverse();
+        self.flags.reverse();
+
+        // The two control points of each cubic are now in the wrong order, so swap their flags.
+        let mut point_index = 0;
+        while point_index + 1 < self.flags.len() {
+            if self.flags[point_index] == PointFlags::CONTROL_POINT_1 &&
+                    self.flags[point_index + 1] == PointFlags::CONTROL_POINT_0 {
+                self.flags[point_index] = PointFlags::CONTROL_POINT_0;
+                self.flags[point_index + 1] = PointFlags::CONTROL_POINT_1;
+                point_index += 2;
+            } else {
+                point_index += 1;
+            }
+        }
+    }
+
+    /// Returns a copy of this contour moved sideways by `distance`, with corners that open up
+    /// filled in by `join`.
+    ///
+    /// Positive distances move the contour inward if it winds clockwise in a y-down coordinate
+    /// system, with positive area like those from `from_rect()`, and outward if it winds
+    /// counterclockwise; negative distances do the opposite. Curves are
+    /// flattened first, so the result consists only of lines. Where neighboring edges overlap after
+    /// offsetting, they are trimmed back to where they cross, but more distant parts of the
+    /// contour may still overlap.
+    pub fn offset(&self, distance: f32, join: LineJoin) -> Contour {
+        ContourOffsetter::new(self, distance, join).offset()
+    }
+
+    /// Returns a copy of this contour with its convex corners rounded off.
+    ///
+    /// Only corners where two lines meet at an endpoint are rounded. Each is replaced by a cubic
+    /// approximating a circular arc of `radius` that is tangent to both lines. If the lines are too
+    /// short for that, the arc shrinks so that it uses up no more than half of either line, so
+    /// neighboring arcs never overlap.
+    pub fn round_corners(&self, radius: f32) -> Contour {
+        self.replace_corners(radius, CornerStyle::Round)
+    }
+
+    /// Returns a copy of this contour with its convex corners cut off by straight bevels.
+    ///
+    /// Like `round_corners()`, but the lines are trimmed back by `size` and joined directly.
+    pub fn chamfer_corners(&self, size: f32) -> Contour {
+        self.replace_corners(size, CornerStyle::Chamfer)
+    }
+
+    fn replace_corners(&self, amount: f32, style: CornerStyle) -> Contour {
+        let segments: Vec<Segment> = self.iter(ContourIterFlags::empty()).filter(|segment| {
+            !segment.is_line() || segment.baseline.square_length() > 0.0
+        }).collect();
+        if segments.is_empty() || amount <= 0.0 {
+            return self.clone();
+        }
+
+        let orientation = if self.area() < 0.0 { -1.0 } else { 1.0 };
+        let corner_count = if self.closed { segments.len() } else { segments.len() - 1 };
+        let corners: Vec<Option<Corner>> = (0..corner_count).map(|corner_index| {
+            let next_segment = &segments[(corner_index + 1) % segments.len()];
+            Corner::new(&segments[corner_index], next_segment, amount, style, orientation)
+        }).collect();
+
+        // Start at the end of the last corner if there is one, so that no point is repeated.
+        let mut contour = Contour::with_capacity(self.points.len() + corner_count * 3);
+        let last_corner = if self.closed { corners[corner_count - 1] } else { None };
+        match last_corner {
+            Some(corner) => {
+                contour.push_endpoint(corner.from);
+                corner.push_to(&mut contour);
+            }
+            None => contour.push_endpoint(segments[0].baseline.from()),
+        }
+
+        for (segment_index, segment) in segments.iter().enumerate() {
+            let corner = corners.get(segment_index).cloned().flatten();
+            if !segment.is_line() {
+                contour.push_segment(segment, PushSegmentFlags::UPDATE_BOUNDS);
+            } else if !self.closed || segment_index + 1 < segments.len() {
+                let to = match corner {
+                    Some(corner) => corner.from,
+                    None => segment.baseline.to(),
+                };
+                if contour.last_position() != Some(to) {
+                    contour.push_endpoint(to);
+                }
+            }
+
+            if segment_index + 1 < segments.len() {
+                if let Some(corner) = corner {
+                    corner.push_to(&mut contour);
+                }
+            }
+        }
+
+        contour.closed = self.closed;
+        contour
+    }
+
+    /// Returns true if this contour has the same points, point types, and closedness as `other`,
+    /// allowing each coordinate to differ by up to `epsilon`.
+    pub fn approx_eq(&self, other: &Contour, epsilon: f32) -> bool {
+        self.closed == other.closed && self.flags == other.flags &&
+            self.points.len() == other.points.len() &&
+            self.points.iter().zip(other.points.iter()).all(|(&point, &other_point)| {
+                point.approx_eq(other_point, epsilon)
+            })
+    }
+
+    /// Returns true if this contour traces the same segments as `other`, allowing each coordinate
+    /// to differ by up to `epsilon`, even if the two start at different endpoints.
+    ///
+    /// Only closed contours may start at different endpoints; open contours must match from the
+    /// start, as in `approx_eq()`. Each segment must match one of the same kind, so a cubic never
+    /// matches a line or a quadratic. The direction matters too, since it decides the winding:
+    /// compare against a reversed copy of `other` to ignore it.
+    pub fn is_same_shape(&self, other: &Contour, epsilon: f32) -> bool {
+        if self.closed != other.closed {
+            return false;
+        }
+        let (segments, other_segments) = (self.segments_for_editing(),
+                                          other.segments_for_editing());
+        if segments.len() != other_segments.len() {
+            return false;
+        }
+        if segments.is_empty() {
+            return match (self.points.first(), other.points.first()) {
+                (Some(&point), Some(&other_point)) => point.approx_eq(other_point, epsilon),
+                (None, None) => true,
+                _ => false,
+            };
+        }
+
+        let start_count = if self.closed { segments.len() } else { 1 };
+        (0..start_count).any(|start_index| {
+            segments.iter().enumerate().all(|(segment_index, segment)| {
+                let other_segment = &other_segments[(start_index + segment_index) %
+                                                    segments.len()];
+                segment_approx_eq(segment, other_segment, epsilon)
+            })
+        })
+    }
+
+    pub fn dilate(&mut self, amount: Vector2F, orientation: Orientation) {
+        ContourDilator::new(self, amount, orientation).dilate();
+        self.bounds = self.bounds.dilate(amount);
+    }
+
+    fn prepare_for_tiling(&mut self, view_box: RectF, scratch: &mut Contour) {
+        // Snap points to the view box bounds. This mops up floating point error from the clipping
+        // process.
+
+        // Convert to monotonic, if necessary.
+        if !self.is_monotonic() {
+            self.make_monotonic(scratch);
+        }
+
+        // Update bounds.
+        self.bounds = self
+            .bounds
+            .intersection(view_box)
+            .unwrap_or_else(|| RectF::default());
+    }
+
+    /// Splits the curves of this contour at their vertical extrema, like `to_monotonic()`, but
+    /// only if some curve has such an extremum, and returns whether the contour was rebuilt.
+    ///
+    /// Curves whose control points progress steadily in y are skipped without solving for their
+    /// extrema, so contours that are already monotonic, such as those made only of lines, are
+    /// left alone cheaply and without allocating.
+    pub fn make_monotonic_if_needed(&mut self) -> bool {
+        let needs_split = self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).any(|segment| {
+            match segment.kind {
+                SegmentKind::None | SegmentKind::Line => false,
+                SegmentKind::Quadratic | SegmentKind::Cubic => {
+                    split_cubic_at_y_extrema(&segment.to_cubic()).len() > 1
+                }
+            }
+        });
+        if needs_split {
+            *self = self.to_monotonic();
+        }
+        needs_split
+    }
+
+    // Returns true if the control points of every segment progress steadily in both x and y.
+    fn is_monotonic(&self) -> bool {
+        let mut last_endpoint_index = None;
+        for point_index in 0..(self.points.len() as u32) {
+            if self.point_is_endpoint(point_index) {
+                if let Some(last_endpoint_index) = last_endpoint_index {
+                    if !self.curve_with_endpoints_is_monotonic(last_endpoint_index, point_index) {
+                        return false;
+                    }
+                }
+                last_endpoint_index = Some(point_index);
+            }
+        }
+        true
+    }
+
+    // Rebuilds this contour so that every curve is monotonic.
+    //
+    // The original points are moved into `scratch`, and the arrays previously held by `scratch`
+    // are reused for the output. Passing the same scratch contour to repeated calls therefore
+    // avoids reallocating once the buffers have grown large enough.
+    fn make_monotonic(&mut self, scratch: &mut Contour) {
+        debug!("--- make_monotonic() ---");
+
+        mem::swap(self, scratch);
+        self.clear();
+        self.closed = scratch.closed;
+        self.bounds = scratch.bounds;
+        let contour = &*scratch;
+
+        // Start with the first endpoint, since the pieces below don't include their from points.
+        if let Some(&first_position) = contour.points.first() {
+            self.push_point(first_position, PointFlags::empty(), false);
+        }
+        for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            match segment.kind {
+                SegmentKind::None | SegmentKind::Line => {
+                    self.push_segment(&segment, PushSegmentFlags::empty());
+                }
+                SegmentKind::Quadratic | SegmentKind::Cubic => {
+                    handle_cubic(self, &segment.to_cubic());
+                }
+            }
+        }
+
+        fn handle_cubic(contour: &mut Contour, segment: &Segment) {
+            debug!("handle_cubic({:?})", segment);
+
+            for piece in split_cubic_at_y_extrema(segment) {
+                contour.push_segment(&piece, PushSegmentFlags::empty());
+            }
+        }
+    }
+
+    fn curve_with_endpoints_is_monotonic(
+        &self,
+        start_endpoint_index: u32,
+        end_endpoint_index: u32,
+    ) -> bool {
+        let start_position = self.points[start_endpoint_index as usize];
+        let end_position = self.points[end_endpoint_index as usize];
+
+        if start_position.x() <= end_position.x() {
+            for point_index in start_endpoint_index..end_endpoint_index {
+                if self.points[point_index as usize].x() > self.points[point_index as usize + 1].x()
+                {
+                    return false;
+                }
+            }
+        } else {
+            for point_index in start_endpoint_index..end_endpoint_index {
+                if self.points[point_index as usize].x() < self.points[point_index as usize + 1].x()
+                {
+                    return false;
+                }
+            }
+        }
+
+        if start_position.y() <= end_position.y() {
+            for point_index in start_endpoint_index..end_endpoint_index {
+                if self.points[point_index as usize].y() > self.points[point_index as usize + 1].y()
+                {
+                    return false;
+                }
+            }
+        } else {
+            for point_index in start_endpoint_index..end_endpoint_index {
+                if self.points[point_index as usize].y() < self.points[point_index as usize + 1].y()
+                {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Cuts this contour at the endpoint `point_index` into two open contours, both of which
+    /// include the point at the cut.
+    ///
+    /// The first piece runs from the start of the contour to the cut and the second from the cut
+    /// to the end. If this contour is closed, the second piece ends with the closing line, so
+    /// together the pieces cover the whole contour. See `open_at()` to cut a closed contour only
+    /// once.
+    pub fn split_at(&self, point_index: u32) -> (Contour, Contour) {
+        debug_assert!(self.point_is_endpoint(point_index));
+        let point_index = point_index as usize;
+        let first = self.open_piece(0..(point_index + 1));
+        let mut second = self.open_piece(point_index..self.points.len());
+        if self.closed {
+            second.push_endpoint(self.points[0]);
+        }
+        (first, second)
+    }
+
+    /// Opens this contour at the endpoint `point_index`, returning an open contour that starts
+    /// and ends there and passes through every segment of this one, including the closing line.
+    ///
+    /// An open contour is returned unchanged, since it has no segment leading back to the start.
+    pub fn open_at(&self, point_index: u32) -> Contour {
+        debug_assert!(self.point_is_endpoint(point_index));
+        if !self.closed {
+            return self.clone();
+        }
+        let point_index = point_index as usize;
+        let mut contour = self.open_piece(point_index..self.points.len());
+        for index in 0..(point_index + 1) {
+            contour.push_point(self.points[index], self.flags[index], true);
+        }
+        contour
+    }
+
+    fn open_piece(&self, range: Range<usize>) -> Contour {
+        let mut contour = Contour {
+            points: self.points[range.clone()].to_vec(),
+            flags: self.flags[range].to_vec(),
+            bounds: RectF::default(),
+            closed: false,
+        };
+        contour.recompute_bounds();
+        contour
+    }
+
+    /// Appends the points of `other` to this contour, connecting the two with a line.
+    ///
+    /// If the last point of this contour and the first point of `other` are no farther apart than
+    /// `weld_tolerance`, the first point of `other` is dropped instead, so that the contours meet
+    /// at a single point. Whether this contour is closed doesn't change.
+    pub fn join(&mut self, other: &Contour, weld_tolerance: f32) {
+        let mut first_index = 0;
+        if let (Some(last_position), Some(&other_first_position)) =
+                (self.last_position(), other.points.first()) {
+            if (other_first_position - last_position).length() <= weld_tolerance {
+                first_index = 1;
+            }
+        }
+
+        self.push_points(&other.points[first_index..], &other.flags[first_index..]);
+    }
+
+    /// Splits the segment that starts at the endpoint `point_index` in two by inserting a new
+    /// endpoint at the point of the segment nearest to `point`.
+    ///
+    /// The control points of the two halves are recomputed so that the shape doesn't change. For
+    /// the last endpoint of a closed contour, this splits the closing line. Nothing happens if no
+    /// segment starts at `point_index`, as at the end of an open contour.
+    pub fn insert_point_after(&mut self, point_index: u32, point: Vector2F) {
+        debug_assert!(self.point_is_endpoint(point_index));
+
+        let mut segments = self.segments_for_editing();
+        let segment_index = self.endpoint_ordinal(point_index);
+        let segment = match segments.get(segment_index) {
+            None => return,
+            Some(segment) => *segment,
+        };
+
+        let (before, after) = segment.split(segment.nearest_t(point));
+        segments[segment_index] = before;
+        segments.insert(segment_index + 1, after);
+        let start = self.points[0];
+        self.rebuild_from_segments(start, &segments);
+    }
+
+    /// Deletes the endpoint `point_index`, joining the segments on either side of it into one.
+    ///
+    /// Two lines are joined into a line. Otherwise they are joined into a cubic curve that leaves
+    /// in the direction of the first segment and arrives in the direction of the second, which
+    /// approximates the original shape. Removing the first or last endpoint of an open contour
+    /// simply drops the segment attached to it.
+    pub fn remove_point(&mut self, point_index: u32) {
+        debug_assert!(self.point_is_endpoint(point_index));
+
+        let mut segments = self.segments_for_editing();
+        let endpoint_index = self.endpoint_ordinal(point_index);
+        let start;
+        if self.closed && segments.len() <= 1 {
+            self.clear();
+            self.closed = true;
+            return;
+        } else if self.closed && endpoint_index == 0 {
+            let (incoming, outgoing) = (segments.pop().unwrap(), segments.remove(0));
+            segments.push(join_segments(&incoming, &outgoing));
+            start = outgoing.baseline.to();
+        } else if endpoint_index == 0 {
+            start = match segments.first() {
+                None => {
+                    self.clear();
+                    return;
+                }
+                Some(segment) => segment.baseline.to(),
+            };
+            segments.remove(0);
+        } else if endpoint_index == segments.len() {
+            segments.pop();
+            start = self.points[0];
+        } else {
+            let joined = join_segments(&segments[endpoint_index - 1], &segments[endpoint_index]);
+            segments[endpoint_index - 1] = joined;
+            segments.remove(endpoint_index);
+            start = self.points[0];
+        }
+        self.rebuild_from_segments(start, &segments);
+    }
+
+    // Returns the segments of this contour, including the closing line of a closed contour unless
+    // it has zero length. For a closed contour, the last segment ends at the first point.
+    fn segments_for_editing(&self) -> Vec<Segment> {
+        let mut segments: Vec<Segment> = self.iter(ContourIterFlags::empty()).collect();
+        if self.closed {
+            if let Some(last_segment) = segments.last() {
+                if last_segment.is_line() && last_segment.baseline.square_length() == 0.0 &&
+                        segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+        }
+        segments
+    }
+
+    // Replaces the points of this contour with `start` followed by the given segments. The final
+    // segment of a closed contour is left to the implicit closing line if it can be.
+    fn rebuild_from_segments(&mut self, start: Vector2F, segments: &[Segment]) {
+        let closed = self.closed;
+        self.clear();
+        self.push_endpoint(start);
+        for (segment_index, segment) in segments.iter().enumerate() {
+            let is_closing_line = closed && segment_index + 1 == segments.len() &&
+                segment.is_line() && segment.baseline.to() == start;
+            if !is_closing_line {
+                self.push_segment(segment, PushSegmentFlags::UPDATE_BOUNDS);
+            }
+        }
+        self.closed = closed;
+    }
+
+    // Returns the number of endpoints before `point_index`, which is also the index of the segment
+    // that starts there.
+    fn endpoint_ordinal(&self, point_index: u32) -> usize {
+        self.flags[..point_index as usize].iter().filter(|flags| flags.is_empty()).count()
+    }
+
+    /// Rebuilds the bounds of this contour from scratch from its points.
+    ///
+    /// Pushing points grows the bounds as needed, but the bounds never shrink on their own.
+    /// Operations that move or remove points, and so may shrink the contour, must call this
+    /// afterward. The bounds of an empty contour are the zero rectangle.
+    pub fn recompute_bounds(&mut self) {
+        self.bounds = RectF::default();
+        for (point_index, &point) in self.points.iter().enumerate() {
+            union_rect(&mut self.bounds, point, point_index == 0);
+        }
+    }
+
+    // Use this function to keep bounds up to date when mutating paths. See `Outline::transform()`
+    // for an example of use.
+    pub(crate) fn update_bounds(&self, bounds: &mut Option<RectF>) {
+        *bounds = Some(match *bounds {
+            None => self.bounds,
+            Some(bounds) => bounds.union_rect(self.bounds),
+        })
+    }
+}
+
+/// Points are compared by their bit patterns, so that equality agrees with `Hash`. This means
+/// that `0.0` and `-0.0` are different, and that NaN equals itself.
+impl PartialEq for Contour {
+    // The bounds are derived from the points, so they need not be compared.
+    #[inline]
+    fn eq(&self, other: &Contour) -> bool {
+        self.closed == other.closed && self.flags == other.flags &&
+            self.points.len() == other.points.len() &&
+            self.points.iter().zip(other.points.iter()).all(|(&point, &other_point)| {
+                point_bits(point) == point_bits(other_point)
+            })
+    }
+}
+
+impl Eq for Contour {}
+
+/// Points are hashed by their bit patterns; see the `PartialEq` implementation.
+impl Hash for Contour {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.closed.hash(state);
+        self.flags.hash(state);
+        for &point in &self.points {
+            point_bits(point).hash(state);
+        }
+    }
+}
+
+// Writes a number with a fixed number of decimal places, without the sign of a negative number
+// that rounds to zero, which PDF readers accept but which looks wrong.
+fn push_postscript_number(string: &mut String, value: f32, precision: usize) {
+    let formatted = format!("{:.*}", precision, value);
+    match formatted.strip_prefix('-') {
+        Some(unsigned) if unsigned.chars().all(|c| c == '0' || c == '.') => {
+            string.push_str(unsigned)
+        }
+        _ => string.push_str(&formatted),
+    }
+}
+
+fn segment_approx_eq(segment: &Segment, other: &Segment, epsilon: f32) -> bool {
+    let ctrl_count = match segment.kind {
+        SegmentKind::None | SegmentKind::Line => 0,
+        SegmentKind::Quadratic => 1,
+        SegmentKind::Cubic => 2,
+    };
+    segment.kind == other.kind &&
+        segment.baseline.from().approx_eq(other.baseline.from(), epsilon) &&
+        segment.baseline.to().approx_eq(other.baseline.to(), epsilon) &&
+        (ctrl_count < 1 || segment.ctrl.from().approx_eq(other.ctrl.from(), epsilon)) &&
+        (ctrl_count < 2 || segment.ctrl.to().approx_eq(other.ctrl.to(), epsilon))
+}
+
+// Joins two consecutive segments into one, keeping the directions at the far ends. The handles
+// are lengthened in proportion to the distance covered by the joined segment.
+fn join_segments(first: &Segment, second: &Segment) -> Segment {
+    let (from, to) = (first.baseline.from(), second.baseline.to());
+    if first.is_line() && second.is_line() {
+        return Segment::line(LineSegment2F::new(from, to));
+    }
+
+    let (first, second) = (first.to_cubic(), second.to_cubic());
+    let (first_length, second_length) = (first.baseline.vector().length(),
+                                         second.baseline.vector().length());
+    let total_length = first_length + second_length;
+    let mut ctrl = LineSegment2F::new(first.ctrl.from(), second.ctrl.to());
+    if first_length > EPSILON {
+        ctrl.set_from(from + (first.ctrl.from() - from) * (total_length / first_length));
+    }
+    if second_length > EPSILON {
+        ctrl.set_to(to + (second.ctrl.to() - to) * (total_length / second_length));
+    }
+    Segment::cubic(LineSegment2F::new(from, to), ctrl)
+}
+
+// Splits `segment` in half recursively until each piece is no longer than `max_length`, appending
+// the pieces to `pieces`.
+fn subdivide_segment(segment: &Segment, max_length: f32, depth: u32, pieces: &mut Vec<Segment>) {
+    if segment.is_none() {
+        return;
+    }
+    if depth == MAX_FLATTENING_SUBDIVISIONS || segment.arc_length() <= max_length {
+        pieces.push(*segment);
+        return;
+    }
+    let (before, after) = segment.split(0.5);
+    subdivide_segment(&before, max_length, depth + 1, pieces);
+    subdivide_segment(&after, max_length, depth + 1, pieces);
+}
+
+#[inline]
+fn quantize_point(point: Vector2F, grid: f32) -> Vector2F {
+    (point * (1.0 / grid)).round() * grid
+}
+
+#[inline]
+fn point_bits(point: Vector2F) -> (u32, u32) {
+    (point.x().to_bits(), point.y().to_bits())
+}
+
+impl Debug for Contour {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        for (segment_index, segment) in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                                            .enumerate() {
+            if segment_index == 0 {
+                write!(
+                    formatter,
+                    "M {} {}",
+                    segment.baseline.from_x(),
+                    segment.baseline.from_y()
+                )?;
+            }
+
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => {
+                    write!(
+                        formatter,
+                        " L {} {}",
+                        segment.baseline.to_x(),
+                        segment.baseline.to_y()
+                    )?;
+                }
+                SegmentKind::Quadratic => {
+                    write!(
+                        formatter,
+                        " Q {} {} {} {}",
+                        segment.ctrl.from_x(),
+                        segment.ctrl.from_y(),
+                        segment.baseline.to_x(),
+                        segment.baseline.to_y()
+                    )?;
+                }
+                SegmentKind::Cubic => {
+                    write!(
+                        formatter,
+                        " C {} {} {} {} {} {}",
+                        segment.ctrl.from_x(),
+                        segment.ctrl.from_y(),
+                        segment.ctrl.to_x(),
+                        segment.ctrl.to_y(),
+                        segment.baseline.to_x(),
+                        segment.baseline.to_y()
+                    )?;
+                }
+            }
+        }
+
+        if self.closed {
+            write!(formatter, " z")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a contour from drawing commands, pushing the right points and flags.
+///
+/// Only destinations and control points are passed in; each command starts at the current pen
+/// position, which is where the last command ended.
+#[derive(Clone, Debug)]
+pub struct ContourBuilder {
+    contour: Contour,
+}
+
+impl ContourBuilder {
+    #[inline]
+    pub fn new() -> ContourBuilder {
+        ContourBuilder { contour: Contour::new() }
+    }
+
+    /// Starts the contour at `to`.
+    ///
+    /// This must come before any segments are added. Calling it again before then moves the start.
+    #[inline]
+    pub fn move_to(&mut self, to: Vector2F) {
+        debug_assert!(self.contour.len() <= 1, "move_to() called after segments were added");
+        self.contour.clear();
+        self.contour.push_endpoint(to);
+    }
+
+    /// Adds a line from the pen position to `to`. If the contour is empty, this starts it at
+    /// `to` instead.
+    #[inline]
+    pub fn line_to(&mut self, to: Vector2F) {
+        self.contour.push_endpoint(to);
+    }
+
+    /// Adds a quadratic Bézier curve. If the contour is empty, it starts at `ctrl`.
+    #[inline]
+    pub fn quadratic_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.start_if_empty(ctrl);
+        self.contour.push_quadratic(ctrl, to);
+    }
+
+    /// Adds a cubic Bézier curve. If the contour is empty, it starts at `ctrl0`.
+    #[inline]
+    pub fn cubic_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        self.start_if_empty(ctrl0);
+        self.contour.push_cubic(ctrl0, ctrl1, to);
+    }
+
+    /// Closes the contour with a line back to its start, which becomes the pen position.
+    #[inline]
+    pub fn close(&mut self) {
+        self.contour.close();
+    }
+
+    /// Returns the pen position, or `None` if nothing has been drawn yet.
+    #[inline]
+    pub fn current_position(&self) -> Option<Vector2F> {
+        if self.contour.is_closed() {
+            self.contour.points.first().cloned()
+        } else {
+            self.contour.last_position()
+        }
+    }
+
+    #[inline]
+    pub fn build(self) -> Contour {
+        self.contour
+    }
+
+    #[inline]
+    fn start_if_empty(&mut self, point: Vector2F) {
+        if self.contour.is_empty() {
+            self.contour.push_endpoint(point);
+        }
+    }
+}
+
+impl Default for ContourBuilder {
+    #[inline]
+    fn default() -> ContourBuilder {
+        ContourBuilder::new()
+    }
+}
+
+/// Collects the glyph outlines that ttf-parser emits into an `Outline`.
+///
+/// Each subpath becomes a contour, closed if ttf-parser closes it, so subpath starts and closes
+/// need no segment flags. Coordinates are passed through unchanged.
+#[cfg(feature = "ttf-parser")]
+#[derive(Clone, Debug)]
+pub struct GlyphOutlineBuilder {
+    outline: Outline,
+    contour: ContourBuilder,
+}
+
+#[cfg(feature = "ttf-parser")]
+impl GlyphOutlineBuilder {
+    #[inline]
+    pub fn new() -> GlyphOutlineBuilder {
+        GlyphOutlineBuilder { outline: Outline::new(), contour: ContourBuilder::new() }
+    }
+
+    /// Returns the outline, including the last subpath even if it was never closed.
+    #[inline]
+    pub fn build(mut self) -> Outline {
+        self.finish_contour();
+        self.outline
+    }
+
+    fn finish_contour(&mut self) {
+        let contour = mem::take(&mut self.contour).build();
+        self.outline.push_contour(contour);
+    }
+}
+
+#[cfg(feature = "ttf-parser")]
+impl Default for GlyphOutlineBuilder {
+    #[inline]
+    fn default() -> GlyphOutlineBuilder {
+        GlyphOutlineBuilder::new()
+    }
+}
+
+#[cfg(feature = "ttf-parser")]
+impl ttf_parser::OutlineBuilder for GlyphOutlineBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.finish_contour();
+        self.contour.move_to(vec2f(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.contour.line_to(vec2f(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.contour.quadratic_to(vec2f(x1, y1), vec2f(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.contour.cubic_to(vec2f(x1, y1), vec2f(x2, y2), vec2f(x, y));
+    }
+
+    fn close(&mut self) {
+        self.contour.close();
+        self.finish_contour();
+    }
+}
+
+/// Outlines are serialized as their list of contours. Bounds are recomputed on deserialization.
+#[cfg(feature = "serde")]
+impl Serialize for Outline {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedOutlineRef { contours: &self.contours }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Outline {
+    fn deserialize<D>(deserializer: D) -> Result<Outline, D::Error> where D: Deserializer<'de> {
+        let data = SerializedOutline::deserialize(deserializer)?;
+        let mut outline = Outline::new();
+        outline.extend(data.contours);
+        Ok(outline)
+    }
+}
+
+/// Contours are serialized as their points, flags, and closed flag. Bounds are not stored, so
+/// that they can never disagree with the points; they are recomputed on deserialization.
+#[cfg(feature = "serde")]
+impl Serialize for Contour {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        SerializedContourRef {
+            points: &self.points,
+            flags: &self.flags,
+            closed: self.closed,
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Contour {
+    fn deserialize<D>(deserializer: D) -> Result<Contour, D::Error> where D: Deserializer<'de> {
+        let data = SerializedContour::deserialize(deserializer)?;
+        if data.points.len() != data.flags.len() {
+            return Err(D::Error::custom(format!("contour has {} points but {} flags",
+                                                data.points.len(),
+                                                data.flags.len())));
+        }
+        validate_point_flags(&data.flags).map_err(D::Error::custom)?;
+
+        let mut contour = Contour {
+            points: data.points,
+            flags: data.flags,
+            bounds: RectF::default(),
+            closed: data.closed,
+        };
+        contour.recompute_bounds();
+        Ok(contour)
+    }
+}
+
+// Checks that the flags describe a sequence of segments that the rest of this module can walk:
+// the contour begins and ends with an endpoint, and each curve has one control point flagged
+// `CONTROL_POINT_0`, optionally followed by one flagged `CONTROL_POINT_1`.
+#[cfg(feature = "serde")]
+fn validate_point_flags(flags: &[PointFlags]) -> Result<(), String> {
+    if let (Some(first), Some(last)) = (flags.first(), flags.last()) {
+        if !first.is_empty() || !last.is_empty() {
+            return Err("contour must begin and end with an endpoint".to_owned());
+        }
+    }
+    for (point_index, pair) in flags.windows(2).enumerate() {
+        let (prev_flags, point_flags) = (pair[0], pair[1]);
+        let is_valid = if point_flags == PointFlags::CONTROL_POINT_0 {
+            prev_flags.is_empty()
+        } else if point_flags == PointFlags::CONTROL_POINT_1 {
+            prev_flags == PointFlags::CONTROL_POINT_0
+        } else {
+            point_flags.is_empty()
+        };
+        if !is_valid {
+            return Err(format!("invalid point flags {:#x} at point {}",
+                               point_flags.bits(),
+                               point_index + 1));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PointFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PointFlags {
+    fn deserialize<D>(deserializer: D) -> Result<PointFlags, D::Error>
+                      where D: Deserializer<'de> {
+        let bits = u8::deserialize(deserializer)?;
+        PointFlags::from_bits(bits).ok_or_else(|| {
+            D::Error::custom(format!("invalid point flags: {:#x}", bits))
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct SerializedOutlineRef<'a> {
+    contours: &'a [Contour],
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SerializedOutline {
+    contours: Vec<Contour>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct SerializedContourRef<'a> {
+    points: &'a [Vector2F],
+    flags: &'a [PointFlags],
+    closed: bool,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SerializedContour {
+    points: Vec<Vector2F>,
+    flags: Vec<PointFlags>,
+    closed: bool,
+}
+
+/// Each subpath becomes a contour, which is closed if the subpath ends with `ClosePath`. Drawing
+/// after a `ClosePath` without a `MoveTo` starts a new contour at the start of the closed one.
+#[cfg(feature = "kurbo")]
+impl<'a> From<&'a BezPath> for Outline {
+    fn from(path: &'a BezPath) -> Outline {
+        let mut outline = Outline::new();
+        let (mut contour, mut subpath_start) = (Contour::new(), Vector2F::zero());
+        for element in path.elements() {
+            match *element {
+                PathEl::MoveTo(point) => {
+                    outline.push_contour(mem::replace(&mut contour, Contour::new()));
+                    subpath_start = point_to_vector(point);
+                    contour.push_endpoint(subpath_start);
+                    continue;
+                }
+                PathEl::ClosePath => {
+                    contour.close();
+                    outline.push_contour(mem::replace(&mut contour, Contour::new()));
+                    continue;
+                }
+                _ => {}
+            }
+
+            if contour.is_empty() {
+                contour.push_endpoint(subpath_start);
+            }
+            match *element {
+                PathEl::LineTo(point) => contour.push_endpoint(point_to_vector(point)),
+                PathEl::QuadTo(ctrl, point) => {
+                    contour.push_quadratic(point_to_vector(ctrl), point_to_vector(point))
+                }
+                PathEl::CurveTo(ctrl0, ctrl1, point) => {
+                    contour.push_cubic(point_to_vector(ctrl0),
+                                       point_to_vector(ctrl1),
+                                       point_to_vector(point))
+                }
+                PathEl::MoveTo(_) | PathEl::ClosePath => unreachable!(),
+            }
+        }
+        outline.push_contour(contour);
+        outline
+    }
+}
+
+/// Each contour becomes a subpath, ending with `ClosePath` if the contour is closed.
+#[cfg(feature = "kurbo")]
+impl<'a> From<&'a Outline> for BezPath {
+    fn from(outline: &'a Outline) -> BezPath {
+        let mut path = BezPath::new();
+        for contour in &outline.contours {
+            let first_position = match contour.points.first() {
+                None => continue,
+                Some(&first_position) => first_position,
+            };
+            path.move_to(vector_to_point(first_position));
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                let to = vector_to_point(segment.baseline.to());
+                match segment.kind {
+                    SegmentKind::None => {}
+                    SegmentKind::Line => path.line_to(to),
+                    SegmentKind::Quadratic => {
+                        path.quad_to(vector_to_point(segment.ctrl.from()), to)
+                    }
+                    SegmentKind::Cubic => {
+                        path.curve_to(vector_to_point(segment.ctrl.from()),
+                                      vector_to_point(segment.ctrl.to()),
+                                      to)
+                    }
+                }
+            }
+            if contour.closed {
+                path.close_path();
+            }
+        }
+        path
+    }
+}
+
+/// Each subpath becomes a contour, which is closed if its `End` event has the `close` flag set.
+#[cfg(feature = "lyon")]
+impl<'a> From<&'a LyonPath> for Outline {
+    fn from(path: &'a LyonPath) -> Outline {
+        let mut outline = Outline::new();
+        let mut contour = Contour::new();
+        for event in path.iter() {
+            match event {
+                PathEvent::Begin { at } => {
+                    outline.push_contour(mem::replace(&mut contour, Contour::new()));
+                    contour.push_endpoint(lyon_point_to_vector(at));
+                }
+                PathEvent::Line { to, .. } => contour.push_endpoint(lyon_point_to_vector(to)),
+                PathEvent::Quadratic { ctrl, to, .. } => {
+                    contour.push_quadratic(lyon_point_to_vector(ctrl), lyon_point_to_vector(to))
+                }
+                PathEvent::Cubic { ctrl1, ctrl2, to, .. } => {
+                    contour.push_cubic(lyon_point_to_vector(ctrl1),
+                                       lyon_point_to_vector(ctrl2),
+                                       lyon_point_to_vector(to))
+                }
+                PathEvent::End { close, .. } => {
+                    if close {
+                        contour.close();
+                    }
+                    outline.push_contour(mem::replace(&mut contour, Contour::new()));
+                }
+            }
+        }
+        outline.push_contour(contour);
+        outline
+    }
+}
+
+#[cfg(feature = "lyon")]
+impl From<LyonPath> for Outline {
+    #[inline]
+    fn from(path: LyonPath) -> Outline {
+        Outline::from(&path)
+    }
+}
+
+#[cfg(feature = "lyon")]
+fn segment_to_lyon_event(segment: &Segment) -> Option<PathEvent> {
+    let (from, to) = (vector_to_lyon_point(segment.baseline.from()),
+                      vector_to_lyon_point(segment.baseline.to()));
+    match segment.kind {
+        SegmentKind::None => None,
+        SegmentKind::Line => Some(PathEvent::Line { from, to }),
+        SegmentKind::Quadratic => {
+            let ctrl = vector_to_lyon_point(segment.ctrl.from());
+            Some(PathEvent::Quadratic { from, ctrl, to })
+        }
+        SegmentKind::Cubic => {
+            let (ctrl1, ctrl2) = (vector_to_lyon_point(segment.ctrl.from()),
+                                  vector_to_lyon_point(segment.ctrl.to()));
+            Some(PathEvent::Cubic { from, ctrl1, ctrl2, to })
+        }
+    }
+}
+
+#[cfg(feature = "lyon")]
+#[inline]
+fn lyon_point_to_vector(point: LyonPoint) -> Vector2F {
+    vec2f(point.x, point.y)
+}
+
+#[cfg(feature = "lyon")]
+#[inline]
+fn vector_to_lyon_point(vector: Vector2F) -> LyonPoint {
+    lyon_math::point(vector.x(), vector.y())
+}
+
+#[cfg(feature = "kurbo")]
+#[inline]
+fn point_to_vector(point: Point) -> Vector2F {
+    vec2f(point.x as f32, point.y as f32)
+}
+
+#[cfg(feature = "kurbo")]
+#[inline]
+fn vector_to_point(vector: Vector2F) -> Point {
+    Point::new(vector.x() as f64, vector.y() as f64)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PointIndex(u32);
+
+impl PointIndex {
+    /// Packs a contour index and a point index into 32 bits.
+    ///
+    /// `contour` must be at most 0xfff and `point` at most 0xfffff. This is only checked in debug
+    /// builds; use `try_new()` for indices that might be out of range.
+    #[inline]
+    pub fn new(contour: u32, point: u32) -> PointIndex {
+        debug_assert!(contour <= 0xfff);
+        debug_assert!(point <= 0x000f_ffff);
+        PointIndex((contour << 20) | point)
+    }
+
+    /// Like `new()`, but checks that `contour` fits in 12 bits and `point` fits in 20 bits even in
+    /// release builds, where `new()` would silently produce a corrupt index.
+    #[inline]
+    pub fn try_new(contour: u32, point: u32) -> Result<PointIndex, PointIndexError> {
+        if contour > 0xfff {
+            Err(PointIndexError::ContourOutOfRange(contour))
+        } else if point > 0x000f_ffff {
+            Err(PointIndexError::PointOutOfRange(point))
+        } else {
+            Ok(PointIndex::new(contour, point))
+        }
+    }
+
+    #[inline]
+    pub fn contour(self) -> u32 {
+        self.0 >> 20
+    }
+
+    #[inline]
+    pub fn point(self) -> u32 {
+        self.0 & 0x000f_ffff
+    }
+}
+
+/// The reason `PointIndex::try_new()` failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointIndexError {
+    /// The contour index was greater than 0xfff.
+    ContourOutOfRange(u32),
+    /// The point index was greater than 0xfffff.
+    PointOutOfRange(u32),
+}
+
+impl Display for PointIndexError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            PointIndexError::ContourOutOfRange(contour) => {
+                write!(formatter, "contour index {} is out of range", contour)
+            }
+            PointIndexError::PointOutOfRange(point) => {
+                write!(formatter, "point index {} is out of range", point)
+            }
+        }
+    }
+}
+
+impl Error for PointIndexError {}
+
+pub struct ContourIter<'a> {
+    contour: &'a Contour,
+    index: u32,
+    flags: ContourIterFlags,
+}
+
+impl<'a> Iterator for ContourIter<'a> {
+    type Item = Segment;
+
+    #[inline]
+    fn next(&mut self) -> Option<Segment> {
+        let contour = self.contour;
+
+        let include_close_segment = self.contour.closed &&
+            !self.flags.contains(ContourIterFlags::IGNORE_CLOSE_SEGMENT);
+        if (self.index == contour.len() && !include_close_segment) ||
+                self.index == contour.len() + 1 {
+            return None;
+        }
+
+        let point0_index = self.index - 1;
+        let point0 = contour.position_of(point0_index);
+        if self.index == contour.len() {
+            let point1 = contour.position_of(0);
+            self.index += 1;
+            return Some(Segment::line(LineSegment2F::new(point0, point1)));
+        }
+
+        let point1_index = self.index;
+        self.index += 1;
+        let point1 = contour.position_of(point1_index);
+        if contour.point_is_endpoint(point1_index) {
+            return Some(Segment::line(LineSegment2F::new(point0, point1)));
+        }
+
+        let point2_index = self.index;
+        let point2 = contour.position_of(point2_index);
+        self.index += 1;
+        if contour.point_is_endpoint(point2_index) {
+            return Some(Segment::quadratic(LineSegment2F::new(point0, point2), point1));
+        }
+
+        let point3_index = self.index;
+        let point3 = contour.position_of(point3_index);
+        self.index += 1;
+        debug_assert!(contour.point_is_endpoint(point3_index));
+        return Some(Segment::cubic(
+            LineSegment2F::new(point0, point3),
+            LineSegment2F::new(point1, point2),
+        ));
+    }
+}
+
+pub struct MonotonicSegmentIter<'a> {
+    iter: ContourIter<'a>,
+    // The remaining pieces of the current curve, in reverse order.
+    pending: ArrayVec<[Segment; 3]>,
+}
+
+impl<'a> Iterator for MonotonicSegmentIter<'a> {
+    type Item = Segment;
+
+    #[inline]
+    fn next(&mut self) -> Option<Segment> {
+        if let Some(segment) = self.pending.pop() {
+            return Some(segment);
+        }
+
+        let segment = self.iter.next()?;
+        if segment.is_line() || segment.is_none() {
+            return Some(segment);
+        }
+
+        self.pending.extend(split_cubic_at_y_extrema(&segment.to_cubic()).into_iter().rev());
+        self.pending.pop()
+    }
+}
+
+// Splits a cubic segment at its vertical extrema, returning the pieces in order.
+pub(crate) fn split_cubic_at_y_extrema(segment: &Segment) -> ArrayVec<[Segment; 3]> {
+    let mut pieces = ArrayVec::new();
+    match segment.as_cubic_segment().y_extrema() {
+        (Some(t0), Some(t1)) => {
+            let (segments_01, segment_2) = segment.as_cubic_segment().split(t1);
+            let (segment_0, segment_1) = segments_01.as_cubic_segment().split(t0 / t1);
+            pieces.push(segment_0);
+            pieces.push(segment_1);
+            pieces.push(segment_2);
+        }
+        (Some(t0), None) | (None, Some(t0)) => {
+            let (segment_0, segment_1) = segment.as_cubic_segment().split(t0);
+            pieces.push(segment_0);
+            pieces.push(segment_1);
+        }
+        (None, None) => pieces.push(*segment),
+    }
+    pieces
+}
+
+const MIN_ARC_LENGTH_TABLE_SUBDIVISIONS: u32 = 4;
+const MAX_ARC_LENGTH_TABLE_SUBDIVISIONS: u32 = 4096;
+const BOOLEAN_FLATTENING_TOLERANCE: f32 = 0.1;
+const DEBUG_WIREFRAME_TOLERANCE: f32 = 0.1;
+
+// How many pieces each curve is split into when measuring how far it turns. Each piece must turn
+// through less than a half turn for the total to be right.
+const TOTAL_TURNING_CURVE_SAMPLES: u32 = 8;
+
+// The distance of the control points of a cubic Bézier curve approximating a quarter of a unit
+// circle from its endpoints: 4/3 (√2 - 1).
+const CIRCLE_CUBIC_HANDLE: f32 = 0.552_284_8;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CornerStyle {
+    Round,
+    Chamfer,
+}
+
+// The replacement for one corner of a contour: the lines around it are trimmed back to `from` and
+// `to`, which are joined by a cubic with the given control points, or by a line.
+#[derive(Clone, Copy)]
+struct Corner {
+    from: Vector2F,
+    ctrl: Option<LineSegment2F>,
+    to: Vector2F,
+}
+
+impl Corner {
+    fn new(prev_segment: &Segment,
+           next_segment: &Segment,
+           amount: f32,
+           style: CornerStyle,
+           orientation: f32)
+           -> Option<Corner> {
+        if !prev_segment.is_line() || !next_segment.is_line() {
+            return None;
+        }
+
+        let (prev_vector, next_vector) = (prev_segment.baseline.vector(),
+                                          next_segment.baseline.vector());
+        let (prev_length, next_length) = (prev_vector.length(), next_vector.length());
+        let (prev_direction, next_direction) = (prev_vector / prev_length,
+                                                next_vector / next_length);
+        let (sin_turn, cos_turn) = (prev_direction.det(next_direction),
+                                    prev_direction.dot(next_direction));
+        if sin_turn * orientation <= EPSILON {
+            return None;
+        }
+
+        // A circle of radius `r` tangent to both lines touches them `r * tan(turn / 2)` from the
+        // corner.
+        let turn = f32::atan2(sin_turn.abs(), cos_turn);
+        let half_turn_tan = f32::tan(turn * 0.5);
+        let mut trim = match style {
+            CornerStyle::Round => amount * half_turn_tan,
+            CornerStyle::Chamfer => amount,
+        };
+        trim = trim.min(prev_length * 0.5).min(next_length * 0.5);
+
+        let corner_point = prev_segment.baseline.to();
+        let (from, to) = (corner_point - prev_direction * trim,
+                          corner_point + next_direction * trim);
+        let ctrl = match style {
+            CornerStyle::Chamfer => None,
+            CornerStyle::Round => {
+                let radius = trim / half_turn_tan;
+                let handle_length = 4.0 / 3.0 * f32::tan(turn * 0.25) * radius;
+                Some(LineSegment2F::new(from + prev_direction * handle_length,
+                                        to - next_direction * handle_length))
+            }
+        };
+        Some(Corner { from, ctrl, to })
+    }
+
+    fn push_to(&self, contour: &mut Contour) {
+        match self.ctrl {
+            Some(ctrl) => contour.push_cubic(ctrl.from(), ctrl.to(), self.to),
+            None => contour.push_endpoint(self.to),
+        }
+    }
+}
+const MAX_BISECTION_ITERATIONS: u32 = 32;
+pub(crate) const MAX_FLATTENING_SUBDIVISIONS: u32 = 16;
+
+// Returns the cumulative chord lengths of the segment at evenly spaced values of t, starting with
+// zero at t = 0. Lines are measured exactly with a single entry. Curves are measured with twice
+// as many steps at a time until doubling changes the total length by no more than `tolerance`.
+// Chord lengths converge on the arc length from below with an error that falls by about four
+// times per doubling, so that change is an upper bound on the remaining error.
+fn arc_length_table(segment: &Segment, tolerance: f32) -> Vec<f32> {
+    if !segment.is_quadratic() && !segment.is_cubic() {
+        return vec![0.0, segment.baseline.vector().length()];
+    }
+
+    let mut subdivisions = MIN_ARC_LENGTH_TABLE_SUBDIVISIONS;
+    let mut table = chord_length_table(segment, subdivisions);
+    while subdivisions < MAX_ARC_LENGTH_TABLE_SUBDIVISIONS {
+        subdivisions *= 2;
+        let finer_table = chord_length_table(segment, subdivisions);
+        let change = finer_table[finer_table.len() - 1] - table[table.len() - 1];
+        table = finer_table;
+        if change <= tolerance {
+            break;
+        }
+    }
+    table
+}
+
+fn chord_length_table(segment: &Segment, subdivisions: u32) -> Vec<f32> {
+    let mut table = Vec::with_capacity(subdivisions as usize + 1);
+    table.push(0.0);
+    let (mut length, mut last_position) = (0.0, segment.baseline.from());
+    for step in 1..(subdivisions + 1) {
+        let position = segment.sample(step as f32 / subdivisions as f32);
+        length += (position - last_position).length();
+        table.push(length);
+        last_position = position;
+    }
+    table
+}
+
+// Buckets the endpoints of open contours by position for `Outline::weld_contours()`. Endpoint
+// `2 * i` is the start of contour `i` and `2 * i + 1` its end.
+struct EndpointGrid {
+    tolerance: f32,
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(usize, Vector2F)>>,
+}
+
+impl EndpointGrid {
+    fn new(tolerance: f32) -> EndpointGrid {
+        EndpointGrid { tolerance, cell_size: tolerance.max(EPSILON), cells: HashMap::new() }
+    }
+
+    fn cell_of(&self, position: Vector2F) -> (i32, i32) {
+        let cell = (position * (1.0 / self.cell_size)).floor();
+        (cell.x() as i32, cell.y() as i32)
+    }
+
+    fn insert(&mut self, endpoint: usize, position: Vector2F) {
+        let cell = self.cell_of(position);
+        self.cells.entry(cell).or_default().push((endpoint, position));
+    }
+
+    // Returns the endpoint of an unused contour nearest to `position`, if any is within the
+    // tolerance.
+    fn nearest(&self, position: Vector2F, used: &[bool]) -> Option<usize> {
+        let (cell_x, cell_y) = self.cell_of(position);
+        let mut nearest = None;
+        let mut nearest_distance = self.tolerance;
+        for y in (cell_y - 1)..=(cell_y + 1) {
+            for x in (cell_x - 1)..=(cell_x + 1) {
+                let endpoints = self.cells.get(&(x, y)).into_iter().flatten();
+                for &(endpoint, endpoint_position) in endpoints {
+                    let distance = (endpoint_position - position).length();
+                    if !used[endpoint / 2] && distance <= nearest_distance {
+                        nearest = Some(endpoint);
+                        nearest_distance = distance;
+                    }
+                }
+            }
+        }
+        nearest
+    }
+}
+
+// Inverts a table produced by `arc_length_table()`, interpolating linearly between entries.
+fn time_for_distance_in_table(table: &[f32], distance: f32) -> f32 {
+    let upper_index = match table.iter().position(|&length| length >= distance) {
+        None => return 1.0,
+        Some(0) => return 0.0,
+        Some(upper_index) => upper_index,
+    };
+    let (lower_length, upper_length) = (table[upper_index - 1], table[upper_index]);
+    let fraction = (distance - lower_length) / (upper_length - lower_length);
+    (upper_index as f32 - 1.0 + fraction) / (table.len() - 1) as f32
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArcDirection {
+    CW,
+    CCW,
+}
+
+bitflags! {
+    pub struct ContourIterFlags: u8 {
+        const IGNORE_CLOSE_SEGMENT = 1;
+    }
+}
+
+// Returns the signed number of times the segment crosses the ray extending rightward from the
+// point, or `None` if the point lies on the segment.
+fn segment_winding_number(segment: &Segment, point: Vector2F) -> Option<i32> {
+    match segment.kind {
+        SegmentKind::None => Some(0),
+        SegmentKind::Line => line_winding_number(segment.baseline, point),
+        SegmentKind::Quadratic | SegmentKind::Cubic => {
+            let segment = segment.to_cubic();
+            let cubic = segment.as_cubic_segment();
+            if point.y() < cubic.min_y() - EPSILON || point.y() > cubic.max_y() + EPSILON ||
+                    point.x() > cubic.max_x() + EPSILON {
+                return Some(0);
+            }
+
+            match cubic.y_extrema() {
+                (Some(t0), Some(t1)) => {
+                    let (segments_01, segment_2) = cubic.split(t1);
+                    let (segment_0, segment_1) = segments_01.as_cubic_segment().split(t0 / t1);
+                    Some(monotonic_cubic_winding_number(&segment_0, point)? +
+                         monotonic_cubic_winding_number(&segment_1, point)? +
+                         monotonic_cubic_winding_number(&segment_2, point)?)
+                }
+                (Some(t0), None) | (None, Some(t0)) => {
+                    let (segment_0, segment_1) = cubic.split(t0);
+                    Some(monotonic_cubic_winding_number(&segment_0, point)? +
+                         monotonic_cubic_winding_number(&segment_1, point)?)
+                }
+                (None, None) => monotonic_cubic_winding_number(&segment, point),
+            }
+        }
+    }
+}
+
+fn line_winding_number(line: LineSegment2F, point: Vector2F) -> Option<i32> {
+    let vector = line.vector();
+    let t = if vector.is_zero() {
+        0.0
+    } else {
+        vector.projection_coefficient(point - line.from()).clamp(0.0, 1.0)
+    };
+    if (line.sample(t) - point).square_length() <= EPSILON * EPSILON {
+        return None;
+    }
+
+    let (from_y, to_y) = (line.from_y(), line.to_y());
+    let spans_y = (from_y <= point.y() && point.y() < to_y) ||
+        (to_y <= point.y() && point.y() < from_y);
+    if spans_y && line.solve_x_for_y(point.y()) > point.x() {
+        return Some(if from_y < to_y { 1 } else { -1 });
+    }
+    Some(0)
+}
+
+// The segment must be a cubic that is monotonic in y.
+fn monotonic_cubic_winding_number(segment: &Segment, point: Vector2F) -> Option<i32> {
+    let (from_y, to_y) = (segment.baseline.from_y(), segment.baseline.to_y());
+    if f32::abs(to_y - from_y) <= EPSILON {
+        return line_winding_number(segment.baseline, point);
+    }
+    if point.y() < f32::min(from_y, to_y) || point.y() > f32::max(from_y, to_y) {
+        return Some(0);
+    }
+
+    // Find the point on the curve at the ray's height by bisection.
+    let cubic = segment.as_cubic_segment();
+    let (mut t_min, mut t_max) = (0.0, 1.0);
+    for _ in 0..MAX_BISECTION_ITERATIONS {
+        let t_mid = (t_min + t_max) * 0.5;
+        if (cubic.sample(t_mid).y() < point.y()) == (from_y < to_y) {
+            t_min = t_mid;
+        } else {
+            t_max = t_mid;
+        }
+    }
+    let x = cubic.sample((t_min + t_max) * 0.5).x();
+
+    if f32::abs(x - point.x()) <= EPSILON {
+        return None;
+    }
+    if x > point.x() && point.y() != f32::max(from_y, to_y) {
+        return Some(if from_y < to_y { 1 } else { -1 });
+    }
+    Some(0)
+}
+
+// Computes ∫(x dy - y dx) over the segment, which is twice the signed area swept out between the
+// segment and the origin. These are the closed-form results for Bézier curves via Green's theorem.
+fn segment_double_area(segment: &Segment) -> f32 {
+    let (p0, p3) = (segment.baseline.from(), segment.baseline.to());
+    match segment.kind {
+        SegmentKind::None => 0.0,
+        SegmentKind::Line => p0.det(p3),
+        SegmentKind::Quadratic => {
+            let p1 = segment.ctrl.from();
+            (2.0 * p0.det(p1) + p0.det(p3) + 2.0 * p1.det(p3)) * (1.0 / 3.0)
+        }
+        SegmentKind::Cubic => {
+            let (p1, p2) = (segment.ctrl.from(), segment.ctrl.to());
+            (6.0 * p0.det(p1) + 3.0 * p0.det(p2) + p0.det(p3) +
+             3.0 * p1.det(p2) + 3.0 * p1.det(p3) + 6.0 * p2.det(p3)) * 0.1
+        }
+    }
+}
+
+// Returns which points of the polyline survive Douglas-Peucker simplification. The first and last
+// points always do.
+fn simplify_polyline(points: &[Vector2F], tolerance: f32) -> Vec<bool> {
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let chord = LineSegment2F::new(points[start], points[end]);
+        let mut farthest = None;
+        for (point_index, &point) in points.iter().enumerate().take(end).skip(start + 1) {
+            let distance = distance_to_line_segment(point, chord);
+            match farthest {
+                Some((_, farthest_distance)) if farthest_distance >= distance => {}
+                _ => farthest = Some((point_index, distance)),
+            }
+        }
+
+        if let Some((point_index, distance)) = farthest {
+            if distance > tolerance {
+                keep[point_index] = true;
+                ranges.push((start, point_index));
+                ranges.push((point_index, end));
+            }
+        }
+    }
+
+    keep
+}
+
+// Five-point Gauss-Legendre nodes and weights, mapped to [0, 1].
+const GAUSS_LEGENDRE_NODES: [(f32, f32); 5] = [
+    (0.046_910_077, 0.118_463_44),
+    (0.230_765_35, 0.239_314_34),
+    (0.5, 0.284_444_45),
+    (0.769_234_6, 0.239_314_34),
+    (0.953_089_9, 0.118_463_44),
+];
+
+fn average_of_endpoints<'a, I>(contours: I) -> Vector2F where I: Iterator<Item = &'a Contour> {
+    let (mut sum, mut count) = (Vector2F::zero(), 0);
+    for contour in contours {
+        for (&point, flags) in contour.points.iter().zip(contour.flags.iter()) {
+            if flags.is_empty() {
+                sum += point;
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        sum
+    } else {
+        sum * (1.0 / count as f32)
+    }
+}
+
+#[inline]
+fn circle_contains_point((center, radius): (Vector2F, f32), point: Vector2F) -> bool {
+    (point - center).length() <= radius + EPSILON
+}
+
+#[inline]
+fn circle_from_diameter(a: Vector2F, b: Vector2F) -> (Vector2F, f32) {
+    ((a + b) * 0.5, (b - a).length() * 0.5)
+}
+
+// Returns the circumcircle of the three points. If they are collinear, returns the circle whose
+// diameter joins the two farthest apart instead.
+fn circle_through_points(a: Vector2F, b: Vector2F, c: Vector2F) -> (Vector2F, f32) {
+    let (ab, ac) = (b - a, c - a);
+    let denominator = 2.0 * ab.det(ac);
+    if denominator.abs() <= EPSILON * EPSILON {
+        let candidates = [circle_from_diameter(a, b),
+                          circle_from_diameter(a, c),
+                          circle_from_diameter(b, c)];
+        return candidates.iter().cloned().fold(candidates[0], |best, candidate| {
+            if candidate.1 > best.1 { candidate } else { best }
+        });
+    }
+
+    let offset = vec2f(ac.y() * ab.square_length() - ab.y() * ac.square_length(),
+                       ab.x() * ac.square_length() - ac.x() * ab.square_length()) *
+        (1.0 / denominator);
+    (a + offset, offset.length())
+}
+
+// Appends one chain of Andrew's monotone chain algorithm, keeping only right turns.
+fn extend_convex_chain<I>(hull_points: &mut Vec<Vector2F>, points: I)
+                          where I: Iterator<Item = Vector2F> {
+    let start = hull_points.len();
+    for point in points {
+        while hull_points.len() >= start + 2 {
+            let count = hull_points.len();
+            let (a, b) = (hull_points[count - 2], hull_points[count - 1]);
+            if (b - a).det(point - a) > 0.0 {
+                break;
+            }
+            hull_points.pop();
+        }
+        hull_points.push(point);
+    }
+}
+
+fn distance_to_rect(point: Vector2F, rect: RectF) -> f32 {
+    let dx = f32::max(f32::max(rect.min_x() - point.x(), point.x() - rect.max_x()), 0.0);
+    let dy = f32::max(f32::max(rect.min_y() - point.y(), point.y() - rect.max_y()), 0.0);
+    vec2f(dx, dy).length()
+}
+
+// Returns true if `point` lies within `epsilon` of the line segment from `from` to `to`, strictly
+// between them.
+fn point_is_between(from: Vector2F, point: Vector2F, to: Vector2F, epsilon: f32) -> bool {
+    let chord = LineSegment2F::new(from, to);
+    let t = (point - from).dot(chord.vector());
+    t > 0.0 && t < chord.square_length() && distance_to_line_segment(point, chord) <= epsilon
+}
+
+fn distance_to_line_segment(point: Vector2F, segment: LineSegment2F) -> f32 {
+    let vector = segment.vector();
+    let square_length = vector.square_length();
+    if square_length == 0.0 {
+        return (point - segment.from()).length();
+    }
+    let t = ((point - segment.from()).dot(vector) / square_length).clamp(0.0, 1.0);
+    (point - segment.sample(t)).length()
+}
+
+#[inline]
+fn point_is_finite(point: Vector2F) -> bool {
+    point.x().is_finite() && point.y().is_finite()
+}
+
+#[inline]
+pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
+    if first {
+        *bounds = RectF::from_points(new_point, new_point);
+    } else {
+        *bounds = bounds.union_point(new_point)
+    }
+}
+
+/// Transforms the points in place, two at a time.
+///
+/// Each pair is packed into a single `F32x4` so that the multiply-adds for both points happen
+/// together. A trailing odd point goes through the scalar path. On targets without SIMD support,
+/// `pathfinder_simd` falls back to scalar code.
+pub(crate) fn transform_points(points: &mut [Vector2F], transform: &Transform2F) {
+    let (col_0, col_1) = (transform.matrix.0.xyxy(), transform.matrix.0.zwzw());
+    let translation = transform.vector.0.to_f32x4().xyxy();
+
+    let mut pairs = points.chunks_exact_mut(2);
+    for pair in &mut pairs {
+        let packed = pair[0].0.concat_xy_xy(pair[1].0);
+        let result = col_0 * packed.xxzz() + col_1 * packed.yyww() + translation;
+        pair[0] = Vector2F(result.xy());
+        pair[1] = Vector2F(result.zw());
+    }
+
+    for point in pairs.into_remainder() {
+        *point = *transform * *point;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::clip::BooleanOp;
+    use crate::fill::FillRule;
+    use crate::orientation::Orientation;
+    use crate::outline::{ArcDirection, Contour, ContourBuilder, ContourIterFlags, Outline};
+    use crate::outline::{PointFlags, PointIndex, PointIndexError, PushSegmentFlags};
+    use crate::outline::transform_points;
+    use crate::segment::{Segment, SegmentFlags, SegmentKind};
+    use crate::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::transform3d::{Perspective, Transform4F};
+    use pathfinder_geometry::util::EPSILON;
+    use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F, vec2f};
+    use std::collections::HashSet;
+    use std::f32::consts::PI;
+
+    fn line_quadratic_cubic_contour() -> Contour {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_quadratic(vec2f(15.0, 5.0), vec2f(10.0, 10.0));
+        contour.push_cubic(vec2f(7.0, 12.0), vec2f(3.0, 12.0), vec2f(0.0, 10.0));
+        contour
+    }
+
+    #[test]
+    fn test_append() {
+        let rect_a = RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0));
+        let rect_b = RectF::new(vec2f(2.0, 3.0), vec2f(1.0, 1.0));
+
+        let mut outline = Outline::new();
+        outline.append(Outline::from_rect(rect_a));
+        assert_eq!(outline.bounds(), rect_a);
+
+        outline.append(Outline::new());
+        assert_eq!(outline.bounds(), rect_a);
+        assert_eq!(outline.contours().len(), 1);
+
+        outline.append(Outline::from_rect(rect_b));
+        assert_eq!(outline.bounds(), rect_a.union_rect(rect_b));
+        assert_eq!(outline.contours().len(), 2);
+    }
+
+    #[test]
+    fn test_extend() {
+        let rect_a = RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0));
+        let rect_b = RectF::new(vec2f(-2.0, 3.0), vec2f(1.0, 1.0));
+
+        let mut outline = Outline::new();
+        outline.extend(vec![
+            Contour::from_rect(rect_a),
+            Contour::new(),
+            Contour::from_rect(rect_b),
+        ]);
+        assert_eq!(outline.contours().len(), 2);
+        assert_eq!(outline.bounds(), rect_a.union_rect(rect_b));
+    }
+
+    #[test]
+    fn test_reverse() {
+        for &closed in &[false, true] {
+            let mut contour = line_quadratic_cubic_contour();
+            if closed {
+                contour.close();
+            }
+            let bounds = contour.bounds();
+
+            // The closing segment stays last.
+            let mut expected: Vec<Segment> = contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                                                    .map(|segment| segment.reversed())
+                                                    .collect();
+            expected.reverse();
+            let close_segment = contour.iter(ContourIterFlags::empty()).nth(expected.len());
+            expected.extend(close_segment.map(|segment| segment.reversed()));
+
+            contour.reverse();
+            let reversed: Vec<Segment> = contour.iter(ContourIterFlags::empty()).collect();
+            assert_eq!(reversed, expected);
+            assert_eq!(contour.bounds(), bounds);
+        }
+
+        // A closed contour that begins with a curve must not end with control points.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(1.0, -1.0), vec2f(2.0, -1.0), vec2f(3.0, 0.0));
+        contour.push_endpoint(vec2f(3.0, 3.0));
+        contour.close();
+        contour.reverse();
+        assert_eq!(format!("{:?}", contour), "M 3 3 L 3 0 C 2 -1 1 -1 0 0 z");
+    }
+
+    #[test]
+    fn test_area() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        assert_eq!(square.area(), 1.0);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::default());
+        assert!((circle.area() - PI).abs() < 0.001);
+
+        let mut outline = Outline::new();
+        outline.push_contour(square.clone());
+        outline.push_contour(circle);
+        assert!((outline.area() - (1.0 + PI)).abs() < 0.001);
+
+        outline.reverse();
+        assert!((outline.area() + (1.0 + PI)).abs() < 0.001);
+
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(1.0, 2.0));
+        assert_eq!(point.area(), 0.0);
+        assert_eq!(Contour::new().area(), 0.0);
+    }
+
+    #[test]
+    fn test_length() {
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(0.0, 0.0));
+        triangle.push_endpoint(vec2f(3.0, 0.0));
+        triangle.push_endpoint(vec2f(3.0, 4.0));
+        assert_eq!(triangle.length(), 7.0);
+        triangle.close();
+        assert_eq!(triangle.length(), 12.0);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::default());
+        circle.close();
+        assert!((circle.length() - 2.0 * PI).abs() < 0.01);
+
+        let mut outline = Outline::new();
+        outline.push_contour(triangle);
+        outline.push_contour(circle);
+        assert!((outline.length() - (12.0 + 2.0 * PI)).abs() < 0.01);
+    }
+
+    fn assert_no_repeated_points(contour: &Contour) {
+        for pair in contour.points.windows(2) {
+            assert_ne!(pair[0], pair[1], "{:?}", contour);
+        }
+    }
+
+    #[test]
+    fn test_push_segment() {
+        let curve = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                       vec2f(5.0, 10.0));
+        let line = Segment::line(LineSegment2F::new(vec2f(10.0, 0.0), vec2f(10.0, 10.0)));
+
+        let mut contour = Contour::new();
+        let flags = PushSegmentFlags::UPDATE_BOUNDS | PushSegmentFlags::INCLUDE_FROM_POINT;
+        contour.push_segment(&curve, flags);
+        assert_eq!(contour.len(), 3);
+        assert_eq!(contour.position_of(0), vec2f(0.0, 0.0));
+        assert!(contour.point_is_endpoint(0));
+        assert!(!contour.point_is_endpoint(1));
+
+        // Without `INCLUDE_FROM_POINT`, only the control points and the end point are pushed.
+        contour.push_segment(&line, PushSegmentFlags::UPDATE_BOUNDS);
+        assert_eq!(contour.len(), 4);
+        assert_eq!(contour.position_of(3), vec2f(10.0, 10.0));
+        assert_eq!(contour.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+    }
+
+    #[test]
+    fn test_push_arc_and_ellipse_point_counts() {
+        let mut arc = Contour::new();
+        arc.push_arc(&Transform2F::default(), 0.0, PI * 0.5, ArcDirection::CW);
+        assert_eq!(arc.len(), 4);
+        assert!(arc.point_is_endpoint(0) && arc.point_is_endpoint(3));
+        assert_no_repeated_points(&arc);
+
+        let mut ellipse = Contour::new();
+        ellipse.push_ellipse(&Transform2F::default());
+        assert_eq!(ellipse.len(), 13);
+        assert_no_repeated_points(&ellipse);
+    }
+
+    #[test]
+    fn test_clip_starts_with_endpoint() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, 10.0), vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(5.0, -10.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        outline.clip_against_rect(RectF::new(vec2f(-1.0, -5.0), vec2f(14.0, 20.0)));
+        let clipped = &outline.contours()[0];
+        assert!(clipped.point_is_endpoint(0));
+        assert_no_repeated_points(clipped);
+    }
+
+    #[test]
+    fn test_stroke_includes_from_points() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle {
+            line_width: 2.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Bevel,
+        };
+        let mut stroke = OutlineStrokeToFill::new(&outline, style);
+        stroke.offset();
+        let stroked = stroke.into_outline();
+
+        // Each offset side starts with its own from point, so the butt-capped line becomes a
+        // rectangle with no missing corners.
+        let rect = &stroked.contours()[0];
+        assert_eq!(rect.len(), 4);
+        assert_eq!(rect.position_of(0), vec2f(0.0, -1.0));
+        assert_eq!(rect.position_of(1), vec2f(10.0, -1.0));
+        assert_eq!(rect.position_of(2), vec2f(10.0, 1.0));
+        assert_eq!(rect.position_of(3), vec2f(0.0, 1.0));
+        assert_no_repeated_points(rect);
+    }
+
+    #[test]
+    fn test_make_monotonic_starts_with_endpoint() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, 10.0), vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(5.0, -5.0));
+        contour.close();
+
+        contour.make_monotonic(&mut Contour::new());
+        assert_eq!(contour.len(), 8);
+        assert!(contour.point_is_endpoint(0));
+        assert_eq!(contour.position_of(0), vec2f(0.0, 0.0));
+        assert_eq!(contour.position_of(3), vec2f(5.0, 5.0));
+        assert_eq!(contour.position_of(7), vec2f(5.0, -5.0));
+        assert!(contour.is_closed());
+        assert_no_repeated_points(&contour);
+    }
+
+    #[test]
+    fn test_time_for_distance() {
+        let curve = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                       vec2f(0.0, 10.0));
+        let length = curve.arc_length();
+        for &fraction in &[0.25, 0.5, 0.75] {
+            let t = curve.time_for_distance(length * fraction);
+            assert!((curve.split(t).0.arc_length() - length * fraction).abs() < 0.01);
+        }
+        assert_eq!(curve.time_for_distance(length * 2.0), 2.0);
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let square = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)));
+        assert!(square.contains_point(vec2f(1.0, 1.0), FillRule::Winding));
+        assert!(square.contains_point(vec2f(2.0, 1.0), FillRule::Winding));
+        assert!(square.contains_point(vec2f(0.0, 0.0), FillRule::EvenOdd));
+        assert!(!square.contains_point(vec2f(3.0, 1.0), FillRule::Winding));
+
+        let mut rings = Outline::new();
+        for &radius in &[1.0, 0.5] {
+            let mut circle = Contour::new();
+            circle.push_ellipse(&Transform2F::from_scale(radius));
+            circle.close();
+            rings.push_contour(circle);
+        }
+        assert!(rings.contains_point(vec2f(0.7, 0.7), FillRule::Winding));
+        assert!(!rings.contains_point(vec2f(0.72, 0.72), FillRule::Winding));
+        assert!(rings.contains_point(vec2f(0.0, 0.0), FillRule::Winding));
+        assert!(!rings.contains_point(vec2f(0.0, 0.0), FillRule::EvenOdd));
+        assert!(rings.contains_point(vec2f(0.0, 0.75), FillRule::EvenOdd));
+        assert!(rings.contains_point(vec2f(0.0, -1.0), FillRule::EvenOdd));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        circle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(circle);
+
+        let coarse = outline.flatten(0.1);
+        let fine = outline.flatten(0.001);
+        assert!(fine.contours()[0].len() > coarse.contours()[0].len());
+
+        for flattened in &[coarse, fine] {
+            let contour = &flattened.contours()[0];
+            assert!(contour.is_closed());
+            assert!(contour.flags.iter().all(|flags| flags.is_empty()));
+            for point_index in 0..contour.len() {
+                let position = contour.position_of(point_index);
+                assert!((position.length() - 10.0).abs() < 0.01);
+            }
+            let bounds = flattened.bounds();
+            assert!((bounds.min_x() + 10.0).abs() < 0.01 && (bounds.max_y() - 10.0).abs() < 0.01);
+        }
+
+        let mut output = Contour::new();
+        let line = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        line.flatten_into(0.1, &mut output);
+        assert_eq!(format!("{:?}", output), format!("{:?}", line));
+    }
+
+    #[test]
+    fn test_closed() {
+        let mut first = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 0.0)));
+        first.flags = SegmentFlags::FIRST_IN_SUBPATH;
+        let segments =
+            vec![first, Segment::line(LineSegment2F::new(vec2f(1.0, 0.0), vec2f(1.0, 1.0)))];
+        let mut closing = Segment::none();
+        closing.flags = SegmentFlags::CLOSES_SUBPATH;
+        let mut segments_with_close = segments.clone();
+        segments_with_close.push(closing);
+
+        let open = Outline::from_segments(segments.into_iter());
+        let mut contour = open.contours()[0].clone();
+        assert!(!contour.is_closed());
+        assert_eq!(format!("{:?}", open), "M 0 0 L 1 0 L 1 1");
+        assert_eq!(contour.iter(ContourIterFlags::empty()).count(), 2);
+
+        let closed = Outline::from_segments(segments_with_close.into_iter());
+        assert!(closed.contours()[0].is_closed());
+        assert_eq!(format!("{:?}", closed), "M 0 0 L 1 0 L 1 1 z");
+
+        contour.set_closed(true);
+        assert_eq!(contour.iter(ContourIterFlags::empty()).count(), 3);
+        assert_eq!(contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).count(), 2);
+        contour.set_closed(false);
+        assert_eq!(format!("{:?}", contour), "M 0 0 L 1 0 L 1 1");
+    }
+
+    #[test]
+    fn test_from_segments_with_capacity() {
+        let mut first = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 0.0)));
+        first.flags = SegmentFlags::FIRST_IN_SUBPATH;
+        let segments = vec![
+            first,
+            Segment::quadratic(LineSegment2F::new(vec2f(1.0, 0.0), vec2f(1.0, 1.0)),
+                               vec2f(2.0, 0.5)),
+        ];
+
+        let outline = Outline::from_segments_with_capacity(segments.clone().into_iter(), 4, 32);
+        assert!(outline.contours.capacity() >= 4);
+        assert!(outline.contours[0].points.capacity() >= 32);
+        assert!(outline.contours[0].flags.capacity() >= 32);
+        assert_eq!(format!("{:?}", outline), "M 0 0 L 1 0 Q 2 0.5 1 1");
+
+        let hinted = Outline::from_segments(segments.into_iter());
+        assert!(hinted.contours[0].points.capacity() >= 2);
+        assert_eq!(format!("{:?}", hinted), format!("{:?}", outline));
+    }
+
+    #[test]
+    fn test_point_index_wrapping() {
+        let empty = Contour::new();
+        assert_eq!(empty.checked_prev_point_index_of(0), None);
+        assert_eq!(empty.checked_next_point_index_of(0), None);
+        assert_eq!(empty.checked_prev_endpoint_index_of(0), None);
+        assert_eq!(empty.checked_next_endpoint_index_of(0), None);
+
+        let mut single = Contour::new();
+        single.push_endpoint(vec2f(1.0, 1.0));
+        assert_eq!(single.checked_prev_point_index_of(0), Some(0));
+        assert_eq!(single.checked_next_point_index_of(0), Some(0));
+        assert_eq!(single.prev_endpoint_index_of(0), 0);
+        assert_eq!(single.next_endpoint_index_of(0), 0);
+
+        let mut quadratic = Contour::new();
+        quadratic.push_endpoint(vec2f(0.0, 0.0));
+        quadratic.push_quadratic(vec2f(1.0, 1.0), vec2f(2.0, 0.0));
+        assert_eq!(quadratic.prev_point_index_of(0), 2);
+        assert_eq!(quadratic.next_point_index_of(2), 0);
+        assert_eq!(quadratic.checked_next_endpoint_index_of(0), Some(2));
+        assert_eq!(quadratic.checked_prev_endpoint_index_of(0), Some(2));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "empty contour")]
+    fn test_point_index_of_empty_contour() {
+        Contour::new().next_point_index_of(0);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        // `push_point()` asserts that points aren't NaN, so poison the points afterward, as a
+        // bad transform would.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(2.0, 5.0));
+        contour.push_endpoint(vec2f(4.0, 0.0));
+        contour.push_quadratic(vec2f(6.0, 2.0), vec2f(4.0, 4.0));
+        contour.push_cubic(vec2f(3.0, 5.0), vec2f(1.0, 5.0), vec2f(0.0, 4.0));
+        contour.close();
+        contour.points[1] = vec2f(f32::NAN, 5.0);
+        contour.points[3] = vec2f(f32::INFINITY, 2.0);
+        let mut garbage = Contour::new();
+        garbage.push_endpoint(vec2f(0.0, 0.0));
+        garbage.points[0] = vec2f(f32::NEG_INFINITY, 0.0);
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.push_contour(garbage);
+        outline.bounds = outline.bounds.union_point(vec2f(f32::NEG_INFINITY, 0.0));
+        assert!(!outline.is_finite());
+        assert!(!outline.bounds().origin().x().is_finite());
+
+        outline.sanitize();
+        assert!(outline.is_finite());
+        assert_eq!(outline.contours().len(), 1);
+        assert!(outline.contours()[0].is_closed());
+        assert_eq!(format!("{:?}", outline), "M 0 0 L 4 0 L 4 4 C 3 5 1 5 0 4 z");
+        assert_eq!(outline.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 5.0)));
+    }
+
+    #[test]
+    fn test_monotonic_segments() {
+        let mut outline = Outline::new();
+        outline.push_contour(line_quadratic_cubic_contour());
+        let mut s_curve = Contour::new();
+        s_curve.push_endpoint(vec2f(0.0, 0.0));
+        s_curve.push_cubic(vec2f(10.0, -10.0), vec2f(0.0, 20.0), vec2f(10.0, 10.0));
+        outline.push_contour(s_curve);
+        let before = format!("{:?}", outline);
+
+        let segments: Vec<Segment> = outline.monotonic_segments().collect();
+        assert_eq!(format!("{:?}", outline), before);
+        assert!(segments.len() > outline.contours().iter().map(|contour| {
+            contour.iter(ContourIterFlags::empty()).count()
+        }).sum());
+        for segment in &segments {
+            if segment.is_line() {
+                continue;
+            }
+            let cubic = segment.as_cubic_segment();
+            let (from_y, to_y) = (segment.baseline.from_y(), segment.baseline.to_y());
+            let (min_y, max_y) = (f32::min(from_y, to_y), f32::max(from_y, to_y));
+            for step in 0..=16 {
+                let y = cubic.sample(step as f32 / 16.0).y();
+                assert!(y >= min_y - EPSILON && y <= max_y + EPSILON);
+            }
+        }
+
+        // The pieces join up end to end within each contour.
+        let s_curve_segments = outline.contours()[1].monotonic_segments().collect::<Vec<_>>();
+        assert_eq!(s_curve_segments.len(), 3);
+        for pair in s_curve_segments.windows(2) {
+            assert!((pair[0].baseline.to() - pair[1].baseline.from()).length() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_clip_drops_slivers() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(2.0, 2.0), vec2f(6.0, 6.0)));
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(0.0, 10.0));
+        triangle.push_endpoint(vec2f(10.0, 10.0));
+        triangle.push_endpoint(vec2f(5.0, 20.0));
+        triangle.close();
+        outline.push_contour(triangle.clone());
+
+        // The triangle only touches the bottom of the clip area, which leaves a zero-area sliver.
+        let clip_rect = RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let mut clipped = outline.clone();
+        clipped.clip_against_rect(clip_rect);
+        assert_eq!(clipped.contours().len(), 1);
+        assert_eq!(clipped.area(), 36.0);
+        assert_eq!(clipped.bounds(), RectF::new(vec2f(2.0, 2.0), vec2f(6.0, 6.0)));
+
+        let clip_polygon = [vec2f(0.0, 0.0), vec2f(10.0, 0.0), vec2f(10.0, 10.0), vec2f(0.0, 10.0)];
+        let mut clipped = outline.clone();
+        clipped.clip_against_polygon(&clip_polygon);
+        assert_eq!(clipped.contours().len(), 1);
+    }
+
+    #[test]
+    fn test_clip_preserves_holes() {
+        let mut annulus = Outline::from_circle(vec2f(50.0, 50.0), 40.0);
+        let mut hole = Outline::from_circle(vec2f(50.0, 50.0), 10.0);
+        hole.reverse();
+        annulus.append(hole);
+        let (outer_area, hole_area) = (annulus.contours()[0].area(), annulus.contours()[1].area());
+        assert!(outer_area > 0.0 && hole_area < 0.0);
+
+        // Cut off the left side of the ring, leaving the hole whole.
+        let clip_rect = RectF::new(vec2f(30.0, 0.0), vec2f(100.0, 100.0));
+        let mut by_rect = annulus.clone();
+        by_rect.clip_against_rect(clip_rect);
+        let mut by_polygon = annulus.clone();
+        by_polygon.clip_against_polygon(&[
+            clip_rect.origin(),
+            clip_rect.upper_right(),
+            clip_rect.lower_right(),
+            clip_rect.lower_left(),
+        ]);
+        for clipped in &[by_rect, by_polygon] {
+            assert_eq!(clipped.contours().len(), 2);
+            for contour in clipped.contours() {
+                assert!(contour.point_is_endpoint(0));
+            }
+            let clipped_outer_area = clipped.contours()[0].area();
+            assert!(clipped_outer_area > 0.0 && clipped_outer_area < outer_area);
+            assert!((clipped.contours()[1].area() - hole_area).abs() < 0.01);
+            assert!(!clipped.contains_point(vec2f(50.0, 50.0), FillRule::Winding));
+            assert!(clipped.contains_point(vec2f(70.0, 50.0), FillRule::Winding));
+        }
+    }
+
+    #[test]
+    fn test_clip_against_outline() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let clip = Outline::from_rect(RectF::new(vec2f(5.0, 5.0), vec2f(10.0, 10.0)));
+        outline.clip_against_outline(&clip, FillRule::Winding);
+        assert_eq!(outline.contours().len(), 1);
+        assert_eq!(outline.contours()[0].len(), 4);
+        assert!((outline.area() - 25.0).abs() < EPSILON);
+        assert_eq!(outline.bounds(), RectF::new(vec2f(5.0, 5.0), vec2f(5.0, 5.0)));
+
+        let mut disjoint = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        disjoint.clip_against_outline(&clip, FillRule::Winding);
+        assert!(disjoint.contours().is_empty());
+    }
+
+    #[test]
+    fn test_clip_against_outline_with_hole() {
+        let outer = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 20.0)));
+        let mut hole = Contour::from_rect(RectF::new(vec2f(5.0, 5.0), vec2f(10.0, 10.0)));
+
+        // Same orientation, so the hole only exists under the even-odd rule.
+        let mut even_odd_clip = Outline::new();
+        even_odd_clip.push_contour(outer.clone());
+        even_odd_clip.push_contour(hole.clone());
+        // Reversed, so the hole exists under either rule.
+        hole.reverse();
+        let mut winding_clip = Outline::new();
+        winding_clip.push_contour(outer);
+        winding_clip.push_contour(hole);
+
+        let subject = Outline::from_rect(RectF::new(vec2f(-5.0, 10.0), vec2f(30.0, 15.0)));
+        for &(clip, fill_rule) in &[(&even_odd_clip, FillRule::EvenOdd),
+                                    (&winding_clip, FillRule::Winding),
+                                    (&winding_clip, FillRule::EvenOdd)] {
+            let mut outline = subject.clone();
+            outline.clip_against_outline(clip, fill_rule);
+            assert!((outline.area() - 150.0).abs() < EPSILON);
+            for &rule in &[FillRule::Winding, FillRule::EvenOdd] {
+                assert!(outline.contains_point(vec2f(2.0, 12.0), rule));
+                assert!(outline.contains_point(vec2f(17.0, 17.0), rule));
+                assert!(!outline.contains_point(vec2f(10.0, 12.0), rule));
+                assert!(!outline.contains_point(vec2f(10.0, 22.0), rule));
+            }
+        }
+
+        let mut outline = subject.clone();
+        outline.clip_against_outline(&even_odd_clip, FillRule::Winding);
+        assert_eq!(outline.contours().len(), 1);
+        assert!((outline.area() - 200.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_boolean() {
+        let mut left = Contour::new();
+        left.push_ellipse(&Transform2F::from_scale(10.0));
+        let mut right = Contour::new();
+        right.push_ellipse(&Transform2F::from_translation_rotation_scale(vec2f(10.0, 0.0),
+                                                                         0.0,
+                                                                         10.0));
+        let (mut a, mut b) = (Outline::new(), Outline::new());
+        a.push_contour(left);
+        b.push_contour(right);
+
+        // Compare against the areas of the flattened inputs, since flattening loses a little.
+        let a_area = a.flatten(0.1).area();
+        let b_area = b.flatten(0.1).area();
+        let intersection = a.boolean(&b, BooleanOp::Intersection, FillRule::Winding);
+        let lens_area = 200.0 * f32::acos(0.5) - 5.0 * f32::sqrt(300.0);
+        assert_eq!(intersection.contours().len(), 1);
+        assert!((intersection.area() - lens_area).abs() < 2.0);
+        assert!(intersection.contains_point(vec2f(5.0, 0.0), FillRule::Winding));
+        assert!(!intersection.contains_point(vec2f(-5.0, 0.0), FillRule::Winding));
+
+        let i_area = intersection.area();
+        let union = a.boolean(&b, BooleanOp::Union, FillRule::Winding);
+        assert_eq!(union.contours().len(), 1);
+        assert!((union.area() - (a_area + b_area - i_area)).abs() < 0.01);
+        assert!(union.contains_point(vec2f(-5.0, 0.0), FillRule::Winding));
+        assert!(union.contains_point(vec2f(15.0, 0.0), FillRule::Winding));
+
+        let difference = a.boolean(&b, BooleanOp::Difference, FillRule::Winding);
+        assert_eq!(difference.contours().len(), 1);
+        assert!((difference.area() - (a_area - i_area)).abs() < 0.01);
+        assert!(difference.contains_point(vec2f(-5.0, 0.0), FillRule::Winding));
+        assert!(!difference.contains_point(vec2f(5.0, 0.0), FillRule::Winding));
+        assert!(!difference.contains_point(vec2f(15.0, 0.0), FillRule::Winding));
+
+        let xor = a.boolean(&b, BooleanOp::Xor, FillRule::Winding);
+        assert_eq!(xor.contours().len(), 2);
+        assert!((xor.area() - (a_area + b_area - 2.0 * i_area)).abs() < 0.01);
+        for &fill_rule in &[FillRule::Winding, FillRule::EvenOdd] {
+            assert!(xor.contains_point(vec2f(-5.0, 0.0), fill_rule));
+            assert!(!xor.contains_point(vec2f(5.0, 0.0), fill_rule));
+            assert!(xor.contains_point(vec2f(15.0, 0.0), fill_rule));
+        }
+    }
+
+    #[test]
+    fn test_boolean_scale() {
+        // The probes that classify edges scale with the outlines, so both huge and tiny outlines
+        // combine correctly.
+        for &scale in &[0.0001, 1.0, 100000.0] {
+            let a = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)) * scale);
+            let b = Outline::from_rect(RectF::new(vec2f(1.0, 1.0), vec2f(2.0, 2.0)) * scale);
+            let union = a.boolean(&b, BooleanOp::Union, FillRule::Winding);
+            assert_eq!(union.contours().len(), 1);
+            assert!((union.area() / (scale * scale) - 7.0).abs() < 0.001);
+            let intersection = a.boolean(&b, BooleanOp::Intersection, FillRule::Winding);
+            assert!((intersection.area() / (scale * scale) - 1.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_orientation() {
+        let outer = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        let mut hole = Contour::from_rect(RectF::new(vec2f(3.0, 3.0), vec2f(4.0, 4.0)));
+        assert!(outer.is_clockwise());
+        assert_eq!(outer.orientation(), Orientation::Cw);
+        hole.reverse();
+        assert!(!hole.is_clockwise());
+        assert_eq!(hole.orientation(), Orientation::Ccw);
+        assert_eq!(Contour::new().orientation(), Orientation::Ccw);
+
+        // Start with both contours winding the same way, and an island inside the hole.
+        hole.reverse();
+        let island = Contour::from_rect(RectF::new(vec2f(4.0, 4.0), vec2f(2.0, 2.0)));
+        let mut outline = Outline::new();
+        outline.push_contour(outer);
+        outline.push_contour(hole);
+        outline.push_contour(island);
+
+        outline.fix_winding(Orientation::Cw);
+        let orientations: Vec<_> = outline.contours().iter().map(|contour| {
+            contour.orientation()
+        }).collect();
+        assert_eq!(orientations, [Orientation::Cw, Orientation::Ccw, Orientation::Cw]);
+        assert!(!outline.contains_point(vec2f(3.5, 3.5), FillRule::Winding));
+        assert!(outline.contains_point(vec2f(5.0, 5.0), FillRule::Winding));
+
+        outline.fix_winding(Orientation::Ccw);
+        assert!(!outline.contours()[0].is_clockwise());
+        assert!(outline.contours()[1].is_clockwise());
+        assert!(!outline.contours()[2].is_clockwise());
+    }
+
+    #[test]
+    fn test_simplify() {
+        let mut zigzag = Contour::new();
+        for point_index in 0..=20 {
+            let wobble = if point_index % 2 == 0 { 0.05 } else { -0.05 };
+            zigzag.push_endpoint(vec2f(point_index as f32, wobble));
+        }
+        let mut simplified = zigzag.clone();
+        simplified.simplify(0.5);
+        assert_eq!(format!("{:?}", simplified), "M 0 0.05 L 20 0.05");
+        assert_eq!(simplified.bounds(), RectF::new(vec2f(0.0, 0.05), vec2f(20.0, 0.0)));
+
+        // A tight tolerance keeps every point.
+        let mut unchanged = zigzag.clone();
+        unchanged.simplify(0.01);
+        assert_eq!(unchanged.len(), zigzag.len());
+
+        // Curves and their endpoints survive, and the lines around them are simplified.
+        let mut mixed = Contour::new();
+        mixed.push_endpoint(vec2f(0.0, 0.0));
+        mixed.push_endpoint(vec2f(1.0, 0.01));
+        mixed.push_endpoint(vec2f(2.0, 0.0));
+        mixed.push_quadratic(vec2f(3.0, 5.0), vec2f(4.0, 0.0));
+        mixed.push_endpoint(vec2f(5.0, -0.01));
+        mixed.push_endpoint(vec2f(6.0, 0.0));
+        mixed.simplify(0.1);
+        assert_eq!(format!("{:?}", mixed), "M 0 0 L 2 0 Q 3 5 4 0 L 6 0");
+
+        // Closed polygons simplify across the closing segment.
+        let mut square = Contour::new();
+        for &point in &[vec2f(0.0, 0.0), vec2f(5.0, 0.0), vec2f(10.0, 0.0), vec2f(10.0, 10.0),
+                        vec2f(0.0, 10.0), vec2f(0.0, 5.0)] {
+            square.push_endpoint(point);
+        }
+        square.close();
+        square.simplify(0.1);
+        assert_eq!(format!("{:?}", square), "M 0 0 L 10 0 L 10 10 L 0 10 z");
+    }
+
+    #[test]
+    fn test_remove_degenerate_points() {
+        let mut line = Contour::new();
+        for point_index in 0..5 {
+            line.push_endpoint(vec2f(point_index as f32 * 2.0, point_index as f32));
+        }
+        line.remove_degenerate_points(EPSILON);
+        assert_eq!(format!("{:?}", line), "M 0 0 L 8 4");
+        assert_eq!(line.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(8.0, 4.0)));
+
+        // A collinear point that doubles back is a real corner, and control points stay put even
+        // when they coincide with their endpoints.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(4.0, 0.0));
+        contour.push_endpoint(vec2f(2.0, 0.0));
+        contour.push_cubic(vec2f(2.0, 0.0), vec2f(3.0, 2.0), vec2f(4.0, 2.0));
+        contour.push_endpoint(vec2f(4.0, 2.0));
+        contour.push_endpoint(vec2f(6.0, 2.0));
+        contour.remove_degenerate_points(EPSILON);
+        assert_eq!(format!("{:?}", contour), "M 0 0 L 4 0 L 2 0 C 2 0 3 2 4 2 L 6 2");
+
+        // Midpoints next to the seam and a duplicate closing point are removed too.
+        let mut square = Contour::new();
+        for &point in &[vec2f(0.0, 0.0), vec2f(5.0, 0.0), vec2f(10.0, 0.0), vec2f(10.0, 10.0),
+                        vec2f(0.0, 10.0), vec2f(0.0, 5.0), vec2f(0.0, 0.0)] {
+            square.push_endpoint(point);
+        }
+        square.close();
+        square.remove_degenerate_points(EPSILON);
+        assert_eq!(format!("{:?}", square), "M 0 0 L 10 0 L 10 10 L 0 10 z");
+    }
+
+    #[test]
+    fn test_remove_degenerate_segments() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(4.0, 0.0));
+        contour.push_endpoint(vec2f(4.0, 0.0));
+        contour.push_cubic(vec2f(4.0, 0.0), vec2f(4.0, 0.0), vec2f(4.0, 0.0));
+        contour.push_endpoint(vec2f(4.0, 4.0));
+        contour.push_endpoint(vec2f(4.0, 8.0));
+        assert_eq!(contour.remove_degenerate_segments(EPSILON), 2);
+        assert_eq!(format!("{:?}", contour), "M 0 0 L 4 0 L 4 4 L 4 8");
+        assert_eq!(contour.remove_degenerate_segments(EPSILON), 0);
+
+        // The neighbors of a segment that is merely short are joined where it started.
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(0.0, 0.0));
+        triangle.push_endpoint(vec2f(4.0, 0.0));
+        triangle.push_quadratic(vec2f(4.0, 0.01), vec2f(4.01, 0.0));
+        triangle.push_endpoint(vec2f(4.0, 4.0));
+        triangle.push_endpoint(vec2f(0.0, 0.01));
+        triangle.close();
+        assert_eq!(triangle.remove_degenerate_segments(0.1), 2);
+        assert_eq!(format!("{:?}", triangle), "M 0 0 L 4 0 L 4 4 z");
+        assert_eq!(triangle.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(1.0, 1.0));
+        point.push_endpoint(vec2f(1.0, 1.0));
+        assert_eq!(point.remove_degenerate_segments(EPSILON), 1);
+        assert_eq!(point.points(), &[vec2f(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_nearest_point() {
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(Contour::from_rect(RectF::new(vec2f(20.0, 20.0), vec2f(5.0, 5.0))));
+        outline.push_contour(line);
+
+        let (point_index, point, distance) = outline.nearest_point(vec2f(4.0, 3.0)).unwrap();
+        assert_eq!(point_index, PointIndex::new(1, 0));
+        assert_eq!(point, vec2f(4.0, 0.0));
+        assert_eq!(distance, 3.0);
+
+        // The closing segment of the square counts, but not the implicit one of the open line.
+        let (point_index, point, distance) = outline.nearest_point(vec2f(18.0, 22.0)).unwrap();
+        assert_eq!(point_index, PointIndex::new(0, 3));
+        assert_eq!(point, vec2f(20.0, 22.0));
+        assert_eq!(distance, 2.0);
+
+        let mut curve = Contour::new();
+        curve.push_endpoint(vec2f(0.0, 0.0));
+        curve.push_quadratic(vec2f(2.0, 4.0), vec2f(4.0, 0.0));
+        let (point_index, point, distance) = curve.nearest_point(vec2f(2.0, 5.0)).unwrap();
+        assert_eq!(point_index, 0);
+        assert!((point - vec2f(2.0, 2.0)).length() < 0.001);
+        assert!((distance - 3.0).abs() < 0.001);
+
+        assert!(Outline::new().nearest_point(vec2f(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_find_self_intersections() {
+        let mut figure_eight = Contour::new();
+        figure_eight.push_endpoint(vec2f(0.0, 0.0));
+        figure_eight.push_endpoint(vec2f(2.0, 2.0));
+        figure_eight.push_endpoint(vec2f(2.0, 0.0));
+        figure_eight.push_endpoint(vec2f(0.0, 2.0));
+        figure_eight.close();
+        let intersections = figure_eight.find_self_intersections();
+        assert_eq!(intersections.len(), 1);
+        let (point_index_a, point_index_b, point) = intersections[0];
+        assert_eq!((point_index_a, point_index_b), (0, 2));
+        assert!((point - vec2f(1.0, 1.0)).length() < 0.001);
+
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)));
+        assert!(square.find_self_intersections().is_empty());
+
+        // A curve looping across an earlier line.
+        let mut looped = Contour::new();
+        looped.push_endpoint(vec2f(0.0, 0.0));
+        looped.push_endpoint(vec2f(4.0, 0.0));
+        looped.push_endpoint(vec2f(4.0, 2.0));
+        looped.push_quadratic(vec2f(2.0, -4.0), vec2f(0.0, 2.0));
+        let intersections = looped.find_self_intersections();
+        assert_eq!(intersections.len(), 2);
+        for &(point_index_a, point_index_b, point) in &intersections {
+            assert_eq!((point_index_a, point_index_b), (0, 2));
+            assert!(point.y().abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let mut star = Contour::new();
+        for point_index in 0..10 {
+            let radius = if point_index % 2 == 0 { 10.0 } else { 4.0 };
+            let angle = point_index as f32 * PI / 5.0;
+            star.push_endpoint(vec2f(angle.cos(), angle.sin()) * radius);
+        }
+        star.close();
+        let mut outline = Outline::new();
+        outline.push_contour(star.clone());
+
+        let hull = outline.convex_hull(false);
+        assert!(hull.is_closed());
+        assert_eq!(hull.len(), 5);
+        assert!(hull.area() > 0.0);
+        for point_index in 0..hull.len() {
+            assert!((hull.position_of(point_index).length() - 10.0).abs() < 0.001);
+        }
+
+        // Control points that bulge outward join the hull only when asked for.
+        let mut bump = Contour::new();
+        bump.push_endpoint(vec2f(10.0, 0.0));
+        bump.push_quadratic(vec2f(20.0, 0.0), vec2f(10.0, 0.1));
+        outline.push_contour(bump);
+        assert_eq!(outline.convex_hull(false).len(), 6);
+        assert!(outline.convex_hull(true).points.contains(&vec2f(20.0, 0.0)));
+
+        // Infinite points are skipped.
+        let mut stray = Contour::new();
+        stray.push_endpoint(vec2f(f32::NEG_INFINITY, 0.0));
+        stray.push_endpoint(vec2f(f32::INFINITY, 5.0));
+        outline.push_contour(stray);
+        assert_eq!(outline.convex_hull(false).len(), 6);
+
+        let mut line = Outline::new();
+        let mut collinear = Contour::new();
+        collinear.push_endpoint(vec2f(0.0, 0.0));
+        collinear.push_endpoint(vec2f(2.0, 2.0));
+        collinear.push_endpoint(vec2f(1.0, 1.0));
+        line.push_contour(collinear);
+        let hull = line.convex_hull(false);
+        assert!(!hull.is_closed());
+        assert_eq!(format!("{:?}", hull), "M 0 0 L 2 2");
+
+        let mut single = Outline::new();
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(3.0, 4.0));
+        point.push_endpoint(vec2f(3.0, 4.0));
+        single.push_contour(point);
+        assert_eq!(single.convex_hull(false).len(), 1);
+        assert!(Outline::new().convex_hull(true).is_empty());
+    }
+
+    #[test]
+    fn test_bounding_circle() {
+        let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        let (center, radius) = outline.bounding_circle();
+        assert!((center - vec2f(0.5, 0.5)).length() < 0.0001);
+        assert!((radius - f32::sqrt(2.0) * 0.5).abs() < 0.0001);
+        assert_eq!(outline.bounding_circle(), outline.clone().bounding_circle());
+
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(3.0, 4.0));
+        let mut single = Outline::new();
+        single.push_contour(point.clone());
+        assert_eq!(single.bounding_circle(), (vec2f(3.0, 4.0), 0.0));
+
+        point.push_endpoint(vec2f(7.0, 4.0));
+        let mut pair = Outline::new();
+        pair.push_contour(point);
+        assert_eq!(pair.bounding_circle(), (vec2f(5.0, 4.0), 2.0));
+
+        // An obtuse triangle's circle is set by its longest side, and a point inside changes
+        // nothing.
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(0.0, 0.0));
+        triangle.push_endpoint(vec2f(10.0, 0.0));
+        triangle.push_endpoint(vec2f(5.0, 1.0));
+        triangle.push_endpoint(vec2f(5.0, 0.5));
+        triangle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(triangle);
+        let (center, radius) = outline.bounding_circle();
+        assert!((center - vec2f(5.0, 0.0)).length() < 0.0001);
+        assert!((radius - 5.0).abs() < 0.0001);
+
+        // An acute triangle's circle is its circumcircle.
+        let mut equilateral = Contour::new();
+        for point_index in 0..3 {
+            let angle = point_index as f32 * 2.0 * PI / 3.0;
+            equilateral.push_endpoint(vec2f(angle.cos(), angle.sin()) * 2.0 + vec2f(1.0, 1.0));
+        }
+        let mut outline = Outline::new();
+        outline.push_contour(equilateral);
+        let (center, radius) = outline.bounding_circle();
+        assert!((center - vec2f(1.0, 1.0)).length() < 0.001);
+        assert!((radius - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        assert!((square.centroid() - vec2f(0.5, 0.5)).length() < 0.0001);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_translation_rotation_scale(vec2f(3.0, -2.0),
+                                                                          0.3,
+                                                                          2.0));
+        assert!((circle.centroid() - vec2f(3.0, -2.0)).length() < 0.0001);
+
+        // A curved shape that isn't symmetric: the region under a parabola, whose centroid is at
+        // 2/5 of its height.
+        let mut arch = Contour::new();
+        arch.push_endpoint(vec2f(0.0, 0.0));
+        arch.push_quadratic(vec2f(1.0, 2.0), vec2f(2.0, 0.0));
+        arch.close();
+        assert!((arch.centroid() - vec2f(1.0, 0.4)).length() < 0.0001);
+
+        // The hole winds the other way, so it is subtracted.
+        let mut hole = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)));
+        hole.reverse();
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        outline.push_contour(hole);
+        let expected = 28.0 / 12.0;
+        assert!((outline.centroid() - vec2f(expected, expected)).length() < 0.0001);
+
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(4.0, 2.0));
+        assert_eq!(line.centroid(), vec2f(2.0, 1.0));
+        assert_eq!(Outline::new().centroid(), vec2f(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_triangulate() {
+        let triangle_area = |triangle: &[Vector2F; 3]| {
+            (triangle[1] - triangle[0]).det(triangle[2] - triangle[0]) * 0.5
+        };
+
+        let square = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let triangles = square.triangulate(FillRule::Winding);
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().all(|triangle| triangle_area(triangle) > 0.0));
+
+        // A square with a square hole, wound the same way so that only even-odd leaves it empty.
+        let mut ring = square.clone();
+        ring.push_contour(Contour::from_rect(RectF::new(vec2f(1.0, 1.0), vec2f(2.0, 2.0))));
+        let triangles = ring.triangulate(FillRule::EvenOdd);
+        let area: f32 = triangles.iter().map(triangle_area).sum();
+        assert!((area - 12.0).abs() < 0.001);
+        assert!(triangles.iter().all(|triangle| triangle_area(triangle) > 0.0));
+        let hole_center = vec2f(2.0, 2.0);
+        assert!(triangles.iter().all(|triangle| {
+            let [a, b, c] = *triangle;
+            (b - a).det(hole_center - a) <= 0.0 || (c - b).det(hole_center - b) <= 0.0 ||
+                (a - c).det(hole_center - c) <= 0.0
+        }));
+
+        let area: f32 = ring.triangulate(FillRule::Winding).iter().map(triangle_area).sum();
+        assert!((area - 16.0).abs() < 0.001);
+
+        assert!(Outline::new().triangulate(FillRule::Winding).is_empty());
+    }
+
+    #[test]
+    fn test_offset() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        let mut reversed_square = square.clone();
+        reversed_square.reverse();
+        let grown_bounds = RectF::new(vec2f(-1.0, -1.0), vec2f(3.0, 3.0));
+        let rects_approx_eq = |a: RectF, b: RectF| {
+            (a.origin() - b.origin()).length() < 0.001 && (a.lower_right() - b.lower_right())
+                .length() < 0.001
+        };
+
+        let mitered = reversed_square.offset(1.0, LineJoin::Miter(10.0));
+        assert!(mitered.is_closed());
+        assert!(rects_approx_eq(mitered.bounds(), grown_bounds));
+        assert!((mitered.area() + 9.0).abs() < 0.001);
+
+        let rounded = square.offset(-1.0, LineJoin::Round);
+        assert!(rects_approx_eq(rounded.bounds(), grown_bounds));
+        assert!(rounded.area() > 0.0);
+
+        // Insetting trims the overlapping edges back to where they cross.
+        let inset = square.offset(0.25, LineJoin::Round);
+        assert_eq!(inset.len(), 4);
+        let inset_bounds = RectF::new(vec2f(0.25, 0.25), vec2f(0.5, 0.5));
+        assert!(rects_approx_eq(inset.bounds(), inset_bounds));
+        assert!((inset.area() - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_offset_winding() {
+        let clockwise = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let mut counterclockwise = clockwise.clone();
+        counterclockwise.reverse();
+        assert!(clockwise.is_clockwise() && !counterclockwise.is_clockwise());
+
+        // Each offset keeps the winding of its input, so compare the magnitudes of the areas.
+        for &(contour, grow_sign) in &[(&clockwise, -1.0), (&counterclockwise, 1.0)] {
+            let grown = contour.offset(grow_sign, LineJoin::Miter(10.0));
+            let shrunk = contour.offset(-grow_sign, LineJoin::Miter(10.0));
+            assert_eq!(grown.is_clockwise(), contour.is_clockwise());
+            assert_eq!(shrunk.is_clockwise(), contour.is_clockwise());
+            assert!((grown.area().abs() - 36.0).abs() < 0.001);
+            assert!((shrunk.area().abs() - 4.0).abs() < 0.001);
+            assert!(grown.bounds().contains_rect(contour.bounds()));
+            assert!(contour.bounds().contains_rect(shrunk.bounds()));
+        }
+    }
+
+    #[test]
+    fn test_round_corners() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let count_segments = |contour: &Contour, kind: SegmentKind| {
+            contour.iter(ContourIterFlags::empty()).filter(|segment| {
+                segment.kind == kind && segment.baseline.square_length() > EPSILON
+            }).count()
+        };
+
+        let rounded = square.round_corners(1.0);
+        assert!(rounded.is_closed());
+        assert_eq!(count_segments(&rounded, SegmentKind::Cubic), 4);
+        assert_eq!(count_segments(&rounded, SegmentKind::Line), 4);
+        assert_eq!(rounded.bounds(), square.bounds());
+        assert!((rounded.area() - (16.0 - (4.0 - PI))).abs() < 0.01);
+
+        // The radius is clamped so that the arcs meet in the middle of each side.
+        let circle = square.round_corners(10.0);
+        assert_eq!(count_segments(&circle, SegmentKind::Cubic), 4);
+        assert_eq!(count_segments(&circle, SegmentKind::Line), 0);
+        assert!((circle.area() - 4.0 * PI).abs() < 0.05);
+
+        let chamfered = square.chamfer_corners(1.0);
+        assert_eq!(chamfered.len(), 8);
+        assert!((chamfered.area() - 14.0).abs() < 0.001);
+
+        // Concave corners are left alone.
+        let mut notched = Contour::new();
+        for &point in &[vec2f(0.0, 0.0), vec2f(4.0, 0.0), vec2f(4.0, 4.0), vec2f(2.0, 2.0),
+                        vec2f(0.0, 4.0)] {
+            notched.push_endpoint(point);
+        }
+        notched.close();
+        assert_eq!(count_segments(&notched.chamfer_corners(0.5), SegmentKind::Line), 9);
+    }
+
+    #[test]
+    fn test_curve_order_conversion() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, -5.0), vec2f(10.0, 0.0));
+        contour.push_cubic(vec2f(10.0, 10.0), vec2f(0.0, 10.0), vec2f(0.0, 5.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let cubic = outline.to_cubic();
+        let kinds: Vec<_> = cubic.contours()[0].iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                                               .map(|segment| segment.kind)
+                                               .collect();
+        assert_eq!(kinds, vec![SegmentKind::Cubic, SegmentKind::Cubic]);
+        assert!(cubic.contours()[0].is_closed());
+        assert!((cubic.area() - outline.area()).abs() < 0.001);
+
+        let quadratic = outline.to_quadratic(0.01);
+        let contour = &quadratic.contours()[0];
+        assert!(contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).all(|segment| {
+            segment.kind == SegmentKind::Quadratic
+        }));
+        assert_eq!(contour.position_of(contour.len() - 1), vec2f(0.0, 5.0));
+        assert!((quadratic.area() - outline.area()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_equality() {
+        let rect = RectF::new(vec2f(1.0, 2.0), vec2f(3.0, 4.0));
+        let contour = Contour::from_rect(rect);
+        let mut roomy_contour = Contour::with_capacity(64);
+        for point_index in 0..contour.len() {
+            roomy_contour.push_endpoint(contour.position_of(point_index));
+        }
+        roomy_contour.close();
+        assert_eq!(contour, roomy_contour);
+
+        let mut open_contour = roomy_contour.clone();
+        open_contour.closed = false;
+        assert_ne!(contour, open_contour);
+        assert!(!contour.approx_eq(&open_contour, 0.1));
+
+        let outline = Outline::from_rect(rect);
+        let mut round_trip = outline.clone();
+        round_trip.transform(&Transform2F::from_rotation(0.3));
+        round_trip.transform(&Transform2F::from_rotation(-0.3));
+        assert!(outline.approx_eq(&round_trip, 0.001));
+        assert!(!outline.approx_eq(&Outline::new(), 0.001));
+
+        let mut moved = outline.clone();
+        moved.transform(&Transform2F::from_translation(vec2f(0.01, 0.0)));
+        assert_ne!(outline, moved);
+        assert!(outline.approx_eq(&moved, 0.1));
+        assert!(!outline.approx_eq(&moved, 0.001));
+    }
+
+    #[test]
+    fn test_hash() {
+        let outline = Outline::from_rect(RectF::new(vec2f(1.0, 2.0), vec2f(3.0, 4.0)));
+        let mut outlines = HashSet::new();
+        outlines.insert(outline.clone());
+        outlines.insert(outline.clone());
+        assert_eq!(outlines.len(), 1);
+
+        let mut moved = outline.clone();
+        moved.transform(&Transform2F::from_translation(vec2f(1.0, 0.0)));
+        outlines.insert(moved);
+        assert_eq!(outlines.len(), 2);
+        assert!(outlines.contains(&outline));
+
+        // Equality follows the bit patterns, so negative zero differs from positive zero.
+        let mut positive_zero = Contour::new();
+        positive_zero.push_endpoint(vec2f(0.0, 1.0));
+        let mut negative_zero = Contour::new();
+        negative_zero.push_endpoint(vec2f(-0.0, 1.0));
+        assert_ne!(positive_zero, negative_zero);
+    }
+
+    #[test]
+    fn test_primitive_shapes() {
+        let circle = Outline::from_circle(vec2f(10.0, 20.0), 5.0);
+        assert_eq!(circle.bounds(), RectF::new(vec2f(5.0, 15.0), vec2f(10.0, 10.0)));
+        assert_eq!(circle.contours().len(), 1);
+        assert!(circle.contours()[0].is_closed());
+        assert!((circle.area() - 25.0 * PI).abs() < 0.05);
+        for point_index in 0..circle.contours()[0].len() {
+            let position = circle.contours()[0].position_of(point_index);
+            if circle.contours()[0].point_is_endpoint(point_index) {
+                assert!(((position - vec2f(10.0, 20.0)).length() - 5.0).abs() < 0.001);
+            }
+        }
+
+        let ellipse = Outline::from_ellipse(vec2f(0.0, 0.0), vec2f(4.0, 2.0));
+        assert_eq!(ellipse.bounds(), RectF::new(vec2f(-4.0, -2.0), vec2f(8.0, 4.0)));
+        assert_eq!(ellipse.bounds(), ellipse.tight_bounds());
+        assert!((ellipse.area() - 8.0 * PI).abs() < 0.02);
+
+        let rect = RectF::new(vec2f(1.0, 2.0), vec2f(10.0, 6.0));
+        let rounded = Outline::from_rounded_rect(rect, vec2f(1.0, 1.0));
+        assert_eq!(rounded.bounds(), rect);
+        assert!((rounded.area() - (60.0 - (4.0 - PI))).abs() < 0.01);
+        let fully_rounded = Outline::from_rounded_rect(rect, vec2f(100.0, 100.0));
+        assert!((fully_rounded.area() - 15.0 * PI).abs() < 0.05);
+        let unrounded = Outline::from_rounded_rect(rect, vec2f(0.0, 1.0));
+        assert_eq!(unrounded, Outline::from_rect(rect));
+    }
+
+    #[test]
+    fn test_map_points_mut() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 2.0)));
+        outline.push_contour(Contour::from_ellipse(vec2f(10.0, 10.0), vec2f(1.0, 1.0)));
+        let mut translated = outline.clone();
+        translated.translate(vec2f(-20.0, 5.0));
+        outline.map_points_mut(|point| point + vec2f(-20.0, 5.0));
+        assert_eq!(outline, translated);
+        assert_eq!(outline.bounds(), translated.bounds());
+        assert_eq!(outline.contours()[1].bounds(), translated.contours()[1].bounds());
+
+        // Bounds shrink as well as grow.
+        outline.map_points_mut(|point| point * 0.5);
+        assert_eq!(outline.bounds(), RectF::new(vec2f(-10.0, 2.5), vec2f(5.5, 5.5)));
+    }
+
+    #[test]
+    fn test_retain_contours() {
+        let rect = RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 2.0));
+        let mut outline = Outline::from_rect(rect);
+        let mut sliver = Contour::new();
+        sliver.push_endpoint(vec2f(10.0, 10.0));
+        sliver.push_endpoint(vec2f(20.0, 10.0));
+        sliver.close();
+        outline.push_contour(sliver);
+        assert_eq!(outline.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(20.0, 10.0)));
+
+        outline.retain_contours(|contour| contour.area().abs() > EPSILON);
+        assert_eq!(outline.contours().len(), 1);
+        assert_eq!(outline.bounds(), rect);
+
+        outline.retain_contours(|_| false);
+        assert!(outline.contours().is_empty());
+        assert_eq!(outline.bounds(), RectF::default());
+    }
+
+    #[test]
+    fn test_quantize() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.49, 0.51));
+        contour.push_quadratic(vec2f(2.6, -1.4), vec2f(-3.51, 7.49));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let original = outline.clone();
+        outline.quantize(0.0);
+        outline.quantize(-1.0);
+        assert_eq!(outline, original);
+
+        outline.quantize(1.0);
+        let points = &outline.contours()[0].points;
+        assert_eq!(points, &[vec2f(0.0, 1.0), vec2f(3.0, -1.0), vec2f(-4.0, 7.0)]);
+        assert_eq!(outline.bounds(), RectF::from_points(vec2f(-4.0, -1.0), vec2f(3.0, 7.0)));
+
+        outline.quantize(0.25);
+        assert_eq!(outline.contours()[0].points[1], vec2f(3.0, -1.0));
+        let mut fine = original.clone();
+        fine.quantize(0.25);
+        assert_eq!(fine.contours()[0].points[0], vec2f(0.5, 0.5));
+    }
+
+    #[test]
+    fn test_recompute_bounds() {
+        let mut contour = Contour::from_rect(RectF::new(vec2f(1.0, 2.0), vec2f(3.0, 4.0)));
+        contour.points.truncate(2);
+        contour.flags.truncate(2);
+        contour.recompute_bounds();
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(1.0, 2.0), vec2f(4.0, 2.0)));
+
+        contour.points.clear();
+        contour.flags.clear();
+        contour.recompute_bounds();
+        assert_eq!(contour.bounds(), RectF::default());
+    }
+
+    #[test]
+    fn test_insert_and_remove_points() {
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+        let original_line = line.clone();
+        line.insert_point_after(0, vec2f(5.0, 3.0));
+        assert_eq!(line.points, vec![vec2f(0.0, 0.0), vec2f(5.0, 0.0), vec2f(10.0, 0.0)]);
+        assert_eq!(line.flags.len(), 3);
+        assert_eq!(line.bounds(), original_line.bounds());
+        line.insert_point_after(2, vec2f(20.0, 0.0));
+        assert_eq!(line.len(), 3);
+        line.remove_point(1);
+        assert_eq!(line, original_line);
+
+        // Splitting a curve keeps its shape.
+        let mut curve = Contour::new();
+        curve.push_endpoint(vec2f(0.0, 0.0));
+        curve.push_cubic(vec2f(0.0, 10.0), vec2f(10.0, 10.0), vec2f(10.0, 0.0));
+        let original_segment = curve.iter(ContourIterFlags::empty()).next().unwrap();
+        curve.insert_point_after(0, vec2f(5.0, 10.0));
+        assert_eq!(curve.len(), 7);
+        assert!((curve.position_of(3) - vec2f(5.0, 7.5)).length() < 0.01);
+        let halves: Vec<_> = curve.iter(ContourIterFlags::empty()).collect();
+        assert_eq!(halves.len(), 2);
+        for step in 0..=4 {
+            let t = step as f32 / 4.0;
+            assert!((halves[0].sample(t) - original_segment.sample(t * 0.5)).length() < 0.01);
+            assert!((halves[1].sample(t) - original_segment.sample(0.5 + t * 0.5)).length() <
+                    0.01);
+        }
+        assert_eq!(curve.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 7.5)));
+
+        // Joining the halves again gives back a similar curve.
+        curve.remove_point(3);
+        let joined = curve.iter(ContourIterFlags::empty()).next().unwrap();
+        assert_eq!(curve.len(), 4);
+        assert_eq!(joined.kind, SegmentKind::Cubic);
+        assert!((joined.sample(0.5) - original_segment.sample(0.5)).length() < 0.5);
+
+        // Closed contours wrap around.
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let mut contour = square.clone();
+        contour.insert_point_after(3, vec2f(-1.0, 1.0));
+        assert_eq!(contour.len(), 5);
+        assert_eq!(contour.position_of(4), vec2f(0.0, 1.0));
+        assert_eq!(contour.area(), square.area());
+        contour.remove_point(4);
+        assert_eq!(contour, square);
+
+        contour.remove_point(0);
+        assert!(contour.is_closed());
+        assert_eq!(contour.points, vec![vec2f(4.0, 0.0), vec2f(4.0, 4.0), vec2f(0.0, 4.0)]);
+        assert_eq!(contour.area(), 8.0);
+        assert_eq!(contour.bounds(), square.bounds());
+        contour.remove_point(1);
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        assert_eq!(contour.len(), 2);
+    }
+
+    #[test]
+    fn test_edges() {
+        let circle = Contour::from_ellipse(vec2f(0.0, 0.0), vec2f(10.0, 10.0));
+        let edges: Vec<_> = circle.edges(0.01).collect();
+        assert!(edges.len() > 8);
+        for (edge, next_edge) in edges.iter().zip(edges.iter().skip(1)) {
+            assert_eq!(edge.to(), next_edge.from());
+        }
+        assert_eq!(edges.last().unwrap().to(), edges[0].from());
+        let edge_length: f32 = edges.iter().map(|edge| edge.vector().length()).sum();
+        assert!((edge_length - circle.length()).abs() < 0.1);
+
+        let mut flattened = Contour::new();
+        circle.flatten_into(0.01, &mut flattened);
+        assert_eq!(edges.len(), flattened.len() as usize);
+
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let edges: Vec<_> = square.edges(0.01).collect();
+        assert_eq!(edges.len(), 4);
+        assert_eq!(edges[3], LineSegment2F::new(vec2f(0.0, 4.0), vec2f(0.0, 0.0)));
+        assert_eq!(square.edges(0.01).map(|edge| edge.vector().length()).sum::<f32>(), 16.0);
+        assert_eq!(Contour::new().edges(0.01).count(), 0);
+    }
+
+    #[test]
+    fn test_apply_perspective_inverse() {
+        let transform = Transform4F::from_perspective(1.0, 1.0, 0.1, 100.0) *
+            Transform4F::from_translation(Vector4F::new(-2.0, -2.0, -8.0, 1.0)) *
+            Transform4F::from_rotation(0.5, 0.0, 0.0);
+        let perspective = Perspective::new(&transform, Vector2I::new(800, 600));
+        let square = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+
+        let mut outline = square.clone();
+        outline.apply_perspective(&perspective);
+        assert!(!outline.approx_eq(&square, 0.1));
+        assert!(outline.apply_perspective_inverse(&perspective));
+        assert!(outline.approx_eq(&square, 0.001));
+        assert!((outline.bounds().size() - square.bounds().size()).length() < 0.001);
+
+        let flat = Transform4F::from_scale(Vector4F::new(0.0, 1.0, 1.0, 1.0));
+        let mut outline = square.clone();
+        assert!(!outline.apply_perspective_inverse(&Perspective::new(&flat,
+                                                                     Vector2I::new(800, 600))));
+        assert_eq!(outline, square);
+    }
+
+    #[test]
+    fn test_endpoint_and_segment_counts() {
+        let mut cubic = Contour::new();
+        cubic.push_endpoint(vec2f(0.0, 0.0));
+        cubic.push_cubic(vec2f(0.0, 10.0), vec2f(10.0, 10.0), vec2f(10.0, 0.0));
+        assert_eq!(cubic.len(), 4);
+        assert_eq!(cubic.endpoint_count(), 2);
+        assert_eq!(cubic.segment_count(), 1);
+        cubic.close();
+        assert_eq!(cubic.segment_count(), 2);
+        assert_eq!(cubic.segment_count() as usize, cubic.iter(ContourIterFlags::empty()).count());
+
+        assert_eq!(Contour::new().segment_count(), 0);
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(1.0, 1.0));
+        assert_eq!(point.segment_count(), 0);
+
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        outline.push_contour(cubic);
+        assert_eq!(outline.endpoint_count(), 6);
+        assert_eq!(outline.segment_count(), 6);
+    }
+
+    #[test]
+    fn test_point_accessors() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, 5.0), vec2f(10.0, 0.0));
+        assert_eq!(contour.points().len(), contour.flags().len());
+        for (point_index, &point) in contour.points().iter().enumerate() {
+            let point_index = point_index as u32;
+            assert_eq!(point, contour.position_of(point_index));
+            assert_eq!(contour.flags()[point_index as usize].is_empty(),
+                       contour.point_is_endpoint(point_index));
+        }
+        assert_eq!(contour.flags()[1], PointFlags::CONTROL_POINT_0);
+    }
+
+    #[test]
+    fn test_point_index_try_new() {
+        let point_index = PointIndex::try_new(0xfff, 0x000f_ffff).unwrap();
+        assert_eq!((point_index.contour(), point_index.point()), (0xfff, 0x000f_ffff));
+        assert_eq!(PointIndex::try_new(3, 7), Ok(PointIndex::new(3, 7)));
+        assert_eq!(PointIndex::try_new(0x1000, 0), Err(PointIndexError::ContourOutOfRange(0x1000)));
+        assert_eq!(PointIndex::try_new(0, 0x0010_0000),
+                   Err(PointIndexError::PointOutOfRange(0x0010_0000)));
+    }
+
+    #[test]
+    fn test_iter_point_indices() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        outline.push_contour(Contour::from_ellipse(vec2f(10.0, 10.0), vec2f(2.0, 1.0)));
+        let points: Vec<_> = outline.iter_point_indices().collect();
+        let point_count = outline.contours().iter().map(|contour| contour.len()).sum::<u32>();
+        assert_eq!(points.len(), point_count as usize);
+        assert_eq!(points[0], (PointIndex::new(0, 0), vec2f(0.0, 0.0)));
+        assert_eq!(points[4].0, PointIndex::new(1, 0));
+        for &(point_index, point) in &points {
+            let contour = &outline.contours()[point_index.contour() as usize];
+            assert_eq!(contour.position_of(point_index.point()), point);
+        }
+        assert_eq!(Outline::new().iter_point_indices().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "contour index 4096 is out of range")]
+    fn test_iter_point_indices_out_of_range() {
+        let mut outline = Outline::new();
+        for _ in 0..4097 {
+            outline.push_contour(Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0))));
+        }
+        outline.iter_point_indices().for_each(drop);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let (first, second) = square.split_at(2);
+        assert!(!first.is_closed() && !second.is_closed());
+        assert_eq!(first.points, vec![vec2f(0.0, 0.0), vec2f(4.0, 0.0), vec2f(4.0, 4.0)]);
+        assert_eq!(second.points, vec![vec2f(4.0, 4.0), vec2f(0.0, 4.0), vec2f(0.0, 0.0)]);
+        assert_eq!(first.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        assert_eq!(second.bounds(), RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        assert_eq!(first.length() + second.length(), square.length());
+
+        // Reassemble the pieces.
+        let mut reassembled = first.clone();
+        for point_index in 1..(second.len() - 1) {
+            reassembled.push_endpoint(second.position_of(point_index));
+        }
+        reassembled.close();
+        assert_eq!(reassembled, square);
+
+        let opened = square.open_at(2);
+        assert!(!opened.is_closed());
+        assert_eq!(opened.points, vec![
+            vec2f(4.0, 4.0), vec2f(0.0, 4.0), vec2f(0.0, 0.0), vec2f(4.0, 0.0), vec2f(4.0, 4.0),
+        ]);
+        assert_eq!(opened.length(), square.length());
+
+        // Control points stay attached to their segments.
+        let mut curve = Contour::new();
+        curve.push_endpoint(vec2f(0.0, 0.0));
+        curve.push_quadratic(vec2f(5.0, 5.0), vec2f(10.0, 0.0));
+        curve.push_endpoint(vec2f(20.0, 0.0));
+        let (first, second) = curve.split_at(2);
+        assert_eq!(first.len(), 3);
+        assert_eq!(first.iter(ContourIterFlags::empty()).next().unwrap().kind,
+                   SegmentKind::Quadratic);
+        assert_eq!(second.points, vec![vec2f(10.0, 0.0), vec2f(20.0, 0.0)]);
+        assert_eq!(curve.open_at(2), curve);
+    }
+
+    #[test]
+    fn test_join() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(5.0, 0.0));
+        let mut other = Contour::new();
+        other.push_endpoint(vec2f(5.0, 0.001));
+        other.push_endpoint(vec2f(10.0, 0.0));
+
+        let mut welded = contour.clone();
+        welded.join(&other, 0.01);
+        assert_eq!(welded.points, vec![vec2f(0.0, 0.0), vec2f(5.0, 0.0), vec2f(10.0, 0.0)]);
+        assert_eq!(welded.flags.len(), 3);
+        assert_eq!(welded.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 0.0)));
+        assert_eq!(welded.length(), 10.0);
+
+        let mut unwelded = contour.clone();
+        unwelded.join(&other, 0.0);
+        assert_eq!(unwelded.len(), 4);
+        assert_eq!(unwelded.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 0.001)));
+
+        let mut empty = Contour::new();
+        empty.join(&other, 0.01);
+        assert_eq!(empty, other);
+        assert_eq!(empty.bounds(), other.bounds());
+        welded.join(&Contour::new(), 0.01);
+        assert_eq!(welded.len(), 3);
+    }
+
+    #[test]
+    fn test_push_points() {
+        let points = [vec2f(1.0, 2.0), vec2f(3.0, -1.0), vec2f(4.0, 4.0), vec2f(0.0, 5.0)];
+        let flags = [
+            PointFlags::empty(),
+            PointFlags::CONTROL_POINT_0,
+            PointFlags::CONTROL_POINT_1,
+            PointFlags::empty(),
+        ];
+        let mut one_by_one = Contour::new();
+        for (&point, &flags) in points.iter().zip(flags.iter()) {
+            one_by_one.push_point(point, flags, true);
+        }
+        let mut batched = Contour::new();
+        batched.push_points(&points, &flags);
+        assert_eq!(batched, one_by_one);
+        assert_eq!(batched.flags, one_by_one.flags);
+        assert_eq!(batched.bounds(), one_by_one.bounds());
+
+        // Pushing onto a non-empty contour grows the existing bounds.
+        batched.push_points(&[vec2f(-2.0, 0.0)], &[PointFlags::empty()]);
+        one_by_one.push_endpoint(vec2f(-2.0, 0.0));
+        assert_eq!(batched.bounds(), one_by_one.bounds());
+        batched.push_points(&[], &[]);
+        assert_eq!(batched, one_by_one);
+    }
+
+    #[test]
+    fn test_iter_indexed() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_quadratic(vec2f(15.0, 5.0), vec2f(10.0, 10.0));
+        contour.push_cubic(vec2f(5.0, 15.0), vec2f(0.0, 15.0), vec2f(0.0, 10.0));
+        contour.push_endpoint(vec2f(0.0, 5.0));
+
+        let segments: Vec<_> = contour.iter_indexed().collect();
+        let start_indices: Vec<_> = segments.iter().map(|&(index, _)| index).collect();
+        assert_eq!(start_indices, vec![0, 1, 3, 6]);
+        for &(start_index, ref segment) in &segments {
+            assert_eq!(contour.position_of(start_index), segment.baseline.from());
+        }
+        assert_eq!(segments[1].1.kind, SegmentKind::Quadratic);
+        assert_eq!(segments[2].1.kind, SegmentKind::Cubic);
+
+        contour.close();
+        let segments: Vec<_> = contour.iter_indexed().collect();
+        assert_eq!(segments.len(), 5);
+        assert_eq!(segments[4].0, 7);
+        assert_eq!(segments[4].1.baseline.to(), vec2f(0.0, 0.0));
+        assert_eq!(Contour::new().iter_indexed().count(), 0);
+    }
+
+    #[test]
+    fn test_outline_is_empty() {
+        let mut outline = Outline::new();
+        assert!(outline.is_empty());
+        assert_eq!(outline.point_count(), 0);
+
+        // Empty contours don't count.
+        outline.contours.push(Contour::new());
+        assert!(outline.is_empty());
+        assert_eq!(outline.point_count(), 0);
+
+        outline.push_contour(Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0))));
+        assert!(!outline.is_empty());
+        assert_eq!(outline.contours().len(), 2);
+        assert_eq!(outline.point_count(), 4);
+    }
+
+    #[test]
+    fn test_fit_to_rect() {
+        let source = Outline::from_rect(RectF::new(vec2f(5.0, 5.0), vec2f(10.0, 20.0)));
+        let target = RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0));
+        let approx_eq = |a: RectF, b: RectF| {
+            a.origin().approx_eq(b.origin(), EPSILON) && a.size().approx_eq(b.size(), EPSILON)
+        };
+
+        let mut outline = source.clone();
+        outline.fit_to_rect(target, true);
+        assert!(approx_eq(outline.bounds(), RectF::new(vec2f(25.0, 0.0), vec2f(50.0, 100.0))));
+
+        let mut outline = source.clone();
+        outline.fit_to_rect(target, false);
+        assert!(approx_eq(outline.bounds(), target));
+
+        // A horizontal line is only stretched horizontally.
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(line);
+        outline.fit_to_rect(target, true);
+        assert!(approx_eq(outline.bounds(), RectF::new(vec2f(0.0, 50.0), vec2f(100.0, 0.0))));
+
+        let mut outline = Outline::new();
+        outline.fit_to_rect(target, true);
+        assert!(outline.is_empty());
+        assert_eq!(outline.bounds(), RectF::default());
+    }
+
+    #[test]
+    fn test_from_catmull_rom() {
+        let points = [vec2f(0.0, 0.0), vec2f(10.0, 5.0), vec2f(20.0, -5.0), vec2f(30.0, 0.0)];
+        let open = Contour::from_catmull_rom(&points, 0.0, false);
+        assert!(!open.is_closed());
+        let segments: Vec<_> = open.iter(ContourIterFlags::empty()).collect();
+        assert_eq!(segments.len(), 3);
+        for (segment_index, segment) in segments.iter().enumerate() {
+            assert_eq!(segment.kind, SegmentKind::Cubic);
+            assert_eq!(segment.sample(0.0), points[segment_index]);
+            assert!((segment.sample(1.0) - points[segment_index + 1]).length() < EPSILON);
+        }
+
+        // Tangents are continuous at interior points.
+        let incoming = segments[0].baseline.to() - segments[0].ctrl.to();
+        let outgoing = segments[1].ctrl.from() - segments[1].baseline.from();
+        assert!(incoming.det(outgoing).abs() < EPSILON);
+
+        let closed = Contour::from_catmull_rom(&points, 0.5, true);
+        assert!(closed.is_closed());
+        let segments: Vec<_> = closed.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).collect();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[3].baseline.to(), points[0]);
+        for (segment_index, segment) in segments.iter().enumerate() {
+            assert_eq!(segment.baseline.from(), points[segment_index]);
+        }
+
+        // Full tension gives straight lines.
+        let straight = Contour::from_catmull_rom(&points, 1.0, false);
+        for segment in straight.iter(ContourIterFlags::empty()) {
+            assert_eq!(segment.ctrl, segment.baseline);
+        }
+
+        assert!(Contour::from_catmull_rom(&[], 0.0, false).is_empty());
+        assert_eq!(Contour::from_catmull_rom(&points[..1], 0.0, true).len(), 1);
+    }
+
+    #[test]
+    fn test_fit_to_points() {
+        let mut original = Contour::new();
+        original.push_endpoint(vec2f(0.0, 0.0));
+        original.push_cubic(vec2f(10.0, 40.0), vec2f(60.0, 40.0), vec2f(80.0, 0.0));
+        let segment = original.iter(ContourIterFlags::empty()).next().unwrap();
+        let samples: Vec<_> = (0..=50).map(|step| segment.sample(step as f32 / 50.0)).collect();
+
+        // The end tangents are estimated from the samples, so a loose fit needs few curves.
+        let fitted = Contour::fit_to_points(&samples, 1.0);
+        assert_eq!(fitted.position_of(0), samples[0]);
+        assert_eq!(fitted.last_position(), samples.last().cloned());
+        assert!(fitted.segment_count() <= 2);
+        assert!((fitted.length() - original.length()).abs() < 1.0);
+        for &error in &[1.0, 0.1] {
+            let fitted = Contour::fit_to_points(&samples, error);
+            for &sample in &samples {
+                let (_, _, distance) = fitted.nearest_point(sample).unwrap();
+                assert!(distance < error);
+            }
+        }
+
+        // A corner is kept sharp only when asked for.
+        let mut corner = vec![];
+        corner.extend((0..=10).map(|step| vec2f(step as f32, 0.0)));
+        corner.extend((1..=10).map(|step| vec2f(10.0, step as f32)));
+        let sharp = Contour::fit_to_points_with_corners(&corner, 0.01, 0.5);
+        assert_eq!(sharp.endpoint_count(), 3);
+        assert_eq!(sharp.position_of(3), vec2f(10.0, 0.0));
+        let smooth = Contour::fit_to_points(&corner, 0.01);
+        assert!(smooth.endpoint_count() > 1);
+        for &point in &corner {
+            assert!(smooth.nearest_point(point).unwrap().2 < 0.01);
+        }
+
+        assert!(Contour::fit_to_points(&[], 0.1).is_empty());
+        assert_eq!(Contour::fit_to_points(&[vec2f(1.0, 1.0); 3], 0.1).len(), 1);
+    }
+
+    #[test]
+    fn test_is_same_shape() {
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        let mut rotated = Contour::new();
+        for point_index in 0..4 {
+            rotated.push_endpoint(square.position_of((point_index + 2) % 4));
+        }
+        rotated.close();
+        assert_ne!(rotated, square);
+        assert!(rotated.is_same_shape(&square, EPSILON));
+        assert!(square.is_same_shape(&rotated, EPSILON));
+
+        // An explicit closing point doesn't matter.
+        let mut explicitly_closed = square.clone();
+        explicitly_closed.push_endpoint(vec2f(0.0, 0.0));
+        assert!(explicitly_closed.is_same_shape(&square, EPSILON));
+
+        let mut nudged = rotated.clone();
+        nudged.points[1] += vec2f(0.0005, 0.0);
+        assert!(nudged.is_same_shape(&square, EPSILON));
+        nudged.points[1] += vec2f(0.1, 0.0);
+        assert!(!nudged.is_same_shape(&square, EPSILON));
+
+        let mut reversed = square.clone();
+        reversed.reverse();
+        assert!(!reversed.is_same_shape(&square, EPSILON));
+
+        // A cubic only matches a cubic, even if it traces a straight line.
+        let mut lines = Contour::new();
+        lines.push_endpoint(vec2f(0.0, 0.0));
+        lines.push_endpoint(vec2f(3.0, 0.0));
+        lines.push_endpoint(vec2f(3.0, 3.0));
+        lines.close();
+        let mut curves = Contour::new();
+        curves.push_endpoint(vec2f(0.0, 0.0));
+        curves.push_cubic(vec2f(1.0, 0.0), vec2f(2.0, 0.0), vec2f(3.0, 0.0));
+        curves.push_endpoint(vec2f(3.0, 3.0));
+        curves.close();
+        assert!(!curves.is_same_shape(&lines, EPSILON));
+        let mut rotated_curves = curves.open_at(4);
+        rotated_curves.set_closed(true);
+        assert!(curves.is_same_shape(&rotated_curves, EPSILON));
+
+        let mut open = square.clone();
+        open.set_closed(false);
+        assert!(!open.is_same_shape(&square, EPSILON));
+        assert!(!open.is_same_shape(&square.open_at(1), EPSILON));
+        assert!(Contour::new().is_same_shape(&Contour::new(), EPSILON));
+    }
+
+    #[test]
+    fn test_to_monotonic() {
+        let is_monotonic = |contour: &Contour| {
+            contour.iter(ContourIterFlags::empty()).all(|segment| {
+                segment.is_line() ||
+                    segment.to_cubic().as_cubic_segment().y_extrema() == (None, None)
+            })
+        };
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(10.0, 20.0), vec2f(20.0, -20.0), vec2f(30.0, 0.0));
+        contour.push_quadratic(vec2f(35.0, 10.0), vec2f(40.0, 5.0));
+        contour.push_endpoint(vec2f(40.0, -5.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour.clone());
+
+        let monotonic = outline.to_monotonic();
+        assert_eq!(outline.contours()[0], contour);
+        assert!(!is_monotonic(&outline.contours()[0]));
+        assert!(is_monotonic(&monotonic.contours()[0]));
+        assert!(monotonic.contours()[0].is_closed());
+        assert_eq!(monotonic.contours()[0].segment_count(), 7);
+        assert!(outline.bounds().contains_rect(monotonic.bounds()));
+
+        // The pieces trace the same curves.
+        let original_segments: Vec<_> = contour.monotonic_segments().collect();
+        let copied_segments: Vec<_> = monotonic.contours()[0].monotonic_segments().collect();
+        assert_eq!(original_segments.len(), copied_segments.len());
+        for (original, copied) in original_segments.iter().zip(copied_segments.iter()) {
+            assert!(original.sample(0.5).approx_eq(copied.sample(0.5), EPSILON));
+        }
+    }
+
+    #[test]
+    fn test_rasterize_coverage() {
+        let rect = Outline::from_rect(RectF::new(vec2f(2.0, 3.0), vec2f(6.0, 4.0)));
+        let coverage = rect.rasterize_coverage(10, 10, FillRule::Winding);
+        assert_eq!(coverage.len(), 100);
+        for y in 0..10 {
+            for x in 0..10 {
+                let inside = (2..8).contains(&x) && (3..7).contains(&y);
+                let expected = if inside { 1.0 } else { 0.0 };
+                assert!((coverage[y * 10 + x] - expected).abs() < EPSILON);
+            }
+        }
+
+        // Edges through the middle of a pixel cover half of it, whichever way the contour winds.
+        let mut half = Outline::from_rect(RectF::new(vec2f(1.5, 0.0), vec2f(2.0, 2.0)));
+        half.reverse();
+        let coverage = half.rasterize_coverage(4, 2, FillRule::Winding);
+        for &(x, expected) in &[(0, 0.0), (1, 0.5), (2, 1.0), (3, 0.5)] {
+            assert!((coverage[x] - expected).abs() < EPSILON);
+        }
+
+        // Curves, and shapes sticking out of the image.
+        let circle = Outline::from_circle(vec2f(8.0, 8.0), 6.0);
+        let total: f32 = circle.rasterize_coverage(16, 16, FillRule::Winding).iter().sum();
+        assert!((total - PI * 36.0).abs() < 0.5);
+        let clipped_circle = Outline::from_circle(vec2f(0.0, 0.0), 6.0);
+        let total: f32 = clipped_circle.rasterize_coverage(16, 16, FillRule::Winding).iter().sum();
+        assert!((total - PI * 9.0).abs() < 0.5);
+
+        // Overlapping contours.
+        let mut overlap = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(2.0, 1.0)));
+        overlap.push_contour(Contour::from_rect(RectF::new(vec2f(1.0, 0.0), vec2f(2.0, 1.0))));
+        let winding = overlap.rasterize_coverage(3, 1, FillRule::Winding);
+        let even_odd = overlap.rasterize_coverage(3, 1, FillRule::EvenOdd);
+        assert_eq!(winding, vec![1.0, 1.0, 1.0]);
+        assert_eq!(even_odd, vec![1.0, 0.0, 1.0]);
+
+        let empty = Outline::new().rasterize_coverage(4, 4, FillRule::Winding);
+        assert_eq!(empty, vec![0.0; 16]);
+        assert!(rect.rasterize_coverage(0, 0, FillRule::Winding).is_empty());
+    }
+
+    #[test]
+    fn test_to_postscript_path() {
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(0.0, 0.0));
+        triangle.push_endpoint(vec2f(10.0, 0.0));
+        triangle.push_endpoint(vec2f(5.0, 8.66));
+        triangle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(triangle);
+        assert_eq!(outline.to_postscript_path(2, None),
+                   "0.00 0.00 m\n10.00 0.00 l\n5.00 8.66 l\nh\n");
+        assert_eq!(outline.to_postscript_path(0, Some(100.0)), "0 100 m\n10 100 l\n5 91 l\nh\n");
+
+        // Quadratics become cubics, and open contours aren't closed.
+        let mut curve = Contour::new();
+        curve.push_endpoint(vec2f(0.0, -0.001));
+        curve.push_quadratic(vec2f(3.0, 3.0), vec2f(6.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(curve);
+        assert_eq!(outline.to_postscript_path(1, None), "0.0 0.0 m\n2.0 2.0 4.0 2.0 6.0 0.0 c\n");
+        assert_eq!(Outline::new().to_postscript_path(2, None), "");
+    }
+
+    #[test]
+    fn test_transform_matrix() {
+        let original = Outline::from_rect(RectF::new(vec2f(1.0, 0.0), vec2f(2.0, 3.0)));
+        let mut outline = original.clone();
+        outline.transform_matrix(&[1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(outline, original);
+
+        // A quarter turn followed by a translation.
+        outline.transform_matrix(&[0.0, 1.0, -1.0, 0.0, 10.0, 20.0]);
+        let contour = &outline.contours()[0];
+        assert_eq!(contour.position_of(0), vec2f(10.0, 21.0));
+        assert_eq!(contour.position_of(1), vec2f(10.0, 23.0));
+        assert_eq!(contour.position_of(2), vec2f(7.0, 23.0));
+        assert_eq!(outline.bounds(), RectF::from_points(vec2f(7.0, 21.0), vec2f(10.0, 23.0)));
+    }
+
+    #[test]
+    fn test_trim() {
+        let rect = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(30.0, 20.0)));
+        assert_eq!(rect.length(), 100.0);
+
+        let half = rect.trim(0.0, 0.5);
+        assert_eq!(half.contours().len(), 1);
+        let contour = &half.contours()[0];
+        assert!(!contour.is_closed());
+        assert!((contour.length() - 50.0).abs() < 0.001);
+        assert_eq!(contour.points(), &[vec2f(0.0, 0.0), vec2f(30.0, 0.0), vec2f(30.0, 20.0)]);
+
+        // Wrapping around runs across the start of the closed rectangle.
+        let wrapped = rect.trim(0.8, 0.2);
+        assert_eq!(wrapped.contours().len(), 1);
+        assert_eq!(wrapped.contours()[0].points(),
+                   &[vec2f(0.0, 20.0), vec2f(0.0, 0.0), vec2f(20.0, 0.0)]);
+        assert_eq!(wrapped.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(20.0, 20.0)));
+
+        let mut circles = Outline::from_circle(vec2f(0.0, 0.0), 10.0);
+        circles.append(Outline::from_circle(vec2f(50.0, 0.0), 10.0));
+        let length = circles.length();
+        let trimmed = circles.trim(0.25, 0.75);
+        assert_eq!(trimmed.contours().len(), 2);
+        for contour in trimmed.contours() {
+            assert!((contour.length() - 0.25 * length).abs() < 0.01);
+        }
+
+        assert!(rect.trim(0.5, 0.5).is_empty());
+        assert!(Outline::new().trim(0.0, 1.0).is_empty());
+        assert!((rect.trim(0.0, 1.0).length() - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_weld_contours() {
+        let mut top = Contour::new();
+        top.push_arc(&Transform2F::from_scale(10.0), 0.0, PI, ArcDirection::CW);
+        let mut bottom = Contour::new();
+        bottom.push_arc(&Transform2F::from_scale(10.0), PI, 2.0 * PI, ArcDirection::CW);
+
+        // Nudge the ends apart, and run the second arc backward.
+        bottom.transform(&Transform2F::from_translation(vec2f(0.0, 0.01)));
+        bottom.reverse();
+        let mut outline = Outline::new();
+        outline.push_contour(top.clone());
+        outline.push_contour(bottom);
+        let square = Outline::from_rect(RectF::new(vec2f(50.0, 50.0), vec2f(1.0, 1.0)));
+        outline.append(square.clone());
+
+        outline.weld_contours(0.001);
+        assert_eq!(outline.contours().len(), 3);
+        outline.weld_contours(0.05);
+        assert_eq!(outline.contours().len(), 2);
+        let circle = &outline.contours()[0];
+        assert!(circle.is_closed());
+        assert!((circle.area().abs() - PI * 100.0).abs() < 1.0);
+        assert_eq!(circle.position_of(0), top.position_of(0));
+        assert_eq!(outline.contours()[1], square.contours()[0]);
+        assert_eq!(outline.bounds().max_x(), 51.0);
+    }
+
+    #[test]
+    fn test_debug_wireframe() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(0.0, 10.0), vec2f(20.0, 10.0), vec2f(20.0, 0.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let (edges, handles) = outline.debug_wireframe();
+        assert_eq!(handles, vec![
+            LineSegment2F::new(vec2f(0.0, 0.0), vec2f(0.0, 10.0)),
+            LineSegment2F::new(vec2f(20.0, 10.0), vec2f(20.0, 0.0)),
+        ]);
+        assert!(edges.len() > 2);
+        assert_eq!(edges[0].from(), vec2f(0.0, 0.0));
+        assert_eq!(edges[edges.len() - 1], LineSegment2F::new(vec2f(20.0, 0.0), vec2f(0.0, 0.0)));
+        for pair in edges.windows(2) {
+            assert_eq!(pair[0].to(), pair[1].from());
+        }
+
+        let (edges, handles) = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)))
+            .debug_wireframe();
+        assert_eq!((edges.len(), handles.len()), (4, 0));
+    }
+
+    #[test]
+    fn test_to_geojson_polygon() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        outline.append(Outline::from_rect(RectF::new(vec2f(3.0, 3.0), vec2f(4.0, 4.0))));
+        assert_eq!(outline.to_geojson_polygon(0.1),
+                   "{\"type\":\"Polygon\",\"coordinates\":[\
+                    [[0,0],[10,0],[10,10],[0,10],[0,0]],\
+                    [[3,3],[3,7],[7,7],[7,3],[3,3]]]}");
+
+        // A second square with an island inside its hole makes a multipolygon.
+        let mut outline = outline.clone();
+        outline.append(Outline::from_rect(RectF::new(vec2f(20.0, 0.0), vec2f(1.0, 1.0))));
+        outline.append(Outline::from_rect(RectF::new(vec2f(4.0, 4.0), vec2f(2.0, 2.0))));
+        let json = outline.to_geojson_polygon(0.1);
+        assert!(json.starts_with("{\"type\":\"MultiPolygon\",\"coordinates\":[[[[0,0],"));
+        assert_eq!(json.matches("]]]").count(), 3);
+        assert_eq!(json.matches("[[").count(), 4 + 1);
+
+        let circle = Outline::from_circle(vec2f(0.0, 0.0), 10.0).to_geojson_polygon(0.01);
+        assert!(circle.starts_with("{\"type\":\"Polygon\",\"coordinates\":[[[10,0],"));
+        assert!(circle.ends_with(",[10,0]]]}"));
+        assert_eq!(Outline::new().to_geojson_polygon(0.1),
+                   "{\"type\":\"MultiPolygon\",\"coordinates\":[]}");
+
+        // Infinite points are skipped.
+        let mut square = Contour::new();
+        square.push_endpoint(vec2f(0.0, 0.0));
+        square.push_endpoint(vec2f(10.0, 0.0));
+        square.push_endpoint(vec2f(f32::INFINITY, 5.0));
+        square.push_endpoint(vec2f(10.0, 10.0));
+        square.push_endpoint(vec2f(0.0, 10.0));
+        square.close();
+        let mut outline = Outline::new();
+        outline.push_contour(square);
+        assert_eq!(outline.to_geojson_polygon(0.1),
+                   "{\"type\":\"Polygon\",\"coordinates\":[[[0,0],[10,0],[10,10],[0,10],[0,0]]]}");
+    }
+
+    #[test]
+    fn test_to_cubics() {
+        let mut outline = Outline::new();
+        outline.push_contour(line_quadratic_cubic_contour());
+        let mut closed = line_quadratic_cubic_contour();
+        closed.close();
+        outline.push_contour(closed);
+
+        let cubics = outline.to_cubics();
+        assert!(outline.bounds().contains_rect(cubics.bounds()));
+        for (contour, original) in cubics.contours().iter().zip(outline.contours()) {
+            assert_eq!(contour.is_closed(), original.is_closed());
+            let segments: Vec<_> = contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).collect();
+            assert!(segments.iter().all(|segment| segment.is_cubic()));
+            let original_segments: Vec<_> = original.iter(ContourIterFlags::empty()).collect();
+            assert_eq!(segments.len(), original_segments.len());
+            for (segment, original_segment) in segments.iter().zip(&original_segments) {
+                for step in 0..=10 {
+                    let t = step as f32 / 10.0;
+                    assert!(segment.sample(t).approx_eq(original_segment.sample(t), 0.0001));
+                }
+            }
+        }
+        let closing_segment = cubics.contours()[1].iter(ContourIterFlags::empty()).last();
+        assert_eq!(closing_segment.unwrap().baseline.vector(), Vector2F::zero());
+
+        // `to_cubic()` elevates the same curves but keeps the line.
+        let kinds: Vec<_> = outline.to_cubic().contours()[0]
+                                   .iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                                   .map(|segment| segment.kind)
+                                   .collect();
+        assert_eq!(kinds, vec![SegmentKind::Line, SegmentKind::Cubic, SegmentKind::Cubic]);
+    }
+
+    #[test]
+    fn test_subdivide_to_max_length() {
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+        let subdivided = line.subdivide_to_max_length(3.0);
+        assert_eq!(subdivided.points(),
+                   &[vec2f(0.0, 0.0), vec2f(2.5, 0.0), vec2f(5.0, 0.0), vec2f(7.5, 0.0),
+                     vec2f(10.0, 0.0)]);
+
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        outline.push_contour(line_quadratic_cubic_contour());
+        let subdivided = outline.subdivide_to_max_length(4.0);
+        let square = &subdivided.contours()[0];
+        assert!(square.is_closed());
+        assert_eq!(square.segment_count(), 16);
+        let curves = &subdivided.contours()[1];
+        let original = &outline.contours()[1];
+        assert!((curves.length() - original.length()).abs() < 0.01);
+        for segment in curves.iter(ContourIterFlags::empty()) {
+            assert!(segment.arc_length() <= 4.0);
+            let nearest = original.iter(ContourIterFlags::empty()).map(|original_segment| {
+                let point = segment.sample(0.5);
+                (original_segment.sample(original_segment.nearest_t(point)) - point).length()
+            }).fold(f32::INFINITY, f32::min);
+            assert!(nearest < 0.001);
+        }
+        assert!(curves.iter(ContourIterFlags::empty()).any(|segment| segment.is_quadratic()));
+        assert!(curves.iter(ContourIterFlags::empty()).any(|segment| segment.is_cubic()));
+        assert_eq!(outline.subdivide_to_max_length(0.0), outline);
+    }
+
+    #[test]
+    fn test_total_turning() {
+        let mut hexagon = Contour::new();
+        for vertex_index in 0..6 {
+            let angle = vertex_index as f32 * PI / 3.0;
+            hexagon.push_endpoint(vec2f(angle.cos(), angle.sin()));
+        }
+        hexagon.close();
+        assert!((hexagon.total_turning() - 2.0 * PI).abs() < EPSILON);
+        hexagon.reverse();
+        assert!((hexagon.total_turning() + 2.0 * PI).abs() < EPSILON);
+
+        let circle = Contour::from_ellipse(vec2f(0.0, 0.0), vec2f(3.0, 2.0));
+        assert!((circle.total_turning().abs() - 2.0 * PI).abs() < 0.001);
+        assert_eq!(circle.total_turning() > 0.0, circle.area() > 0.0);
+
+        let mut figure_eight = Contour::new();
+        figure_eight.push_endpoint(vec2f(0.0, 0.0));
+        figure_eight.push_endpoint(vec2f(2.0, 2.0));
+        figure_eight.push_endpoint(vec2f(2.0, 0.0));
+        figure_eight.push_endpoint(vec2f(0.0, 2.0));
+        figure_eight.close();
+        assert!(figure_eight.total_turning().abs() < EPSILON);
+
+        let mut corner = Contour::new();
+        corner.push_endpoint(vec2f(0.0, 0.0));
+        corner.push_endpoint(vec2f(1.0, 0.0));
+        corner.push_endpoint(vec2f(1.0, 1.0));
+        assert!((corner.total_turning() - PI * 0.5).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_make_monotonic_if_needed() {
+        let mut rect = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 2.0)));
+        let (original, points) = (rect.clone(), rect.points().as_ptr());
+        assert!(!rect.make_monotonic_if_needed());
+        assert_eq!(rect, original);
+        assert_eq!(rect.points().as_ptr(), points);
+
+        let mut arch = Contour::new();
+        arch.push_endpoint(vec2f(0.0, 0.0));
+        arch.push_quadratic(vec2f(1.0, 2.0), vec2f(2.0, 0.0));
+        assert!(arch.make_monotonic_if_needed());
+        assert_eq!(arch.segment_count(), 2);
+        assert!(arch.position_of(3).approx_eq(vec2f(1.0, 1.0), EPSILON));
+        assert!(!arch.make_monotonic_if_needed());
+
+        // Curves that double back only horizontally need no splitting.
+        let mut hook = Contour::new();
+        hook.push_endpoint(vec2f(0.0, 0.0));
+        hook.push_quadratic(vec2f(3.0, 1.0), vec2f(2.0, 2.0));
+        let original = hook.clone();
+        assert!(!hook.make_monotonic_if_needed());
+        assert_eq!(hook, original);
+    }
+
+    #[test]
+    fn test_offset_monotonic() {
+        let mut outline = Outline::from_circle(vec2f(0.0, 0.0), 5.0);
+        let mut zigzag = Contour::new();
+        zigzag.push_endpoint(vec2f(10.0, 0.0));
+        zigzag.push_endpoint(vec2f(15.0, 5.0));
+        zigzag.push_endpoint(vec2f(20.0, 0.0));
+        outline.push_contour(zigzag);
+
+        for &(line_cap, line_join) in &[(LineCap::Round, LineJoin::Round),
+                                       (LineCap::Square, LineJoin::Miter(10.0))] {
+            let style = StrokeStyle { line_width: 2.0, line_cap, line_join };
+            let mut stroker = OutlineStrokeToFill::new(&outline, style);
+            stroker.offset();
+            let stroked = stroker.into_outline();
+            let mut stroker = OutlineStrokeToFill::new(&outline, style);
+            stroker.offset_monotonic();
+            let monotonic = stroker.into_outline();
+
+            assert_eq!(monotonic, stroked.to_monotonic());
+            if line_cap == LineCap::Round {
+                // The round caps have vertical extrema, so they must have been split.
+                assert!(monotonic.segment_count() > stroked.segment_count());
+            }
+            assert_eq!(monotonic.bounds(), stroked.to_monotonic().bounds());
+            assert_eq!(monotonic.monotonic_segments().count() as u32, monotonic.segment_count());
+        }
+    }
+
+    #[test]
+    fn test_signed_distance() {
+        let circle = Outline::from_circle(vec2f(0.0, 0.0), 1.0);
+        assert!((circle.signed_distance(vec2f(0.0, 0.0)) + 1.0).abs() < 0.001);
+        assert!((circle.signed_distance(vec2f(0.5, 0.0)) + 0.5).abs() < 0.001);
+        assert!((circle.signed_distance(vec2f(0.0, 3.0)) - 2.0).abs() < 0.001);
+        for angle_index in 0..8 {
+            let angle = angle_index as f32 * PI / 4.0 + 0.1;
+            let on_boundary = vec2f(angle.cos(), angle.sin());
+            assert!(circle.signed_distance(on_boundary).abs() < 0.001);
+        }
+
+        // Holes count as outside.
+        let mut ring = Outline::from_circle(vec2f(0.0, 0.0), 2.0);
+        let mut hole = Outline::from_circle(vec2f(0.0, 0.0), 1.0);
+        hole.reverse();
+        ring.append(hole);
+        assert!((ring.signed_distance(vec2f(0.0, 0.0)) - 1.0).abs() < 0.001);
+        assert!((ring.signed_distance(vec2f(1.5, 0.0)) + 0.5).abs() < 0.001);
+
+        assert_eq!(Outline::new().signed_distance(vec2f(0.0, 0.0)), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_to_sdf() {
+        let (center, radius, spread) = (vec2f(16.0, 16.0), 10.0, 4.0);
+        let sdf = Outline::from_circle(center, radius).to_sdf(32, 32, spread);
+        assert_eq!(sdf.len(), 32 * 32);
+        for (pixel_index, &value) in sdf.iter().enumerate() {
+            let (x, y) = ((pixel_index % 32) as f32 + 0.5, (pixel_index / 32) as f32 + 0.5);
+            let distance = (vec2f(x, y) - center).length() - radius;
+            assert!((value - (distance / spread).clamp(-1.0, 1.0)).abs() < 0.001);
+        }
+
+        // Along the middle row, the field crosses zero on the circle.
+        let row = &sdf[(16 * 32)..(17 * 32)];
+        let x = row.windows(2).position(|pair| pair[0] > 0.0 && pair[1] <= 0.0).unwrap();
+        let t = row[x] / (row[x] - row[x + 1]);
+        let crossing = vec2f(x as f32 + 0.5 + t, 16.5);
+        assert!(((crossing - center).length() - radius).abs() < 0.01);
+        assert_eq!(sdf[0], 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive")]
+    fn test_to_sdf_rejects_nan_spread() {
+        Outline::from_circle(vec2f(4.0, 4.0), 2.0).to_sdf(8, 8, f32::NAN);
+    }
+
+    #[test]
+    fn test_control_handles() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(1.0, 0.0));
+        contour.push_cubic(vec2f(2.0, 0.0), vec2f(3.0, 1.0), vec2f(3.0, 2.0));
+        assert_eq!(contour.control_handles().collect::<Vec<_>>(), vec![
+            (2, vec2f(2.0, 0.0), 1),
+            (3, vec2f(3.0, 1.0), 4),
+        ]);
+
+        contour.push_quadratic(vec2f(3.0, 4.0), vec2f(1.0, 4.0));
+        contour.close();
+        assert_eq!(contour.control_handles().nth(2), Some((5, vec2f(3.0, 4.0), 4)));
+        assert_eq!(contour.control_handles().count(), 3);
+        assert_eq!(Contour::from_rect(RectF::default()).control_handles().count(), 0);
+    }
+
+    #[test]
+    fn test_set_position() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(2.0, 1.0), vec2f(4.0, 0.0));
+        contour.push_endpoint(vec2f(2.0, 2.0));
+        contour.close();
+
+        contour.set_position(1, vec2f(2.0, -3.0));
+        assert_eq!(contour.position_of(1), vec2f(2.0, -3.0));
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(0.0, -3.0), vec2f(4.0, 2.0)));
+        contour.set_position(1, vec2f(2.0, 1.0));
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(0.0, 0.0), vec2f(4.0, 2.0)));
+        contour.set_position(0, vec2f(1.0, 1.0));
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(1.0, 0.0), vec2f(4.0, 2.0)));
+        contour.set_position(3, vec2f(5.0, 6.0));
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(1.0, 0.0), vec2f(5.0, 6.0)));
+
+        // Points inside the bounds can move without shrinking them.
+        contour.set_position(1, vec2f(3.0, 2.0));
+        assert_eq!(contour.bounds(), RectF::from_points(vec2f(1.0, 0.0), vec2f(5.0, 6.0)));
+        assert!(!contour.point_is_endpoint(1));
+    }
+
+    #[test]
+    fn test_prepare_for_tiling_starts_with_endpoint() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_quadratic(vec2f(5.0, 10.0), vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(5.0, -5.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour.clone());
+
+        outline.prepare_for_tiling(RectF::new(vec2f(-100.0, -100.0), vec2f(200.0, 200.0)));
+        let prepared = &outline.contours()[0];
+        assert!(prepared.point_is_endpoint(0));
+        assert_eq!(prepared.position_of(0), vec2f(0.0, 0.0));
+        assert!(prepared.is_closed());
+        assert!(prepared.approx_eq(&contour.to_monotonic(), EPSILON));
+        assert_eq!(prepared.segment_count(), 4);
+    }
+
+    #[test]
+    fn test_sample_uniform() {
+        let mut line = Contour::new();
+        line.push_endpoint(vec2f(0.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 0.0));
+        line.push_endpoint(vec2f(10.0, 5.0));
+        let points = line.sample_uniform(2.5, 0.001);
+        let expected = [(0.0, 0.0), (2.5, 0.0), (5.0, 0.0), (7.5, 0.0), (10.0, 0.0), (10.0, 2.5),
+                        (10.0, 5.0)];
+        assert_eq!(points.len(), expected.len());
+        for (&point, &(x, y)) in points.iter().zip(expected.iter()) {
+            assert!((point - vec2f(x, y)).length() < 0.001);
+        }
+
+        assert_eq!(line.sample_uniform(100.0, 0.001), vec![vec2f(0.0, 0.0)]);
+        assert!(line.sample_uniform(0.0, 0.001).is_empty());
+        let mut point = Contour::new();
+        point.push_endpoint(vec2f(1.0, 1.0));
+        point.push_endpoint(vec2f(1.0, 1.0));
+        assert!(point.sample_uniform(1.0, 0.001).is_empty());
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(10.0));
+        circle.close();
+        let points = circle.sample_uniform(3.0, 0.001);
+        assert_eq!(points.len(), 21);
+        for pair in points.windows(2) {
+            let chord = (pair[1] - pair[0]).length();
+            assert!((chord - 20.0 * f32::sin(0.15)).abs() < 0.01);
+            assert!((pair[1].length() - 10.0).abs() < 0.01);
+        }
+
+        // The end of a closed contour is not repeated.
+        let square = Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 10.0)));
+        assert_eq!(square.sample_uniform(10.0, 0.001).len(), 4);
+
+        // Each point's distance along the curve stays within the tolerance.
+        let mut curve = Contour::new();
+        curve.push_endpoint(vec2f(0.0, 0.0));
+        curve.push_cubic(vec2f(0.0, 20.0), vec2f(30.0, 20.0), vec2f(30.0, 0.0));
+        let segment = curve.iter(ContourIterFlags::empty()).next().unwrap();
+        let length = segment.arc_length_with_tolerance(0.00001);
+        for &tolerance in &[0.1, 0.01, 0.001] {
+            let points = curve.sample_uniform(5.0, tolerance);
+            assert_eq!(points.len(), (length / 5.0) as usize + 1);
+            for (index, &point) in points.iter().enumerate() {
+                let prefix = segment.split(segment.nearest_t(point)).0;
+                let distance = prefix.arc_length_with_tolerance(0.00001);
+                assert!((distance - index as f32 * 5.0).abs() <= tolerance * 2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_svg_arc() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_svg_arc(vec2f(10.0, 10.0), 0.0, false, ArcDirection::CW, vec2f(0.0, 10.0));
+        assert_eq!(contour.position_of(0), vec2f(10.0, 0.0));
+        assert!(contour.iter(ContourIterFlags::empty()).all(|segment| segment.is_cubic()));
+        for segment in contour.iter(ContourIterFlags::empty()) {
+            for step in 0..=16 {
+                let radius = segment.sample(step as f32 / 16.0).length();
+                assert!((radius - 10.0).abs() < 0.01);
+            }
+        }
+        assert!((contour.last_position().unwrap() - vec2f(0.0, 10.0)).length() < 0.001);
+
+        // Opposite ends of the major axis of a rotated ellipse centered at the origin.
+        let mut contour = Contour::new();
+        let from = Transform2F::from_rotation(PI / 4.0) * vec2f(20.0, 0.0);
+        let to = -from;
+        contour.push_endpoint(from);
+        contour.push_svg_arc(vec2f(20.0, 5.0), PI / 4.0, false, ArcDirection::CCW, to);
+        let inverse = Transform2F::from_rotation(-PI / 4.0);
+        for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let point = inverse * segment.sample(0.5) / vec2f(20.0, 5.0);
+            assert!((point.length() - 1.0).abs() < 0.01);
+        }
+        assert!((contour.last_position().unwrap() - to).length() < 0.001);
+    }
+
+    #[test]
+    fn test_contour_builder() {
+        let mut builder = ContourBuilder::new();
+        assert_eq!(builder.current_position(), None);
+        builder.move_to(vec2f(0.0, 0.0));
+        builder.line_to(vec2f(10.0, 0.0));
+        builder.quadratic_to(vec2f(15.0, 5.0), vec2f(10.0, 10.0));
+        builder.cubic_to(vec2f(7.0, 12.0), vec2f(3.0, 12.0), vec2f(0.0, 10.0));
+        assert_eq!(builder.current_position(), Some(vec2f(0.0, 10.0)));
+        builder.close();
+        assert_eq!(builder.current_position(), Some(vec2f(0.0, 0.0)));
+
+        let contour = builder.build();
+        assert!(contour.is_closed());
+        assert_eq!(format!("{:?}", contour), "M 0 0 L 10 0 Q 15 5 10 10 C 7 12 3 12 0 10 z");
+        let kinds: Vec<_> = contour.iter(ContourIterFlags::empty()).map(|segment| {
+            segment.kind
+        }).collect();
+        assert_eq!(kinds, [SegmentKind::Line, SegmentKind::Quadratic, SegmentKind::Cubic,
+                           SegmentKind::Line]);
+
+        // Curves drawn on an empty builder start at their first control point.
+        let mut builder = ContourBuilder::new();
+        builder.quadratic_to(vec2f(1.0, 1.0), vec2f(2.0, 0.0));
+        assert_eq!(format!("{:?}", builder.build()), "M 1 1 Q 1 1 2 0");
+    }
+
+    #[test]
+    fn test_rotate_scale_translate() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        outline.rotate(PI * 0.5);
+        let bounds = outline.bounds();
+        assert!((bounds.origin() - vec2f(-1.0, 0.0)).length() < 0.0001);
+        assert!((bounds.size() - vec2f(1.0, 1.0)).length() < 0.0001);
+
+        outline.scale(vec2f(2.0, 3.0));
+        outline.translate(vec2f(2.0, 0.0));
+        let bounds = outline.bounds();
+        assert!((bounds.origin() - vec2f(0.0, 0.0)).length() < 0.0001);
+        assert!((bounds.size() - vec2f(2.0, 3.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_transform_about() {
+        let rect = RectF::new(vec2f(2.0, 3.0), vec2f(4.0, 2.0));
+        let mut outline = Outline::from_rect(rect);
+        outline.rotate_about(PI, rect.center());
+        assert!((outline.bounds().origin() - rect.origin()).length() < 0.0001);
+        assert!((outline.bounds().size() - rect.size()).length() < 0.0001);
+        assert!((outline.contours()[0].position_of(0) - rect.lower_right()).length() < 0.0001);
+
+        let mut outline = Outline::from_rect(rect);
+        outline.transform_about(&Transform2F::from_scale(2.0), rect.origin());
+        assert_eq!(outline.bounds(), RectF::new(rect.origin(), rect.size() * 2.0));
+    }
+
+    #[test]
+    fn test_transformed() {
+        let rect = RectF::new(vec2f(1.0, 1.0), vec2f(2.0, 3.0));
+        let mut outline = Outline::from_rect(rect);
+        outline.push_contour(line_quadratic_cubic_contour());
+        let transform = Transform2F::from_translation_rotation_scale(vec2f(3.0, -4.0), 1.0, 2.0);
+
+        let transformed = outline.transformed(&transform);
+        assert_eq!(outline.bounds(), rect.union_rect(line_quadratic_cubic_contour().bounds()));
+        let mut expected = outline.clone();
+        expected.transform(&transform);
+        assert_eq!(format!("{:?}", transformed), format!("{:?}", expected));
+        assert_eq!(transformed.bounds(), expected.bounds());
+        assert_eq!(transformed.contours()[1].bounds(), expected.contours()[1].bounds());
+        assert!(transformed.contours()[0].is_closed());
+    }
+
+    #[test]
+    fn test_transform_points() {
+        let transform = Transform2F::from_translation_rotation_scale(vec2f(3.0, -4.0),
+                                                                     PI / 3.0,
+                                                                     vec2f(2.0, 0.5));
+        let original: Vec<_> = (0..7).map(|index| {
+            let index = index as f32;
+            vec2f(index * 1.5 - 4.0, 10.0 - index * index)
+        }).collect();
+
+        let mut points = original.clone();
+        transform_points(&mut points, &transform);
+        assert_eq!(points.len(), original.len());
+        for (&point, &original_point) in points.iter().zip(original.iter()) {
+            let expected = transform * original_point;
+            assert!((point - expected).length() < EPSILON);
+        }
+    }
+
+    #[cfg(pf_rustc_nightly)]
+    #[bench]
+    fn bench_transform_points(bencher: &mut test::Bencher) {
+        let mut contour = Contour::with_capacity(10000);
+        for index in 0..10000 {
+            let angle = index as f32 * 0.01;
+            contour.push_endpoint(vec2f(angle.cos(), angle.sin()) * (100.0 + angle));
+        }
+        let transform = Transform2F::from_rotation(0.001);
+        bencher.iter(|| transform_points(&mut contour.points, &transform));
+    }
+
+    #[test]
+    fn test_make_monotonic_reuses_scratch() {
+        let mut contour = Contour::with_capacity(64);
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(0.0, 10.0), vec2f(10.0, 10.0), vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        let bounds = contour.bounds();
+
+        let mut scratch = Contour::new();
+        let mut first = contour.clone();
+        first.make_monotonic(&mut scratch);
+        assert_eq!(first.bounds(), bounds);
+        let endpoint_indices: Vec<u32> = (0..first.len()).filter(|&point_index| {
+            first.point_is_endpoint(point_index)
+        }).collect();
+        for window in endpoint_indices.windows(2) {
+            assert!(first.curve_with_endpoints_is_monotonic(window[0], window[1]));
+        }
+
+        // The input arrays move into the scratch contour, and the next conversion writes its
+        // output into them instead of allocating.
+        let mut second = contour.clone();
+        contour.make_monotonic(&mut scratch);
+        let points_ptr = scratch.points.as_ptr();
+        second.make_monotonic(&mut scratch);
+        assert_eq!(second.points, first.points);
+        assert_eq!(second.points.as_ptr(), points_ptr);
+    }
+
+    // A row of cubic arches, each of which is split at its top.
+    #[cfg(pf_rustc_nightly)]
+    fn arches_contour(arch_count: u32) -> Contour {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        for arch_index in 0..arch_count {
+            let x = arch_index as f32 * 10.0;
+            contour.push_cubic(vec2f(x, 10.0), vec2f(x + 10.0, 10.0), vec2f(x + 10.0, 0.0));
+        }
+        contour.close();
+        contour
+    }
+
+    // Restores `contour` to `original` without reallocating, so that only the allocations made by
+    // `make_monotonic()` itself are measured.
+    #[cfg(pf_rustc_nightly)]
+    fn bench_make_monotonic(bencher: &mut test::Bencher, reuse_scratch: bool) {
+        let original = arches_contour(1000);
+        let (mut contour, mut scratch) = (original.clone(), Contour::new());
+        bencher.iter(|| {
+            contour.points.clone_from(&original.points);
+            contour.flags.clone_from(&original.flags);
+            if reuse_scratch {
+                contour.make_monotonic(&mut scratch);
+            } else {
+                contour.make_monotonic(&mut Contour::new());
+            }
+        });
+    }
+
+    #[cfg(pf_rustc_nightly)]
+    #[bench]
+    fn bench_make_monotonic_reusing_scratch(bencher: &mut test::Bencher) {
+        bench_make_monotonic(bencher, true);
+    }
+
+    #[cfg(pf_rustc_nightly)]
+    #[bench]
+    fn bench_make_monotonic_with_new_scratch(bencher: &mut test::Bencher) {
+        bench_make_monotonic(bencher, false);
+    }
+
+    #[test]
+    fn test_flip() {
+        let mut triangle = Contour::new();
+        triangle.push_endpoint(vec2f(1.0, 0.0));
+        triangle.push_endpoint(vec2f(3.0, 0.0));
+        triangle.push_endpoint(vec2f(3.0, 2.0));
+        triangle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(triangle);
+        let area = outline.area();
+
+        let mut flipped = outline.clone();
+        flipped.flip_about_x(5.0);
+        assert_eq!(format!("{:?}", flipped), "M 9 0 L 7 0 L 7 2 z");
+        assert_eq!(flipped.bounds(), RectF::from_points(vec2f(7.0, 0.0), vec2f(9.0, 2.0)));
+        assert_eq!(flipped.area(), -area);
+        flipped.reverse();
+        assert_eq!(flipped.area(), area);
+
+        let mut flipped = outline.clone();
+        flipped.flip_y();
+        assert_eq!(format!("{:?}", flipped), "M 1 0 L 3 0 L 3 -2 z");
+        flipped.flip_about_y(1.0);
+        assert_eq!(format!("{:?}", flipped), "M 1 2 L 3 2 L 3 4 z");
+        flipped.flip_x();
+        assert_eq!(flipped.bounds(), RectF::from_points(vec2f(-3.0, 2.0), vec2f(-1.0, 4.0)));
+    }
+
+    #[test]
+    fn test_tight_bounds() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(-10.0, 10.0), vec2f(20.0, 10.0), vec2f(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let (bounds, tight_bounds) = (outline.bounds(), outline.tight_bounds());
+        assert_eq!(bounds, RectF::from_points(vec2f(-10.0, 0.0), vec2f(20.0, 10.0)));
+        assert!(bounds.contains_rect(tight_bounds) && bounds != tight_bounds);
+        assert!(tight_bounds.min_x() > -10.0 && tight_bounds.max_x() < 20.0);
+        assert!((tight_bounds.max_y() - 7.5).abs() < 0.001);
+        assert_eq!(tight_bounds.min_y(), 0.0);
+
+        let rect = RectF::new(vec2f(1.0, 2.0), vec2f(3.0, 4.0));
+        assert_eq!(Outline::from_rect(rect).tight_bounds(), rect);
+        assert_eq!(Outline::new().tight_bounds(), RectF::default());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_transform() {
+        let mut outline = Outline::new();
+        for index in 0..5000 {
+            let origin = vec2f((index % 100) as f32, (index / 100) as f32);
+            let mut contour = Contour::from_rect(RectF::new(origin, vec2f(0.5, 0.5)));
+            contour.push_quadratic(origin + vec2f(1.0, 1.0), origin);
+            outline.push_contour(contour);
+        }
+        outline.push_contour(Contour::new());
+
+        let transform = Transform2F::from_translation_rotation_scale(vec2f(-7.0, 11.0),
+                                                                     0.3,
+                                                                     vec2f(2.0, 3.0));
+        let mut serial = outline.clone();
+        serial.transform(&transform);
+        outline.par_transform(&transform);
+        assert_eq!(outline, serial);
+        assert_eq!(outline.bounds(), serial.bounds());
+
+        let mut empty = Outline::new();
+        empty.par_transform(&transform);
+        assert_eq!(empty.bounds(), RectF::default());
+    }
+
+    #[cfg(feature = "kurbo")]
+    #[test]
+    fn test_kurbo_round_trip() {
+        let mut path = kurbo::BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((10.0, 0.0));
+        path.curve_to((15.0, 0.0), (15.0, 10.0), (10.0, 10.0));
+        path.quad_to((5.0, 15.0), (0.0, 10.0));
+        path.close_path();
+        path.move_to((20.0, 0.0));
+        path.line_to((30.0, 5.0));
+
+        let outline = Outline::from(&path);
+        assert_eq!(outline.contours().len(), 2);
+        assert!(outline.contours()[0].is_closed());
+        assert!(!outline.contours()[1].is_closed());
+        assert_eq!(outline.contours()[0].len(), 7);
+        assert_eq!(outline.contours()[0].position_of(2), vec2f(15.0, 0.0));
+        assert_eq!(kurbo::BezPath::from(&outline), path);
+
+        // Drawing after a close continues from the start of the closed subpath.
+        let mut path = kurbo::BezPath::new();
+        path.move_to((1.0, 1.0));
+        path.line_to((2.0, 1.0));
+        path.close_path();
+        path.line_to((1.0, 2.0));
+        let outline = Outline::from(&path);
+        assert_eq!(outline.contours().len(), 2);
+        assert_eq!(outline.contours()[1].position_of(0), vec2f(1.0, 1.0));
+    }
+
+    #[cfg(feature = "lyon")]
+    #[test]
+    fn test_lyon_round_trip() {
+        use lyon_path::math::point;
+
+        let mut builder = lyon_path::Path::builder();
+        builder.begin(point(0.0, 0.0));
+        builder.line_to(point(10.0, 0.0));
+        builder.cubic_bezier_to(point(15.0, 0.0), point(15.0, 10.0), point(10.0, 10.0));
+        builder.quadratic_bezier_to(point(5.0, 15.0), point(0.0, 10.0));
+        builder.close();
+        builder.begin(point(20.0, 0.0));
+        builder.line_to(point(30.0, 5.0));
+        builder.end(false);
+        let path = builder.build();
+
+        let outline = Outline::from(&path);
+        assert_eq!(outline.contours().len(), 2);
+        assert!(outline.contours()[0].is_closed());
+        assert!(!outline.contours()[1].is_closed());
+        assert_eq!(outline.contours()[0].len(), 7);
+
+        let events: Vec<_> = outline.iter_lyon_events().collect();
+        let expected: Vec<_> = path.iter().collect();
+        assert_eq!(events, expected);
+        assert_eq!(Outline::from(path), outline);
+    }
+
+    #[cfg(feature = "ttf-parser")]
+    #[test]
+    fn test_from_ttf_glyph() {
+        let data = include_bytes!("../../resources/fonts/Roboto-Regular.ttf");
+        let font = ttf_parser::Font::from_data(data, 0).unwrap();
+        for &(character, contour_count) in &[('O', 2), ('8', 3)] {
+            let glyph_id = font.glyph_index(character).unwrap();
+            let outline = Outline::from_ttf_glyph(&font, glyph_id).unwrap();
+            assert_eq!(outline.contours().len(), contour_count);
+            assert!(outline.contours().iter().all(|contour| contour.is_closed()));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut closed = line_quadratic_cubic_contour();
+        closed.close();
+        let mut open = Contour::new();
+        open.push_endpoint(vec2f(-5.0, 20.0));
+        open.push_cubic(vec2f(-5.0, 30.0), vec2f(5.0, 30.0), vec2f(5.0, 20.0));
+        let mut outline = Outline::new();
+        outline.push_contour(closed);
+        outline.push_contour(open);
+
+        let json = serde_json::to_string(&outline).unwrap();
+        assert!(!json.contains("bounds"));
+        let result: Outline = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", result), format!("{:?}", outline));
+        assert_eq!(result.bounds(), outline.bounds());
+        assert_eq!(result.contours().len(), 2);
+        for (result_contour, contour) in result.contours().iter().zip(outline.contours()) {
+            assert_eq!(result_contour.points, contour.points);
+            assert_eq!(result_contour.flags, contour.flags);
+            assert_eq!(result_contour.is_closed(), contour.is_closed());
+            assert_eq!(result_contour.bounds(), contour.bounds());
+        }
+
+        let segment = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 0.0)),
+                                     LineSegment2F::new(vec2f(0.0, 1.0), vec2f(1.0, 1.0)));
+        let json = serde_json::to_string(&segment).unwrap();
+        assert_eq!(serde_json::from_str::<Segment>(&json).unwrap(), segment);
+
+        let mismatched = r#"{"contours":[{"points":[[0,0],[1,1]],"flags":[0],"closed":true}]}"#;
+        assert!(serde_json::from_str::<Outline>(mismatched).is_err());
+        // Unknown bits, both bits at once, a second control point without a first, two first
+        // control points or three control points in a row, and a contour that starts with a
+        // control point are all rejected.
+        for flags in &["[0,4,0,0,0]", "[0,3,0,0,0]", "[0,2,0,0,0]", "[0,0,1,1,0]", "[0,1,2,2,0]",
+                       "[1,0,0,0,0]"] {
+            let points = "[[0,0],[1,1],[2,0],[3,1],[4,0]]";
+            let json = format!(r#"{{"points":{},"flags":{},"closed":false}}"#, points, flags);
+            assert!(serde_json::from_str::<Contour>(&json).is_err(), "{}", flags);
+        }
+        let valid = r#"{"points":[[0,0],[1,1],[2,0],[3,1]],"flags":[0,1,2,0],"closed":false}"#;
+        assert_eq!(serde_json::from_str::<Contour>(valid).unwrap().segment_count(), 1);
     }
 }
@@ -0,0 +1,539 @@
+// pathfinder/content/src/path_data.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing and writing of SVG path data (the `d` attribute of `<path>` elements).
+
+use crate::outline::{ArcDirection, Contour, ContourIterFlags, Outline};
+use crate::segment::SegmentKind;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+/// An error encountered while parsing SVG path data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathParseError {
+    /// The byte offset into the path data at which the error occurred.
+    pub offset: usize,
+    pub kind: PathParseErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathParseErrorKind {
+    /// A character that does not begin a command or a number.
+    UnexpectedCharacter,
+    /// A command was missing one of its numeric arguments.
+    ExpectedNumber,
+    /// An arc command was missing one of its flags, which must be `0` or `1`.
+    ExpectedFlag,
+    /// The path data did not begin with a moveto command.
+    ExpectedMoveTo,
+}
+
+impl Display for PathParseError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let message = match self.kind {
+            PathParseErrorKind::UnexpectedCharacter => "unexpected character",
+            PathParseErrorKind::ExpectedNumber => "expected a number",
+            PathParseErrorKind::ExpectedFlag => "expected a flag",
+            PathParseErrorKind::ExpectedMoveTo => "path data must begin with a moveto",
+        };
+        write!(formatter, "{} at byte {}", message, self.offset)
+    }
+}
+
+impl Error for PathParseError {}
+
+pub(crate) struct PathDataParser<'a> {
+    data: &'a [u8],
+    offset: usize,
+    outline: Outline,
+    contour: Contour,
+    current_position: Vector2F,
+    subpath_start: Vector2F,
+    // The control point to reflect for a subsequent smooth curve command, if the previous command
+    // was a curve of the same order.
+    last_cubic_ctrl: Option<Vector2F>,
+    last_quadratic_ctrl: Option<Vector2F>,
+}
+
+impl<'a> PathDataParser<'a> {
+    #[inline]
+    pub(crate) fn new(data: &'a str) -> PathDataParser<'a> {
+        PathDataParser {
+            data: data.as_bytes(),
+            offset: 0,
+            outline: Outline::new(),
+            contour: Contour::new(),
+            current_position: Vector2F::zero(),
+            subpath_start: Vector2F::zero(),
+            last_cubic_ctrl: None,
+            last_quadratic_ctrl: None,
+        }
+    }
+
+    pub(crate) fn parse(mut self) -> Result<Outline, PathParseError> {
+        let mut command = None;
+        loop {
+            self.skip_separators();
+            let byte = match self.data.get(self.offset) {
+                None => break,
+                Some(&byte) => byte,
+            };
+
+            if byte.is_ascii_alphabetic() {
+                if command.is_none() && byte != b'M' && byte != b'm' {
+                    return Err(self.error(PathParseErrorKind::ExpectedMoveTo));
+                }
+                command = Some(byte);
+                self.offset += 1;
+            } else if command.is_none() {
+                return Err(self.error(PathParseErrorKind::ExpectedMoveTo));
+            } else if !begins_number(byte) {
+                return Err(self.error(PathParseErrorKind::UnexpectedCharacter));
+            } else if command == Some(b'Z') || command == Some(b'z') {
+                // Numbers can't follow a closepath command.
+                return Err(self.error(PathParseErrorKind::UnexpectedCharacter));
+            }
+
+            // Implicit moveto repetitions are treated as lineto commands.
+            let this_command = command.unwrap();
+            self.parse_command(this_command)?;
+            command = match this_command {
+                b'M' => Some(b'L'),
+                b'm' => Some(b'l'),
+                _ => command,
+            };
+        }
+
+        self.flush_contour();
+        Ok(self.outline)
+    }
+
+    fn parse_command(&mut self, command: u8) -> Result<(), PathParseError> {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { self.current_position } else { Vector2F::zero() };
+        let (mut last_cubic_ctrl, mut last_quadratic_ctrl) = (None, None);
+
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                let to = origin + self.parse_point()?;
+                self.flush_contour();
+                self.contour.push_endpoint(to);
+                self.subpath_start = to;
+                self.current_position = to;
+            }
+            b'L' => {
+                let to = origin + self.parse_point()?;
+                self.line_to(to);
+            }
+            b'H' => {
+                let x = self.parse_number()?;
+                let to = vec2f(origin.x() + x, self.current_position.y());
+                self.line_to(to);
+            }
+            b'V' => {
+                let y = self.parse_number()?;
+                let to = vec2f(self.current_position.x(), origin.y() + y);
+                self.line_to(to);
+            }
+            b'C' => {
+                let ctrl0 = origin + self.parse_point()?;
+                let ctrl1 = origin + self.parse_point()?;
+                let to = origin + self.parse_point()?;
+                self.cubic_to(ctrl0, ctrl1, to);
+                last_cubic_ctrl = Some(ctrl1);
+            }
+            b'S' => {
+                let ctrl0 = self.reflect(self.last_cubic_ctrl);
+                let ctrl1 = origin + self.parse_point()?;
+                let to = origin + self.parse_point()?;
+                self.cubic_to(ctrl0, ctrl1, to);
+                last_cubic_ctrl = Some(ctrl1);
+            }
+            b'Q' => {
+                let ctrl = origin + self.parse_point()?;
+                let to = origin + self.parse_point()?;
+                self.quadratic_to(ctrl, to);
+                last_quadratic_ctrl = Some(ctrl);
+            }
+            b'T' => {
+                let ctrl = self.reflect(self.last_quadratic_ctrl);
+                let to = origin + self.parse_point()?;
+                self.quadratic_to(ctrl, to);
+                last_quadratic_ctrl = Some(ctrl);
+            }
+            b'A' => {
+                let radii = self.parse_point()?;
+                let x_axis_rotation = self.parse_number()?.to_radians();
+                let large_arc = self.parse_flag()?;
+                let direction = if self.parse_flag()? {
+                    ArcDirection::CW
+                } else {
+                    ArcDirection::CCW
+                };
+                let to = origin + self.parse_point()?;
+                self.begin_contour_if_necessary();
+                self.contour.push_svg_arc(radii, x_axis_rotation, large_arc, direction, to);
+                self.current_position = to;
+            }
+            b'Z' => {
+                if !self.contour.is_empty() {
+                    self.contour.close();
+                    self.flush_contour();
+                }
+                self.current_position = self.subpath_start;
+            }
+            _ => {
+                self.offset -= 1;
+                return Err(self.error(PathParseErrorKind::UnexpectedCharacter));
+            }
+        }
+
+        self.last_cubic_ctrl = last_cubic_ctrl;
+        self.last_quadratic_ctrl = last_quadratic_ctrl;
+        Ok(())
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.begin_contour_if_necessary();
+        self.contour.push_endpoint(to);
+        self.current_position = to;
+    }
+
+    fn quadratic_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.begin_contour_if_necessary();
+        self.contour.push_quadratic(ctrl, to);
+        self.current_position = to;
+    }
+
+    fn cubic_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        self.begin_contour_if_necessary();
+        self.contour.push_cubic(ctrl0, ctrl1, to);
+        self.current_position = to;
+    }
+
+    // A drawing command after a closepath begins a new subpath at the old subpath's start.
+    fn begin_contour_if_necessary(&mut self) {
+        if self.contour.is_empty() {
+            self.contour.push_endpoint(self.subpath_start);
+        }
+    }
+
+    fn flush_contour(&mut self) {
+        let contour = mem::replace(&mut self.contour, Contour::new());
+        self.outline.push_contour(contour);
+    }
+
+    // Reflects the previous control point about the current position, per the rules for the
+    // smooth curve commands. Without a previous control point, the current position is used.
+    fn reflect(&self, ctrl: Option<Vector2F>) -> Vector2F {
+        match ctrl {
+            None => self.current_position,
+            Some(ctrl) => self.current_position * 2.0 - ctrl,
+        }
+    }
+
+    fn parse_point(&mut self) -> Result<Vector2F, PathParseError> {
+        let x = self.parse_number()?;
+        let y = self.parse_number()?;
+        Ok(vec2f(x, y))
+    }
+
+    fn parse_number(&mut self) -> Result<f32, PathParseError> {
+        self.skip_separators();
+        let start = self.offset;
+
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.offset += 1;
+        }
+        let mut digit_count = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.offset += 1;
+            digit_count += self.skip_digits();
+        }
+        if digit_count == 0 {
+            self.offset = start;
+            return Err(self.error(PathParseErrorKind::ExpectedNumber));
+        }
+
+        // Only consume an exponent if it's well-formed, so that e.g. `1e` is `1` followed by an
+        // unexpected character.
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.offset;
+            self.offset += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.offset += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.offset = mantissa_end;
+            }
+        }
+
+        // The slice is ASCII, so this can't fail.
+        let string = std::str::from_utf8(&self.data[start..self.offset]).unwrap();
+        string.parse().map_err(|_| {
+            PathParseError { offset: start, kind: PathParseErrorKind::ExpectedNumber }
+        })
+    }
+
+    // Flags may be written without separators, as in `a1 1 0 00 1 1`.
+    fn parse_flag(&mut self) -> Result<bool, PathParseError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error(PathParseErrorKind::ExpectedFlag)),
+        };
+        self.offset += 1;
+        Ok(flag)
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.offset;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+        self.offset - start
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b'\x0c') |
+                Some(b',') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.offset).cloned()
+    }
+
+    #[inline]
+    fn error(&self, kind: PathParseErrorKind) -> PathParseError {
+        PathParseError { offset: self.offset, kind }
+    }
+}
+
+// Writes compact path data. Each coordinate is rounded as it's written, and relative coordinates
+// are measured from the position that a parser will arrive at after reading the rounded values,
+// so rounding errors don't build up along a contour.
+pub(crate) struct PathDataWriter {
+    data: String,
+    precision: usize,
+    relative: bool,
+    // The command that a parser would assume if the next numbers came without a command letter.
+    implicit_command: Option<u8>,
+    current_position: Vector2F,
+    subpath_start: Vector2F,
+}
+
+impl PathDataWriter {
+    #[inline]
+    pub(crate) fn new(precision: usize, relative: bool) -> PathDataWriter {
+        PathDataWriter {
+            data: String::new(),
+            precision,
+            relative,
+            implicit_command: None,
+            current_position: Vector2F::zero(),
+            subpath_start: Vector2F::zero(),
+        }
+    }
+
+    pub(crate) fn write(mut self, outline: &Outline) -> String {
+        for contour in outline.contours() {
+            let first_position = match contour.points().first() {
+                None => continue,
+                Some(&first_position) => first_position,
+            };
+            self.write_command(b'M', &[first_position]);
+            self.subpath_start = self.current_position;
+
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                match segment.kind {
+                    SegmentKind::None => {}
+                    SegmentKind::Line => self.write_command(b'L', &[segment.baseline.to()]),
+                    SegmentKind::Quadratic => {
+                        self.write_command(b'Q', &[segment.ctrl.from(), segment.baseline.to()]);
+                    }
+                    SegmentKind::Cubic => {
+                        self.write_command(b'C', &[
+                            segment.ctrl.from(),
+                            segment.ctrl.to(),
+                            segment.baseline.to(),
+                        ]);
+                    }
+                }
+            }
+
+            if contour.is_closed() {
+                self.data.push(if self.relative { 'z' } else { 'Z' });
+                self.implicit_command = None;
+                self.current_position = self.subpath_start;
+            }
+        }
+        self.data
+    }
+
+    // Writes a command whose points are all relative to the position before it, as every
+    // command used here is. The last point becomes the new current position.
+    fn write_command(&mut self, command: u8, points: &[Vector2F]) {
+        let command = if self.relative { command.to_ascii_lowercase() } else { command };
+        if self.implicit_command != Some(command) {
+            self.data.push(command as char);
+        }
+        self.implicit_command = Some(match command {
+            b'M' => b'L',
+            b'm' => b'l',
+            _ => command,
+        });
+
+        let origin = if self.relative { self.current_position } else { Vector2F::zero() };
+        let mut new_position = self.current_position;
+        for &point in points {
+            let point = point - origin;
+            let x = self.write_number(point.x());
+            let y = self.write_number(point.y());
+            new_position = origin + vec2f(x, y);
+        }
+        self.current_position = new_position;
+    }
+
+    // Writes a number with trailing zeros trimmed, and returns the value that a parser will read.
+    fn write_number(&mut self, value: f32) -> f32 {
+        let mut string = format!("{:.*}", self.precision, value);
+        if string.contains('.') {
+            let trimmed_length = string.trim_end_matches('0').trim_end_matches('.').len();
+            string.truncate(trimmed_length);
+        }
+        if string == "-0" {
+            string = "0".to_owned();
+        }
+
+        // A minus sign separates numbers by itself.
+        let needs_separator = match self.data.as_bytes().last() {
+            None => false,
+            Some(byte) => !byte.is_ascii_alphabetic() && !string.starts_with('-'),
+        };
+        if needs_separator {
+            self.data.push(' ');
+        }
+        self.data.push_str(&string);
+        string.parse().unwrap()
+    }
+}
+
+#[inline]
+fn begins_number(byte: u8) -> bool {
+    matches!(byte, b'0'..=b'9' | b'+' | b'-' | b'.')
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::Outline;
+    use crate::path_data::{PathParseError, PathParseErrorKind};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+
+    fn parse(data: &str) -> String {
+        format!("{:?}", Outline::from_svg_path_data(data).unwrap())
+    }
+
+    #[test]
+    fn test_write_path_data() {
+        let square = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(4.0, 4.0)));
+        assert_eq!(square.to_svg_path_data(2, false), "M0 0 4 0 4 4 0 4Z");
+        assert_eq!(square.to_svg_path_data(2, true), "m0 0 4 0 0 4-4 0z");
+
+        let data = "M 0.125 -2 Q 3 3 6 0 C 7 -1 9.5 -1.25 10 0 L 12 1 \
+                    M 20 20 L 30 20 L 25 28.66 Z M 1.0001 2 L 3 4";
+        let outline = Outline::from_svg_path_data(data).unwrap();
+        assert_eq!(outline.to_svg_path_data(1, false),
+                   "M0.1-2Q3 3 6 0C7-1 9.5-1.2 10 0L12 1M20 20 30 20 25 28.7ZM1 2 3 4");
+        for &relative in &[false, true] {
+            for &precision in &[0, 2, 4] {
+                let written = outline.to_svg_path_data(precision, relative);
+                let reparsed = Outline::from_svg_path_data(&written).unwrap();
+                let tolerance = 0.5 * 10.0f32.powi(-(precision as i32)) + 0.00001;
+                assert!(reparsed.approx_eq(&outline, tolerance), "{}", written);
+                assert!(reparsed.contours()[1].is_closed());
+                assert!(!reparsed.contours()[2].is_closed());
+            }
+        }
+        assert_eq!(Outline::new().to_svg_path_data(2, true), "");
+    }
+
+    #[test]
+    fn test_parse_absolute_and_relative() {
+        assert_eq!(parse("M 1 2 L 3 4 H 10 V 0 Z"), "M 1 2 L 3 4 L 10 4 L 10 0 z");
+        assert_eq!(parse("m1,2 l2,2 h7 v-4 z"), "M 1 2 L 3 4 L 10 4 L 10 0 z");
+        assert_eq!(parse("M0 0 1 1 2 0"), "M 0 0 L 1 1 L 2 0");
+        assert_eq!(parse("m1 1 1 1"), "M 1 1 L 2 2");
+        assert_eq!(parse("M0 0L1-1.5.5.5"), "M 0 0 L 1 -1.5 L 0.5 0.5");
+        assert_eq!(parse("M 0 0 L 1 0 Z L 1 1"), "M 0 0 L 1 0 z M 0 0 L 1 1");
+        assert_eq!(parse(""), "");
+    }
+
+    #[test]
+    fn test_parse_smooth_curves() {
+        assert_eq!(parse("M 0 0 C 1 1 2 1 3 0 S 5 -1 6 0"),
+                   "M 0 0 C 1 1 2 1 3 0 C 4 -1 5 -1 6 0");
+        assert_eq!(parse("M 0 0 Q 1 1 2 0 T 4 0"), "M 0 0 Q 1 1 2 0 Q 3 -1 4 0");
+        assert_eq!(parse("M 0 0 q 1 1 2 0 t 2 0 t 2 0"), "M 0 0 Q 1 1 2 0 Q 3 -1 4 0 Q 5 1 6 0");
+        // Without a preceding curve, the first control point is the current point.
+        assert_eq!(parse("M 0 0 L 1 0 S 2 1 3 0"), "M 0 0 L 1 0 C 1 0 2 1 3 0");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Outline::from_svg_path_data("L 1 1").unwrap_err(),
+                   PathParseError { offset: 0, kind: PathParseErrorKind::ExpectedMoveTo });
+        assert_eq!(Outline::from_svg_path_data("M 1 1 L 2").unwrap_err(),
+                   PathParseError { offset: 9, kind: PathParseErrorKind::ExpectedNumber });
+        assert_eq!(Outline::from_svg_path_data("M 1 1 X 2 2").unwrap_err(),
+                   PathParseError { offset: 6, kind: PathParseErrorKind::UnexpectedCharacter });
+        assert_eq!(Outline::from_svg_path_data("M 0 0 A 1 1 0 2 0 1 1").unwrap_err(),
+                   PathParseError { offset: 14, kind: PathParseErrorKind::ExpectedFlag });
+    }
+
+    #[test]
+    fn test_parse_arcs() {
+        // A semicircle of radius 5, swept clockwise on screen through (5, -5).
+        let outline = Outline::from_svg_path_data("M 0 0 A 5 5 0 0 1 10 0").unwrap();
+        let contour = &outline.contours()[0];
+        assert_eq!(contour.position_of(0), vec2f(0.0, 0.0));
+        assert!((contour.last_position().unwrap() - vec2f(10.0, 0.0)).length() < 0.001);
+        assert!((outline.bounds().min_y() + 5.0).abs() < 0.01);
+        assert!(outline.bounds().max_y().abs() < 0.001);
+
+        // Flags without separators, and the large arc on the other side.
+        let outline = Outline::from_svg_path_data("M0 0a5 5 0 10 10 0").unwrap();
+        assert!((outline.bounds().max_y() - 5.0).abs() < 0.01);
+        assert!(outline.bounds().min_y().abs() < 0.001);
+
+        // Radii that are too small are scaled up, and zero radii produce lines.
+        let outline = Outline::from_svg_path_data("M 0 0 A 1 1 0 0 1 10 0").unwrap();
+        assert!((outline.bounds().min_y() + 5.0).abs() < 0.01);
+        assert_eq!(parse("M 0 0 A 0 5 0 0 1 10 0"), "M 0 0 L 10 0");
+    }
+
+    #[test]
+    fn test_round_trip_debug_output() {
+        let data = "M 0 0 L 10 0 Q 15 5 10 10 C 7 12 3 12 0 10 z M 20 20 L 30 20";
+        let outline = Outline::from_svg_path_data(data).unwrap();
+        assert_eq!(format!("{:?}", outline), data);
+        let reparsed = Outline::from_svg_path_data(&format!("{:?}", outline)).unwrap();
+        assert_eq!(format!("{:?}", reparsed), data);
+        assert_eq!(reparsed.contours().len(), 2);
+        assert!(reparsed.contours()[0].is_closed());
+        assert!(!reparsed.contours()[1].is_closed());
+    }
+}
@@ -0,0 +1,144 @@
+// pathfinder/content/src/rasterize.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A simple CPU rasterizer that computes antialiased coverage, for reference and testing.
+
+use crate::fill::FillRule;
+use crate::outline::{self, Outline, MAX_FLATTENING_SUBDIVISIONS};
+use crate::segment::{Segment, SegmentKind};
+use pathfinder_geometry::line_segment::LineSegment2F;
+
+// How far, in pixels, the lines that curves are flattened into may stray from them.
+const FLATTENING_TOLERANCE: f32 = 0.01;
+
+// Computes the coverage of each pixel by accumulating the signed area that each edge sweeps to its
+// right, in the manner of font-rs. Each row is summed from left to right afterward to find the
+// winding number, weighted by area, at every pixel.
+//
+// Curves are split at their vertical extrema and then flattened. Edges are clamped horizontally
+// to the image, which keeps the area they cover to the right in each row.
+pub(crate) fn rasterize_coverage(outline: &Outline, width: u32, height: u32, fill_rule: FillRule)
+                                 -> Vec<f32> {
+    let mut rasterizer = CoverageRasterizer::new(width as usize, height as usize);
+    for contour in outline.contours() {
+        for segment in contour.fill_segments() {
+            match segment.kind {
+                SegmentKind::None => {}
+                SegmentKind::Line => rasterizer.draw_line(segment.baseline),
+                SegmentKind::Quadratic | SegmentKind::Cubic => {
+                    for piece in outline::split_cubic_at_y_extrema(&segment.to_cubic()) {
+                        rasterizer.draw_monotonic_cubic(&piece, 0);
+                    }
+                }
+            }
+        }
+    }
+    rasterizer.coverage(fill_rule)
+}
+
+struct CoverageRasterizer {
+    width: usize,
+    height: usize,
+    // Two spare columns per row catch the area of edges that touch the right side.
+    stride: usize,
+    accumulation: Vec<f32>,
+}
+
+impl CoverageRasterizer {
+    fn new(width: usize, height: usize) -> CoverageRasterizer {
+        let stride = width + 2;
+        CoverageRasterizer { width, height, stride, accumulation: vec![0.0; stride * height] }
+    }
+
+    fn draw_monotonic_cubic(&mut self, segment: &Segment, depth: u32) {
+        let cubic = segment.as_cubic_segment();
+        if depth == MAX_FLATTENING_SUBDIVISIONS || cubic.is_flat(FLATTENING_TOLERANCE) {
+            self.draw_line(segment.baseline);
+            return;
+        }
+        let (before, after) = cubic.split(0.5);
+        self.draw_monotonic_cubic(&before, depth + 1);
+        self.draw_monotonic_cubic(&after, depth + 1);
+    }
+
+    fn draw_line(&mut self, line: LineSegment2F) {
+        let (from, to, direction) = if line.from_y() < line.to_y() {
+            (line.from(), line.to(), 1.0)
+        } else {
+            (line.to(), line.from(), -1.0)
+        };
+        let (y_start, y_end) = (from.y().max(0.0), to.y().min(self.height as f32));
+        if y_start >= y_end {
+            return;
+        }
+
+        let dx_dy = (to.x() - from.x()) / (to.y() - from.y());
+        let max_x = self.width as f32;
+        let mut x = from.x() + (y_start - from.y()) * dx_dy;
+        for row in (y_start as usize)..(y_end.ceil() as usize) {
+            let dy = ((row + 1) as f32).min(y_end) - (row as f32).max(y_start);
+            let next_x = x + dx_dy * dy;
+            let area = dy * direction;
+            let (x0, x1) = (x.min(next_x).clamp(0.0, max_x), x.max(next_x).clamp(0.0, max_x));
+            let row_start = row * self.stride;
+            let accumulation = &mut self.accumulation[row_start..(row_start + self.stride)];
+
+            let (x0_floor, x1_ceil) = (x0.floor(), x1.ceil());
+            let (x0_index, x1_index) = (x0_floor as usize, x1_ceil as usize);
+            if x1_index <= x0_index + 1 {
+                // The edge stays within one pixel in this row.
+                let mid_x = 0.5 * (x0 + x1) - x0_floor;
+                accumulation[x0_index] += area * (1.0 - mid_x);
+                accumulation[x0_index + 1] += area * mid_x;
+            } else {
+                let inverse_width = 1.0 / (x1 - x0);
+                let x0_fraction = x0 - x0_floor;
+                let first_area = 0.5 * inverse_width * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+                let x1_fraction = x1 - x1_ceil + 1.0;
+                let last_area = 0.5 * inverse_width * x1_fraction * x1_fraction;
+                accumulation[x0_index] += area * first_area;
+                if x1_index == x0_index + 2 {
+                    accumulation[x0_index + 1] += area * (1.0 - first_area - last_area);
+                } else {
+                    let second_area = inverse_width * (1.5 - x0_fraction);
+                    accumulation[x0_index + 1] += area * (second_area - first_area);
+                    for value in &mut accumulation[(x0_index + 2)..(x1_index - 1)] {
+                        *value += area * inverse_width;
+                    }
+                    let before_last_area =
+                        second_area + (x1_index - x0_index - 3) as f32 * inverse_width;
+                    accumulation[x1_index - 1] += area * (1.0 - before_last_area - last_area);
+                }
+                accumulation[x1_index] += area * last_area;
+            }
+
+            x = next_x;
+        }
+    }
+
+    fn coverage(&self, fill_rule: FillRule) -> Vec<f32> {
+        let mut coverage = Vec::with_capacity(self.width * self.height);
+        for row in self.accumulation.chunks(self.stride) {
+            let mut winding = 0.0;
+            for &value in &row[..self.width] {
+                winding += value;
+                let winding = f32::abs(winding);
+                coverage.push(match fill_rule {
+                    FillRule::Winding => winding.min(1.0),
+                    FillRule::EvenOdd => {
+                        let parity = winding % 2.0;
+                        if parity > 1.0 { 2.0 - parity } else { parity }
+                    }
+                });
+            }
+        }
+        coverage
+    }
+}
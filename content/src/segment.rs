@@ -10,16 +10,39 @@
 
 //! Line or curve segments, optimized with SIMD.
 
+use arrayvec::ArrayVec;
+use crate::outline::ArcDirection;
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::unit_vector::UnitVector;
 use pathfinder_geometry::util::{self, EPSILON};
 use pathfinder_geometry::vector::{Vector2F, vec2f};
 use pathfinder_simd::default::F32x4;
 use std::f32::consts::SQRT_2;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use serde::de::Error as DeserializeError;
+
 const MAX_NEWTON_ITERATIONS: u32 = 32;
+const MAX_ARC_LENGTH_SUBDIVISIONS: u32 = 16;
+const MAX_TIME_FOR_DISTANCE_ITERATIONS: u32 = 32;
+const NEAREST_POINT_SAMPLES: u32 = 16;
+const MAX_NEAREST_POINT_ITERATIONS: u32 = 16;
+const NEAREST_POINT_PARAMETER_EPSILON: f32 = 0.000001;
+const MAX_INTERSECTION_SUBDIVISIONS: u32 = 32;
+const INTERSECTION_TOLERANCE: f32 = 0.001;
+// Intersections closer together than this in both parameters are reported once.
+const INTERSECTION_PARAMETER_EPSILON: f32 = 0.001;
+const MAX_QUADRATIC_APPROXIMATION_PIECES: u32 = 256;
+
+/// The default tolerance used when estimating the lengths of curves.
+pub const ARC_LENGTH_TOLERANCE: f32 = 0.001;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Segment {
     pub baseline: LineSegment2F,
     pub ctrl: LineSegment2F,
@@ -139,22 +162,84 @@ impl Segment {
         CubicSegment(self)
     }
 
+    /// Converts this segment to the cubic curve that traces exactly the same path.
+    ///
+    /// Quadratic curves are degree elevated, and lines become cubics with their control points a
+    /// third and two thirds of the way along them. Cubic curves and empty segments are returned
+    /// unchanged.
     // FIXME(pcwalton): We should basically never use this function.
-    // FIXME(pcwalton): Handle lines!
     #[inline]
     pub fn to_cubic(&self) -> Segment {
-        if self.is_cubic() {
-            return *self;
-        }
-
         let mut new_segment = *self;
-        let p1_2 = self.ctrl.from() + self.ctrl.from();
-        new_segment.ctrl = LineSegment2F::new(self.baseline.from() + p1_2,
-                                              p1_2 + self.baseline.to()) * (1.0 / 3.0);
+        match self.kind {
+            SegmentKind::None | SegmentKind::Cubic => return *self,
+            SegmentKind::Line => {
+                let (from, to) = (self.baseline.from(), self.baseline.to());
+                new_segment.ctrl = LineSegment2F::new(from.lerp(to, 1.0 / 3.0),
+                                                      from.lerp(to, 2.0 / 3.0));
+            }
+            SegmentKind::Quadratic => {
+                let p1_2 = self.ctrl.from() + self.ctrl.from();
+                new_segment.ctrl = LineSegment2F::new(self.baseline.from() + p1_2,
+                                                      p1_2 + self.baseline.to()) * (1.0 / 3.0);
+            }
+        }
         new_segment.kind = SegmentKind::Cubic;
         new_segment
     }
 
+    /// Approximates this segment with quadratic curves that deviate from it by no more than
+    /// `tolerance`.
+    ///
+    /// Cubic curves are split into equal pieces in `t`, as few as the tolerance allows, and each
+    /// piece is replaced by the quadratic that matches it at both ends and in the middle. Lines
+    /// and quadratic curves are returned unchanged.
+    pub fn to_quadratics(&self, tolerance: f32) -> impl Iterator<Item = Segment> {
+        let mut piece_count = match self.kind {
+            SegmentKind::None => 0,
+            SegmentKind::Line | SegmentKind::Quadratic => 1,
+            SegmentKind::Cubic => {
+                // The error of the approximation is at most √3/36 times the length of the third
+                // difference of the control points, which shrinks with the cube of the length of
+                // each piece.
+                let (p0, p3) = (self.baseline.from(), self.baseline.to());
+                let (p1, p2) = (self.ctrl.from(), self.ctrl.to());
+                let third_difference = (p3 - p0 + (p1 - p2) * 3.0).length();
+                let error = f32::sqrt(3.0) / 36.0 * third_difference;
+                let piece_count = f32::ceil(f32::cbrt(error / tolerance));
+                if piece_count >= 1.0 {
+                    piece_count.min(MAX_QUADRATIC_APPROXIMATION_PIECES as f32) as u32
+                } else {
+                    1
+                }
+            }
+        };
+
+        let mut remaining = *self;
+        std::iter::from_fn(move || {
+            if piece_count == 0 {
+                return None;
+            }
+            let piece = if piece_count == 1 {
+                remaining
+            } else {
+                let (piece, rest) = remaining.split(1.0 / piece_count as f32);
+                remaining = rest;
+                piece
+            };
+            piece_count -= 1;
+
+            if !piece.is_cubic() {
+                return Some(piece);
+            }
+            let (p0, p3) = (piece.baseline.from(), piece.baseline.to());
+            let ctrl = ((piece.ctrl.from() + piece.ctrl.to()) * 3.0 - p0 - p3) * 0.25;
+            let mut quadratic = Segment::quadratic(piece.baseline, ctrl);
+            quadratic.flags = piece.flags;
+            Some(quadratic)
+        })
+    }
+
     #[inline]
     pub fn is_monotonic(&self) -> bool {
         // FIXME(pcwalton): Don't degree elevate!
@@ -165,6 +250,13 @@ impl Segment {
         }
     }
 
+    /// Returns this segment traversed in the opposite direction, so that `reversed().sample(t)` is
+    /// `sample(1.0 - t)`.
+    ///
+    /// The kind is unchanged. The control points of a cubic are swapped, while a quadratic keeps
+    /// its single control point. The flags are copied as they are, so `FIRST_IN_SUBPATH` and
+    /// `CLOSES_SUBPATH` still describe this segment's place in the original path; callers that
+    /// reverse whole subpaths should update them.
     #[inline]
     pub fn reversed(&self) -> Segment {
         Segment {
@@ -190,31 +282,242 @@ impl Segment {
         }
     }
 
+    /// Returns true if every point of this segment, including its control points, lies within
+    /// `epsilon` of its start, so that it draws nothing and has no meaningful tangent.
+    pub fn is_degenerate(&self, epsilon: f32) -> bool {
+        let (from, square_epsilon) = (self.baseline.from(), epsilon * epsilon);
+        let is_near = |point: Vector2F| (point - from).square_length() <= square_epsilon;
+        match self.kind {
+            SegmentKind::None => true,
+            SegmentKind::Line => is_near(self.baseline.to()),
+            SegmentKind::Quadratic => is_near(self.ctrl.from()) && is_near(self.baseline.to()),
+            SegmentKind::Cubic => {
+                is_near(self.ctrl.from()) && is_near(self.ctrl.to()) &&
+                    is_near(self.baseline.to())
+            }
+        }
+    }
+
     #[inline]
     pub fn is_tiny(&self) -> bool {
         const EPSILON: f32 = 0.0001;
         self.baseline.square_length() < EPSILON
     }
 
+    /// Splits this segment at parameter t using de Casteljau subdivision, returning the parts
+    /// before and after t.
+    ///
+    /// Both halves have the same kind as this segment and share the split point exactly. If t is
+    /// at or beyond either end, the corresponding half is degenerate: all of its points coincide
+    /// with that end. `FIRST_IN_SUBPATH` is kept on the first half and `CLOSES_SUBPATH` on the
+    /// second.
     #[inline]
     pub fn split(&self, t: f32) -> (Segment, Segment) {
-        // FIXME(pcwalton): Don't degree elevate!
-        if self.is_line() {
-            let (before, after) = self.as_line_segment().split(t);
-            (Segment::line(before), Segment::line(after))
+        match self.kind {
+            SegmentKind::None => (*self, *self),
+            SegmentKind::Line => {
+                let (before, after) = if t <= 0.0 {
+                    let from = self.baseline.from();
+                    (LineSegment2F::new(from, from), self.baseline)
+                } else if t >= 1.0 {
+                    let to = self.baseline.to();
+                    (self.baseline, LineSegment2F::new(to, to))
+                } else {
+                    self.baseline.split(t)
+                };
+                let (mut before, mut after) = (Segment::line(before), Segment::line(after));
+                before.flags = self.flags & SegmentFlags::FIRST_IN_SUBPATH;
+                after.flags = self.flags & SegmentFlags::CLOSES_SUBPATH;
+                (before, after)
+            }
+            SegmentKind::Quadratic => self.split_quadratic(t),
+            SegmentKind::Cubic => self.as_cubic_segment().split(t),
+        }
+    }
+
+    /// Returns the part of this segment between parameters `t0` and `t1`, which are clamped to
+    /// [0, 1].
+    ///
+    /// The slice has the same kind as this segment, but none of its flags. If `t0` is greater than
+    /// `t1`, the slice runs backward, from `t0` to `t1`.
+    pub fn slice(&self, t0: f32, t1: f32) -> Segment {
+        if t0 > t1 {
+            return self.slice(t1, t0).reversed();
+        }
+
+        let (t0, t1) = (t0.clamp(0.0, 1.0), t1.clamp(0.0, 1.0));
+        let after = self.split(t0).1;
+        let t = if t0 < 1.0 { (t1 - t0) / (1.0 - t0) } else { 0.0 };
+        let mut slice = after.split(t).0;
+        slice.flags = SegmentFlags::empty();
+        slice
+    }
+
+    fn split_quadratic(&self, t: f32) -> (Segment, Segment) {
+        let (p0, p1, p2) = (self.baseline.from(), self.ctrl.from(), self.baseline.to());
+        let (p01, p12, p012);
+        if t <= 0.0 {
+            p01 = p0;
+            p12 = p1;
+            p012 = p0;
+        } else if t >= 1.0 {
+            p01 = p1;
+            p12 = p2;
+            p012 = p2;
         } else {
-            self.to_cubic().as_cubic_segment().split(t)
+            p01 = p0.lerp(p1, t);
+            p12 = p1.lerp(p2, t);
+            p012 = p01.lerp(p12, t);
         }
+
+        let mut before = Segment::quadratic(LineSegment2F::new(p0, p012), p01);
+        let mut after = Segment::quadratic(LineSegment2F::new(p012, p2), p12);
+        before.flags = self.flags & SegmentFlags::FIRST_IN_SUBPATH;
+        after.flags = self.flags & SegmentFlags::CLOSES_SUBPATH;
+        (before, after)
     }
 
     #[inline]
     pub fn sample(self, t: f32) -> Vector2F {
-        // FIXME(pcwalton): Don't degree elevate!
-        if self.is_line() {
-            self.as_line_segment().sample(t)
+        match self.kind {
+            SegmentKind::None | SegmentKind::Line => self.as_line_segment().sample(t),
+            SegmentKind::Quadratic => {
+                let (p0, p1, p2) = (self.baseline.from(), self.ctrl.from(), self.baseline.to());
+                let mt = 1.0 - t;
+                p0 * (mt * mt) + p1 * (2.0 * mt * t) + p2 * (t * t)
+            }
+            SegmentKind::Cubic => self.as_cubic_segment().sample(t),
+        }
+    }
+
+    /// Returns the derivative of this segment at parameter t, which points along the direction of
+    /// travel. The result is not normalized.
+    ///
+    /// Where the derivative vanishes, as it does at the ends of a cubic whose control points
+    /// coincide with its endpoints, the direction of the baseline is returned instead.
+    pub fn tangent(self, t: f32) -> Vector2F {
+        let tangent = self.derivative_at(t);
+        if tangent.square_length() < EPSILON * EPSILON {
+            self.baseline.vector()
+        } else {
+            tangent
+        }
+    }
+
+    /// Returns the derivative of this segment, also known as its hodograph: a segment of one
+    /// lower degree whose value at each t is the tangent vector of this segment at t.
+    ///
+    /// The derivative of a cubic is a quadratic and that of a quadratic is a line. A line has a
+    /// constant derivative, which is returned as a line whose endpoints coincide.
+    pub fn derivative(&self) -> Segment {
+        let (p0, p1) = (self.baseline.from(), self.ctrl.from());
+        let (p2, p3) = (self.ctrl.to(), self.baseline.to());
+        match self.kind {
+            SegmentKind::None => Segment::none(),
+            SegmentKind::Line => {
+                let vector = self.baseline.vector();
+                Segment::line(LineSegment2F::new(vector, vector))
+            }
+            SegmentKind::Quadratic => {
+                Segment::line(LineSegment2F::new((p1 - p0) * 2.0, (p3 - p1) * 2.0))
+            }
+            SegmentKind::Cubic => {
+                Segment::quadratic(LineSegment2F::new((p1 - p0) * 3.0, (p3 - p2) * 3.0),
+                                   (p2 - p1) * 3.0)
+            }
+        }
+    }
+
+    /// Returns the values of t, in increasing order, strictly between 0 and 1 at which the signed
+    /// curvature of this cubic changes sign.
+    ///
+    /// These are the roots of the cross product of the first and second derivatives. Lines and
+    /// quadratics never inflect, so they return no values.
+    pub fn inflections(&self) -> ArrayVec<[f32; 2]> {
+        let mut inflections = ArrayVec::new();
+        if !self.is_cubic() {
+            return inflections;
+        }
+
+        // With the derivative written as 3 (a + 2 b t + c t²), the cross product of the first and
+        // second derivatives is proportional to (b × c) t² + (a × c) t + a × b.
+        let (p0, p1) = (self.baseline.from(), self.ctrl.from());
+        let (p2, p3) = (self.ctrl.to(), self.baseline.to());
+        let (a, b, c) = (p1 - p0, p2 - p1 - p1 + p0, p3 - p0 + (p1 - p2) * 3.0);
+        let (qa, qb, qc) = (b.det(c), a.det(c), a.det(b));
+
+        let mut roots: ArrayVec<[f32; 2]> = ArrayVec::new();
+        if qa.abs() < EPSILON {
+            if qb.abs() >= EPSILON {
+                roots.push(-qc / qb);
+            }
         } else {
-            self.to_cubic().as_cubic_segment().sample(t)
+            let discrim = qb * qb - 4.0 * qa * qc;
+            if discrim >= 0.0 {
+                let q = -0.5 * (qb + f32::copysign(f32::sqrt(discrim), qb));
+                roots.push(q / qa);
+                if q != 0.0 {
+                    roots.push(qc / q);
+                }
+            }
         }
+
+        if roots.len() == 2 && roots[1] < roots[0] {
+            roots.swap(0, 1);
+        }
+        for t in roots {
+            if t > 0.0 && t < 1.0 && inflections.last() != Some(&t) {
+                inflections.push(t);
+            }
+        }
+        inflections
+    }
+
+    // The exact derivative, without the fallback that `tangent()` applies.
+    #[inline]
+    pub(crate) fn derivative_at(self, t: f32) -> Vector2F {
+        let (p0, p1) = (self.baseline.from(), self.ctrl.from());
+        let (p2, p3) = (self.ctrl.to(), self.baseline.to());
+        let mt = 1.0 - t;
+        match self.kind {
+            SegmentKind::None | SegmentKind::Line => self.baseline.vector(),
+            SegmentKind::Quadratic => ((p1 - p0) * mt + (p3 - p1) * t) * 2.0,
+            SegmentKind::Cubic => {
+                ((p1 - p0) * (mt * mt) + (p2 - p1) * (2.0 * mt * t) + (p3 - p2) * (t * t)) * 3.0
+            }
+        }
+    }
+
+    /// Returns the signed curvature at parameter t: the reciprocal of the radius of the circle
+    /// that best fits the segment there.
+    ///
+    /// The curvature is positive where the segment turns from the positive x axis toward the
+    /// positive y axis, and zero for lines. Where the derivative vanishes, as at a cusp, it is
+    /// unbounded, and infinity is returned.
+    pub fn curvature(&self, t: f32) -> f32 {
+        let velocity = self.derivative_at(t);
+        let speed = velocity.length();
+        if speed == 0.0 {
+            return f32::INFINITY;
+        }
+        let acceleration = self.derivative().derivative_at(t);
+        velocity.det(acceleration) / (speed * speed * speed)
+    }
+
+    /// Returns the signed radius of curvature at parameter t, the reciprocal of `curvature()`.
+    ///
+    /// This is infinite for lines and zero at cusps.
+    #[inline]
+    pub fn radius_of_curvature(&self, t: f32) -> f32 {
+        1.0 / self.curvature(t)
+    }
+
+    /// Returns the tangent at parameter t rotated by 90°, in the same sense as
+    /// `Transform2F::from_rotation(FRAC_PI_2)`. The result is not normalized.
+    #[inline]
+    pub fn normal(self, t: f32) -> Vector2F {
+        let tangent = self.tangent(t);
+        vec2f(-tangent.y(), tangent.x())
     }
 
     #[inline]
@@ -227,18 +530,224 @@ impl Segment {
         }
     }
 
+    /// Returns the smallest rectangle that contains this segment.
+    ///
+    /// Unlike the bounds of the control points, this only includes the points that the curve
+    /// actually passes through.
+    pub fn tight_bounds(&self) -> RectF {
+        match self.kind {
+            SegmentKind::None | SegmentKind::Line => {
+                RectF::from_points(self.baseline.from(), self.baseline.from())
+                    .union_point(self.baseline.to())
+            }
+            SegmentKind::Quadratic | SegmentKind::Cubic => {
+                self.to_cubic().as_cubic_segment().tight_bounds()
+            }
+        }
+    }
+
+    /// Returns the smallest rectangle that contains this segment; the same as `tight_bounds()`.
+    ///
+    /// Note that `Contour::bounds()` differs in that it includes control points.
+    #[inline]
+    pub fn bounds(&self) -> RectF {
+        self.tight_bounds()
+    }
+
+    /// Returns the length of this segment.
+    ///
+    /// This is exact for lines. Curves are estimated to within `ARC_LENGTH_TOLERANCE`; use
+    /// `arc_length_with_tolerance()` to choose a different tolerance.
+    #[inline]
     pub fn arc_length(&self) -> f32 {
-        // FIXME(pcwalton)
-        self.baseline.vector().length()
+        self.arc_length_with_tolerance(ARC_LENGTH_TOLERANCE)
+    }
+
+    /// Returns the length of this segment, estimating the length of curves to within the given
+    /// absolute tolerance.
+    pub fn arc_length_with_tolerance(&self, tolerance: f32) -> f32 {
+        match self.kind {
+            SegmentKind::None => 0.0,
+            SegmentKind::Line => self.baseline.vector().length(),
+            SegmentKind::Quadratic | SegmentKind::Cubic => {
+                self.to_cubic().as_cubic_segment().arc_length(tolerance, 0)
+            }
+        }
+    }
+
+    /// Returns the parameter t of the point on this segment closest to `point`.
+    ///
+    /// This is exact for lines. For curves, the closest of several evenly spaced samples is
+    /// refined with Newton's method, which finds the global minimum unless the curve doubles back
+    /// on itself more tightly than the sample spacing.
+    pub fn nearest_t(&self, point: Vector2F) -> f32 {
+        match self.kind {
+            SegmentKind::None => 0.0,
+            SegmentKind::Line => {
+                let vector = self.baseline.vector();
+                let square_length = vector.square_length();
+                if square_length == 0.0 {
+                    0.0
+                } else {
+                    ((point - self.baseline.from()).dot(vector) / square_length).clamp(0.0, 1.0)
+                }
+            }
+            SegmentKind::Quadratic | SegmentKind::Cubic => {
+                let square_distance_at = |t: f32| (self.sample(t) - point).square_length();
+
+                let (mut best_t, mut best_square_distance) = (0.0, square_distance_at(0.0));
+                for sample_index in 1..=NEAREST_POINT_SAMPLES {
+                    let t = sample_index as f32 / NEAREST_POINT_SAMPLES as f32;
+                    let square_distance = square_distance_at(t);
+                    if square_distance < best_square_distance {
+                        best_t = t;
+                        best_square_distance = square_distance;
+                    }
+                }
+
+                // Find a root of the derivative of the squared distance, (B(t) - p) · B'(t).
+                let second_derivative = self.derivative();
+                let mut t = best_t;
+                for _ in 0..MAX_NEAREST_POINT_ITERATIONS {
+                    let (offset, velocity) = (self.sample(t) - point, self.derivative_at(t));
+                    let acceleration = second_derivative.derivative_at(t);
+                    let denominator = velocity.square_length() + offset.dot(acceleration);
+                    if denominator <= 0.0 {
+                        break;
+                    }
+                    let next_t = (t - offset.dot(velocity) / denominator).clamp(0.0, 1.0);
+                    let square_distance = square_distance_at(next_t);
+                    if square_distance < best_square_distance {
+                        best_t = next_t;
+                        best_square_distance = square_distance;
+                    }
+                    if (next_t - t).abs() < NEAREST_POINT_PARAMETER_EPSILON {
+                        break;
+                    }
+                    t = next_t;
+                }
+                best_t
+            }
+        }
+    }
+
+    /// Returns the pairs of parameters `(t_self, t_other)` at which this segment crosses `other`.
+    ///
+    /// Lines are intersected analytically. Curves are subdivided until the pieces are flat to
+    /// within a small tolerance, and the resulting lines are then intersected. Parallel lines,
+    /// including collinear lines that overlap, produce no intersections. Results are ordered by
+    /// neither parameter.
+    pub fn intersect(&self, other: &Segment) -> ArrayVec<[(f32, f32); 9]> {
+        let mut results = ArrayVec::new();
+        if self.is_none() || other.is_none() {
+            return results;
+        }
+
+        let (this, other) = (self.to_cubic_unless_line(), other.to_cubic_unless_line());
+        intersect_segments(&this, (0.0, 1.0), &other, (0.0, 1.0), 0, &mut results);
+        return results;
+
+        fn intersect_segments(a: &Segment,
+                              a_range: (f32, f32),
+                              b: &Segment,
+                              b_range: (f32, f32),
+                              depth: u32,
+                              results: &mut ArrayVec<[(f32, f32); 9]>) {
+            if results.is_full() {
+                return;
+            }
+            let (a_bounds, b_bounds) = (a.control_bounds(), b.control_bounds());
+            if !a_bounds.dilate(INTERSECTION_TOLERANCE).intersects(b_bounds) {
+                return;
+            }
+
+            let a_is_flat = a.is_line() || a.as_cubic_segment().is_flat(INTERSECTION_TOLERANCE);
+            let b_is_flat = b.is_line() || b.as_cubic_segment().is_flat(INTERSECTION_TOLERANCE);
+            if (a_is_flat && b_is_flat) || depth == MAX_INTERSECTION_SUBDIVISIONS {
+                if let Some((t, u)) = line_intersection_params(a.baseline, b.baseline) {
+                    let t = a_range.0 + (a_range.1 - a_range.0) * t;
+                    let u = b_range.0 + (b_range.1 - b_range.0) * u;
+                    let is_duplicate = results.iter().any(|&(prev_t, prev_u)| {
+                        f32::abs(prev_t - t) < INTERSECTION_PARAMETER_EPSILON &&
+                            f32::abs(prev_u - u) < INTERSECTION_PARAMETER_EPSILON
+                    });
+                    if !is_duplicate {
+                        results.push((t, u));
+                    }
+                }
+                return;
+            }
+
+            // Split whichever segment is larger, or the one that isn't flat yet.
+            let a_size = a_bounds.size().x() + a_bounds.size().y();
+            let b_size = b_bounds.size().x() + b_bounds.size().y();
+            if !a_is_flat && (b_is_flat || a_size >= b_size) {
+                let (a_before, a_after) = a.split(0.5);
+                let a_mid = (a_range.0 + a_range.1) * 0.5;
+                intersect_segments(&a_before, (a_range.0, a_mid), b, b_range, depth + 1, results);
+                intersect_segments(&a_after, (a_mid, a_range.1), b, b_range, depth + 1, results);
+            } else {
+                let (b_before, b_after) = b.split(0.5);
+                let b_mid = (b_range.0 + b_range.1) * 0.5;
+                intersect_segments(a, a_range, &b_before, (b_range.0, b_mid), depth + 1, results);
+                intersect_segments(a, a_range, &b_after, (b_mid, b_range.1), depth + 1, results);
+            }
+        }
+    }
+
+    #[inline]
+    fn to_cubic_unless_line(self) -> Segment {
+        if self.is_line() {
+            self
+        } else {
+            self.to_cubic()
+        }
     }
 
+    // The bounding box of the endpoints and control points, which contains the whole segment.
+    pub(crate) fn control_bounds(&self) -> RectF {
+        let mut bounds = RectF::from_points(self.baseline.from(), self.baseline.from())
+            .union_point(self.baseline.to());
+        if !self.is_line() {
+            bounds = bounds.union_point(self.ctrl.from());
+            if !self.is_quadratic() {
+                bounds = bounds.union_point(self.ctrl.to());
+            }
+        }
+        bounds
+    }
+
+    /// Returns the parameter t at which the arc length from the start of this segment reaches
+    /// `distance`.
+    ///
+    /// Values greater than 1.0 are returned if `distance` is longer than the segment.
     pub fn time_for_distance(&self, distance: f32) -> f32 {
-        // FIXME(pcwalton)
-        distance / self.arc_length()
+        let length = self.arc_length();
+        if self.is_line() || distance <= 0.0 || distance >= length {
+            return distance / length;
+        }
+
+        // Bisect on the length of the segment before t.
+        let (mut min_t, mut max_t) = (0.0, 1.0);
+        let mut t = distance / length;
+        for _ in 0..MAX_TIME_FOR_DISTANCE_ITERATIONS {
+            let length_before = self.split(t).0.arc_length();
+            if f32::abs(length_before - distance) <= ARC_LENGTH_TOLERANCE {
+                break;
+            }
+            if length_before < distance {
+                min_t = t;
+            } else {
+                max_t = t;
+            }
+            t = (min_t + max_t) * 0.5;
+        }
+        t
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u8)]
 pub enum SegmentKind {
     None,
@@ -254,6 +763,24 @@ bitflags! {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for SegmentFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.bits().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SegmentFlags {
+    fn deserialize<D>(deserializer: D) -> Result<SegmentFlags, D::Error>
+                      where D: Deserializer<'de> {
+        let bits = u8::deserialize(deserializer)?;
+        SegmentFlags::from_bits(bits).ok_or_else(|| {
+            D::Error::custom(format!("invalid segment flags: {:#x}", bits))
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct CubicSegment<'s>(pub &'s Segment);
 
@@ -335,10 +862,47 @@ impl<'s> CubicSegment<'s> {
         self.split(t).1
     }
 
-    // FIXME(pcwalton): Use Horner's method!
     #[inline]
     pub fn sample(self, t: f32) -> Vector2F {
-        self.split(t).0.baseline.to()
+        let (p0, p1) = (self.0.baseline.from(), self.0.ctrl.from());
+        let (p2, p3) = (self.0.ctrl.to(), self.0.baseline.to());
+        let mt = 1.0 - t;
+        let (mt2, t2) = (mt * mt, t * t);
+        p0 * (mt2 * mt) + p1 * (3.0 * mt2 * t) + p2 * (3.0 * mt * t2) + p3 * (t2 * t)
+    }
+
+    // Jens Gravesen, "Adaptive subdivision and the length and energy of Bézier curves", 1997.
+    //
+    // The arc length lies between the chord length and the length of the control polygon, so we
+    // subdivide until those two agree to within the tolerance.
+    fn arc_length(self, tolerance: f32, depth: u32) -> f32 {
+        let (p0, p3) = (self.0.baseline.from(), self.0.baseline.to());
+        let (p1, p2) = (self.0.ctrl.from(), self.0.ctrl.to());
+        let chord_length = (p3 - p0).length();
+        let polygon_length = (p1 - p0).length() + (p2 - p1).length() + (p3 - p2).length();
+        if polygon_length - chord_length <= tolerance || depth == MAX_ARC_LENGTH_SUBDIVISIONS {
+            return (chord_length + polygon_length) * 0.5;
+        }
+
+        let (before, after) = self.split(0.5);
+        before.as_cubic_segment().arc_length(tolerance * 0.5, depth + 1) +
+            after.as_cubic_segment().arc_length(tolerance * 0.5, depth + 1)
+    }
+
+    // Includes the points at which the derivative of either coordinate is zero, in addition to
+    // the endpoints.
+    fn tight_bounds(self) -> RectF {
+        let (p0, p3) = (self.0.baseline.from(), self.0.baseline.to());
+        let (p1, p2) = (self.0.ctrl.from(), self.0.ctrl.to());
+        let mut bounds = RectF::from_points(p0, p0).union_point(p3);
+        let x_roots = cubic_derivative_roots(p0.x(), p1.x(), p2.x(), p3.x());
+        let y_roots = cubic_derivative_roots(p0.y(), p1.y(), p2.y(), p3.y());
+        for &t in x_roots.iter().chain(y_roots.iter()).flatten() {
+            if t > 0.0 && t < 1.0 {
+                bounds = bounds.union_point(self.sample(t));
+            }
+        }
+        bounds
     }
 
     #[inline]
@@ -424,3 +988,549 @@ impl<'s> CubicSegment<'s> {
         f32::max(self.0.baseline.max_y(), self.0.ctrl.max_y())
     }
 }
+
+/// Approximates the elliptical arc from `from` to `to` with at most four cubic Bézier curves, as
+/// the SVG `A` path command describes it.
+///
+/// `x_axis_rotation` is in radians. `large_arc` selects which of the two arcs that fit the
+/// endpoints and radii is taken, and `direction` selects the direction of travel, with
+/// `ArcDirection::CW` corresponding to a sweep flag of 1. Radii that are too small to span the
+/// endpoints are scaled up. If either radius is zero, a single line is returned, and if the
+/// endpoints coincide, nothing is.
+///
+/// Each curve spans at most a quarter of the ellipse, so the radial error is bounded by about
+/// 0.03% of the radius.
+pub fn arc_to_cubics(from: Vector2F,
+                     to: Vector2F,
+                     radii: Vector2F,
+                     x_axis_rotation: f32,
+                     large_arc: bool,
+                     direction: ArcDirection)
+                     -> impl Iterator<Item = Segment> {
+    // See the "Elliptical arc implementation notes" appendix of the SVG specification.
+    let mut segments = ArrayVec::new();
+    if from == to {
+        return segments.into_iter();
+    }
+    let mut radii = vec2f(radii.x().abs(), radii.y().abs());
+    if radii.x() < EPSILON || radii.y() < EPSILON {
+        segments.push(Segment::line(LineSegment2F::new(from, to)));
+        return segments.into_iter();
+    }
+
+    // Work in a frame centered on the midpoint of the chord, with the ellipse axes aligned to the
+    // coordinate axes.
+    let rotation = Transform2F::from_rotation(x_axis_rotation);
+    let half_chord = rotation.inverse() * ((from - to) * 0.5);
+    let lambda = (half_chord / radii).square_length();
+    if lambda > 1.0 {
+        radii *= lambda.sqrt();
+    }
+
+    let (rx2, ry2) = (radii.x() * radii.x(), radii.y() * radii.y());
+    let (x2, y2) = (half_chord.x() * half_chord.x(), half_chord.y() * half_chord.y());
+    let mut center_coefficient = f32::sqrt(f32::max(0.0, (rx2 * ry2 - rx2 * y2 - ry2 * x2) /
+                                                          (rx2 * y2 + ry2 * x2)));
+    if large_arc == (direction == ArcDirection::CW) {
+        center_coefficient = -center_coefficient;
+    }
+    let center = vec2f(radii.x() * half_chord.y() / radii.y(),
+                       -radii.y() * half_chord.x() / radii.x()) * center_coefficient;
+
+    // Map the endpoints onto the unit circle.
+    let chord = LineSegment2F::new(((half_chord - center) / radii).normalize(),
+                                   ((-half_chord - center) / radii).normalize());
+    let transform = Transform2F::from_translation((from + to) * 0.5) *
+        rotation *
+        Transform2F::from_translation(center) *
+        Transform2F::from_scale(radii);
+    unit_arc_to_cubics(&transform, chord, direction).into_iter()
+}
+
+// Approximates the arc of the unit circle from the start of `chord` to its end, travelling in
+// `direction`, with at most four curves mapped through `transform`.
+pub(crate) fn unit_arc_to_cubics(transform: &Transform2F,
+                                 mut chord: LineSegment2F,
+                                 direction: ArcDirection)
+                                 -> ArrayVec<[Segment; 4]> {
+    const EPSILON: f32 = 0.001;
+
+    let mut segments = ArrayVec::new();
+    let mut direction_transform = Transform2F::default();
+    if direction == ArcDirection::CCW {
+        chord *= vec2f(1.0, -1.0);
+        direction_transform = Transform2F::from_scale(vec2f(1.0, -1.0));
+    }
+
+    let (mut vector, end_vector) = (UnitVector(chord.from()), UnitVector(chord.to()));
+    for segment_index in 0..4 {
+        debug!("unit_arc_to_cubics(): loop segment index {}", segment_index);
+
+        let mut sweep_vector = end_vector.rev_rotate_by(vector);
+        let last = sweep_vector.0.x() >= -EPSILON && sweep_vector.0.y() >= -EPSILON;
+        debug!("... end_vector={:?} vector={:?} sweep_vector={:?} last={:?}",
+               end_vector,
+               vector,
+               sweep_vector,
+               last);
+
+        let mut segment;
+        if !last {
+            sweep_vector = UnitVector(vec2f(0.0, 1.0));
+            segment = Segment::quarter_circle_arc();
+        } else {
+            segment = Segment::arc_from_cos(sweep_vector.0.x());
+        }
+
+        let half_sweep_vector = sweep_vector.halve_angle();
+        let rotation = Transform2F::from_rotation_vector(half_sweep_vector.rotate_by(vector));
+        segment = segment.transform(&(*transform * direction_transform * rotation));
+        segments.push(segment);
+
+        if last {
+            break;
+        }
+
+        vector = vector.rotate_by(sweep_vector);
+    }
+
+    segments
+}
+
+// Returns the parameters along both lines at which they cross, or `None` if they are parallel or
+// don't meet within their extents.
+fn line_intersection_params(a: LineSegment2F, b: LineSegment2F) -> Option<(f32, f32)> {
+    let (vector_a, vector_b) = (a.vector(), b.vector());
+    let denominator = vector_a.det(vector_b);
+    if denominator.abs() <= EPSILON * EPSILON * vector_a.length() * vector_b.length() {
+        return None;
+    }
+
+    let offset = b.from() - a.from();
+    let (t, u) = (offset.det(vector_b) / denominator, offset.det(vector_a) / denominator);
+    let range = -INTERSECTION_PARAMETER_EPSILON..=(1.0 + INTERSECTION_PARAMETER_EPSILON);
+    if range.contains(&t) && range.contains(&u) {
+        Some((t.clamp(0.0, 1.0), u.clamp(0.0, 1.0)))
+    } else {
+        None
+    }
+}
+
+// Returns the values of t at which the derivative of the one-dimensional cubic Bézier curve with
+// the given control values is zero.
+fn cubic_derivative_roots(p0: f32, p1: f32, p2: f32, p3: f32) -> [Option<f32>; 2] {
+    // The derivative is 3 (a t² + b t + c).
+    let a = p3 - p0 + 3.0 * (p1 - p2);
+    let b = 2.0 * (p0 - p1 - p1 + p2);
+    let c = p1 - p0;
+
+    let discrim = b * b - 4.0 * a * c;
+    if discrim < 0.0 {
+        return [None, None];
+    }
+
+    // Use the numerically stable form of the quadratic formula. If `a` or `q` is zero, the
+    // resulting infinities and NaNs fall outside (0, 1) and are discarded by the caller.
+    let q = -0.5 * (b + f32::copysign(f32::sqrt(discrim), b));
+    [Some(q / a), Some(c / q)]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::outline::ArcDirection;
+    use crate::segment::{self, Segment, SegmentFlags, SegmentKind};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_sample_and_tangent() {
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, 4.0));
+        assert_eq!(quadratic.sample(0.0), vec2f(0.0, 0.0));
+        assert_eq!(quadratic.sample(0.5), vec2f(2.0, 2.0));
+        assert_eq!(quadratic.sample(1.0), vec2f(4.0, 0.0));
+        assert_eq!(quadratic.tangent(0.0), vec2f(4.0, 8.0));
+        assert_eq!(quadratic.tangent(0.5), vec2f(4.0, 0.0));
+        assert_eq!(quadratic.tangent(1.0), vec2f(4.0, -8.0));
+        assert_eq!(quadratic.normal(0.5), vec2f(0.0, 4.0));
+
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                   LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)));
+        assert_eq!(cubic.sample(0.5), vec2f(5.0, 0.0));
+        assert_eq!(cubic.tangent(0.0), vec2f(10.0, 0.0));
+        assert_eq!(cubic.tangent(1.0), vec2f(10.0, 0.0));
+        assert_eq!(cubic.tangent(0.5), vec2f(15.0, 0.0));
+
+        let line = Segment::line(LineSegment2F::new(vec2f(1.0, 1.0), vec2f(3.0, 1.0)));
+        assert_eq!(line.sample(0.25), vec2f(1.5, 1.0));
+        assert_eq!(line.normal(0.25), vec2f(0.0, 2.0));
+    }
+
+    #[test]
+    fn test_derivative() {
+        let segments = [
+            Segment::line(LineSegment2F::new(vec2f(1.0, 1.0), vec2f(3.0, -2.0))),
+            Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                               vec2f(2.0, 4.0)),
+            Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 2.0)),
+                           LineSegment2F::new(vec2f(1.0, 6.0), vec2f(12.0, -5.0))),
+        ];
+        let expected_kinds = [SegmentKind::Line, SegmentKind::Line, SegmentKind::Quadratic];
+        for (segment, &expected_kind) in segments.iter().zip(expected_kinds.iter()) {
+            let derivative = segment.derivative();
+            assert_eq!(derivative.kind, expected_kind);
+            for step in 0..=10 {
+                let t = step as f32 / 10.0;
+                let (t0, t1) = ((t - 0.001).max(0.0), (t + 0.001).min(1.0));
+                let difference = (segment.sample(t1) - segment.sample(t0)) * (1.0 / (t1 - t0));
+                assert!((derivative.sample(t) - difference).length() < 0.1);
+                assert!((derivative.sample(t) - segment.derivative_at(t)).length() < 0.0001);
+            }
+        }
+        assert!(Segment::none().derivative().is_none());
+    }
+
+    #[test]
+    fn test_inflections() {
+        let s_curve = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(3.0, 0.0)),
+                                     LineSegment2F::new(vec2f(1.0, 1.0), vec2f(2.0, -1.0)));
+        let inflections = s_curve.inflections();
+        assert_eq!(inflections.len(), 1);
+        assert!((inflections[0] - 0.5).abs() < 0.0001);
+
+        let arch = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(3.0, 0.0)),
+                                  LineSegment2F::new(vec2f(1.0, 1.0), vec2f(2.0, 1.0)));
+        assert!(arch.inflections().is_empty());
+
+        // This cubic bends one way, then the other, then back again.
+        let wave = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                  LineSegment2F::new(vec2f(-2.0, -3.0), vec2f(-3.0, -3.0)));
+        let inflections = wave.inflections();
+        assert_eq!(inflections.len(), 2);
+        assert!(inflections[0] < inflections[1]);
+        for &t in &inflections {
+            let curvature = wave.derivative_at(t).det(wave.derivative().derivative_at(t));
+            assert!(curvature.abs() < 0.001);
+        }
+
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, 4.0));
+        assert!(quadratic.inflections().is_empty());
+        assert!(Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)))
+            .inflections()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_curvature() {
+        // The cubic approximation of a circular arc bends within 3% of the circle.
+        let arc = Segment::arc(FRAC_PI_2).transform(&Transform2F::from_scale(5.0));
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert!((arc.curvature(t) - 0.2).abs() < 0.006);
+            assert!((arc.radius_of_curvature(t) - 5.0).abs() < 0.15);
+        }
+        let reversed_arc = arc.reversed();
+        assert!((reversed_arc.curvature(0.5) + 0.2).abs() < 0.006);
+
+        let line = Segment::line(LineSegment2F::new(vec2f(1.0, 1.0), vec2f(3.0, -2.0)));
+        assert_eq!(line.curvature(0.5), 0.0);
+        assert_eq!(line.radius_of_curvature(0.5).abs(), f32::INFINITY);
+
+        let cusp = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(1.0, 0.0)),
+                                  LineSegment2F::new(vec2f(1.0, 1.0), vec2f(0.0, 1.0)));
+        assert_eq!(cusp.curvature(0.5), f32::INFINITY);
+        assert_eq!(cusp.radius_of_curvature(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_slice() {
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)));
+        let middle = line.slice(0.25, 0.75);
+        assert_eq!(middle.kind, SegmentKind::Line);
+        assert_eq!(middle.baseline, LineSegment2F::new(vec2f(2.5, 0.0), vec2f(7.5, 0.0)));
+        assert_eq!(line.slice(0.75, 0.25).baseline,
+                   LineSegment2F::new(vec2f(7.5, 0.0), vec2f(2.5, 0.0)));
+        assert_eq!(line.slice(-1.0, 2.0).baseline, line.baseline);
+
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, 4.0));
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 2.0)),
+                                   LineSegment2F::new(vec2f(1.0, 6.0), vec2f(12.0, -5.0)));
+        for segment in &[quadratic, cubic] {
+            let slice = segment.slice(0.3, 0.7);
+            assert_eq!(slice.kind, segment.kind);
+            for step in 0..=10 {
+                let t = step as f32 / 10.0;
+                let expected = segment.sample(0.3 + 0.4 * t);
+                assert!((slice.sample(t) - expected).length() < 0.0001);
+            }
+            let backward = segment.slice(0.7, 0.3);
+            assert!((backward.sample(0.0) - segment.sample(0.7)).length() < 0.0001);
+            assert!((backward.sample(1.0) - segment.sample(0.3)).length() < 0.0001);
+        }
+
+        let closing = line.slice(1.0, 1.0);
+        assert_eq!(closing.baseline, LineSegment2F::new(vec2f(10.0, 0.0), vec2f(10.0, 0.0)));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                   LineSegment2F::new(vec2f(0.0, 8.0), vec2f(10.0, 8.0)));
+        let bounds = cubic.bounds();
+        assert_eq!(bounds, RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 6.0)));
+        let endpoint_bounds = RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 0.0));
+        let control_bounds = RectF::from_points(vec2f(0.0, 0.0), vec2f(10.0, 8.0));
+        assert!(bounds.contains_rect(endpoint_bounds) && bounds != endpoint_bounds);
+        assert!(control_bounds.contains_rect(bounds) && bounds != control_bounds);
+
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, -4.0));
+        assert_eq!(quadratic.bounds(), RectF::from_points(vec2f(0.0, -2.0), vec2f(4.0, 0.0)));
+        let line = Segment::line(LineSegment2F::new(vec2f(3.0, 1.0), vec2f(1.0, 2.0)));
+        assert_eq!(line.bounds(), RectF::from_points(vec2f(1.0, 1.0), vec2f(3.0, 2.0)));
+    }
+
+    #[test]
+    fn test_reversed() {
+        let segments = [
+            Segment::line(LineSegment2F::new(vec2f(1.0, 1.0), vec2f(3.0, -2.0))),
+            Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                               vec2f(2.0, 4.0)),
+            Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 2.0)),
+                           LineSegment2F::new(vec2f(1.0, 6.0), vec2f(12.0, -5.0))),
+        ];
+        for segment in &segments {
+            let mut segment = *segment;
+            segment.flags = SegmentFlags::FIRST_IN_SUBPATH;
+            let reversed = segment.reversed();
+            assert_eq!(reversed.kind, segment.kind);
+            assert_eq!(reversed.flags, SegmentFlags::FIRST_IN_SUBPATH);
+            for step in 0..=10 {
+                let t = step as f32 / 10.0;
+                assert!((reversed.sample(t) - segment.sample(1.0 - t)).length() < 0.0001);
+            }
+            assert_eq!(reversed.reversed(), segment);
+        }
+    }
+
+    #[test]
+    fn test_is_degenerate() {
+        let point = vec2f(1.0, 1.0);
+        let nearby = vec2f(1.0, 1.0005);
+        assert!(Segment::line(LineSegment2F::new(point, point)).is_degenerate(0.0));
+        assert!(Segment::line(LineSegment2F::new(point, nearby)).is_degenerate(0.001));
+        assert!(!Segment::line(LineSegment2F::new(point, nearby)).is_degenerate(0.0001));
+        assert!(Segment::none().is_degenerate(0.0));
+
+        assert!(Segment::quadratic(LineSegment2F::new(point, nearby), point).is_degenerate(0.001));
+        let loop_ctrl = LineSegment2F::new(vec2f(3.0, 1.0), vec2f(3.0, 3.0));
+        assert!(!Segment::cubic(LineSegment2F::new(point, point), loop_ctrl).is_degenerate(0.001));
+        let ctrl = LineSegment2F::new(nearby, point);
+        assert!(Segment::cubic(LineSegment2F::new(point, point), ctrl).is_degenerate(0.001));
+    }
+
+    #[test]
+    fn test_arc_to_cubics() {
+        let (from, to, radii) = (vec2f(1.0, 0.0), vec2f(0.0, 1.0), vec2f(1.0, 1.0));
+        for &(direction, center) in &[(ArcDirection::CW, vec2f(0.0, 0.0)),
+                                      (ArcDirection::CCW, vec2f(1.0, 1.0))] {
+            let segments: Vec<_> =
+                segment::arc_to_cubics(from, to, radii, 0.0, false, direction).collect();
+            assert_eq!(segments.len(), 1);
+            assert!(segments[0].is_cubic());
+            assert!((segments[0].baseline.from() - from).length() < 0.0001);
+            assert!((segments[0].baseline.to() - to).length() < 0.0001);
+            for step in 0..=16 {
+                let point = segments[0].sample(step as f32 / 16.0);
+                assert!(((point - center).length() - 1.0).abs() < 0.001);
+            }
+        }
+
+        // A half circle takes two curves, and out-of-range radii are scaled up to fit.
+        let segments: Vec<_> = segment::arc_to_cubics(vec2f(-2.0, 0.0),
+                                                      vec2f(2.0, 0.0),
+                                                      vec2f(1.0, 1.0),
+                                                      0.0,
+                                                      false,
+                                                      ArcDirection::CW).collect();
+        assert_eq!(segments.len(), 2);
+        for segment in &segments {
+            for step in 0..=16 {
+                assert!((segment.sample(step as f32 / 16.0).length() - 2.0).abs() < 0.002);
+            }
+        }
+
+        let line: Vec<_> = segment::arc_to_cubics(from, to, vec2f(0.0, 1.0), 0.0, false,
+                                                  ArcDirection::CW).collect();
+        assert_eq!(line, vec![Segment::line(LineSegment2F::new(from, to))]);
+        assert_eq!(segment::arc_to_cubics(from, from, radii, 0.0, false, ArcDirection::CW).count(),
+                   0);
+    }
+
+    #[test]
+    fn test_nearest_t() {
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)));
+        assert_eq!(line.nearest_t(vec2f(3.0, 5.0)), 0.3);
+        assert_eq!(line.nearest_t(vec2f(-3.0, 5.0)), 0.0);
+        assert_eq!(line.nearest_t(vec2f(13.0, -5.0)), 1.0);
+
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, 4.0));
+        assert!((quadratic.nearest_t(vec2f(2.0, 10.0)) - 0.5).abs() < 0.001);
+        let t = quadratic.nearest_t(vec2f(0.0, 2.0));
+        let nearest = quadratic.sample(t);
+        // At the nearest point, the offset to the query is perpendicular to the curve.
+        assert!((vec2f(0.0, 2.0) - nearest).dot(quadratic.tangent(t).normalize()).abs() < 0.001);
+
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 2.0)),
+                                   LineSegment2F::new(vec2f(1.0, 6.0), vec2f(12.0, -5.0)));
+        for &query in &[vec2f(3.0, 5.0), vec2f(8.0, -4.0), vec2f(6.0, 0.5)] {
+            let t = cubic.nearest_t(query);
+            let offset = query - cubic.sample(t);
+            assert!(offset.dot(cubic.tangent(t).normalize()).abs() < 0.0001);
+            for step in 0..=100 {
+                let other = cubic.sample(step as f32 / 100.0);
+                assert!(offset.length() <= (query - other).length() + 0.0001);
+            }
+        }
+        assert_eq!(cubic.nearest_t(vec2f(-5.0, -5.0)), 0.0);
+    }
+
+    #[test]
+    fn test_intersect() {
+        let diagonal_0 = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(2.0, 2.0)));
+        let diagonal_1 = Segment::line(LineSegment2F::new(vec2f(0.0, 2.0), vec2f(2.0, 0.0)));
+        let intersections = diagonal_0.intersect(&diagonal_1);
+        assert_eq!(intersections.len(), 1);
+        assert!((intersections[0].0 - 0.5).abs() < 0.001);
+        assert!((intersections[0].1 - 0.5).abs() < 0.001);
+
+        let parallel = Segment::line(LineSegment2F::new(vec2f(1.0, 0.0), vec2f(3.0, 2.0)));
+        assert!(diagonal_0.intersect(&parallel).is_empty());
+        assert!(diagonal_0.intersect(&diagonal_0).is_empty());
+
+        // An S-shaped cubic crosses its own baseline three times.
+        let s_curve = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(3.0, 0.0)),
+                                     LineSegment2F::new(vec2f(1.0, 2.0), vec2f(2.0, -2.0)));
+        let axis = Segment::line(LineSegment2F::new(vec2f(-1.0, 0.0), vec2f(4.0, 0.0)));
+        let mut intersections = s_curve.intersect(&axis).to_vec();
+        intersections.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        assert_eq!(intersections.len(), 3);
+        for (&(t, u), &(expected_t, expected_u)) in
+                intersections.iter().zip([(0.0, 0.2), (0.5, 0.5), (1.0, 0.8)].iter()) {
+            assert!((t - expected_t).abs() < 0.01);
+            assert!((u - expected_u).abs() < 0.01);
+        }
+        for &(t, u) in &intersections {
+            assert!((s_curve.sample(t) - axis.sample(u)).length() < 0.01);
+        }
+
+        // Swapping the segments swaps the parameters.
+        let swapped = axis.intersect(&s_curve);
+        assert_eq!(swapped.len(), 3);
+
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(2.0, 4.0));
+        let flipped = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 2.0), vec2f(4.0, 2.0)),
+                                         vec2f(2.0, -2.0));
+        let intersections = quadratic.intersect(&flipped);
+        assert_eq!(intersections.len(), 2);
+        for &(t, u) in &intersections {
+            assert!((quadratic.sample(t) - flipped.sample(u)).length() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_curve_order_conversion() {
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(4.0, 0.0)),
+                                           vec2f(1.0, 4.0));
+        let cubic = quadratic.to_cubic();
+        assert_eq!(cubic.kind, SegmentKind::Cubic);
+        assert_eq!(cubic.baseline, quadratic.baseline);
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            assert!((cubic.sample(t) - quadratic.sample(t)).length() < 0.0001);
+        }
+        assert_eq!(cubic.to_cubic(), cubic);
+
+        let line = Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(3.0, 6.0)));
+        let cubic_line = line.to_cubic();
+        assert_eq!(cubic_line.kind, SegmentKind::Cubic);
+        assert_eq!(cubic_line.baseline, line.baseline);
+        assert_eq!(cubic_line.ctrl, LineSegment2F::new(vec2f(1.0, 2.0), vec2f(2.0, 4.0)));
+        assert_eq!(Segment::none().to_cubic(), Segment::none());
+
+        let quadratics: Vec<_> = quadratic.to_quadratics(0.01).collect();
+        assert_eq!(quadratics, vec![quadratic]);
+
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                   LineSegment2F::new(vec2f(0.0, 10.0), vec2f(20.0, -10.0)));
+        let tolerance = 0.01;
+        let quadratics: Vec<_> = cubic.to_quadratics(tolerance).collect();
+        assert!(quadratics.len() > 1);
+        assert_eq!(quadratics[0].baseline.from(), cubic.baseline.from());
+        assert!((quadratics[quadratics.len() - 1].baseline.to() - cubic.baseline.to()).length() <
+                0.001);
+        let piece_count = quadratics.len() as f32;
+        for (piece_index, piece) in quadratics.iter().enumerate() {
+            assert_eq!(piece.kind, SegmentKind::Quadratic);
+            for step in 0..=4 {
+                let t = step as f32 / 4.0;
+                let expected = cubic.sample((piece_index as f32 + t) / piece_count);
+                assert!((piece.sample(t) - expected).length() <= tolerance + 0.001);
+            }
+        }
+        for pair in quadratics.windows(2) {
+            assert_eq!(pair[0].baseline.to(), pair[1].baseline.from());
+        }
+    }
+
+    #[test]
+    fn test_split() {
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                   LineSegment2F::new(vec2f(0.0, 10.0), vec2f(10.0, 10.0)));
+        let (before, after) = cubic.split(0.5);
+        assert_eq!(before.kind, SegmentKind::Cubic);
+        assert_eq!(before.baseline.to(), after.baseline.from());
+        for step in 0..=10 {
+            let t = step as f32 / 10.0;
+            let expected_before = cubic.sample(t * 0.5);
+            let expected_after = cubic.sample(0.5 + t * 0.5);
+            assert!((before.sample(t) - expected_before).length() < 0.001);
+            assert!((after.sample(t) - expected_after).length() < 0.001);
+        }
+
+        let mut quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0),
+                                                                  vec2f(4.0, 0.0)),
+                                               vec2f(2.0, 4.0));
+        quadratic.flags = SegmentFlags::FIRST_IN_SUBPATH | SegmentFlags::CLOSES_SUBPATH;
+        let (before, after) = quadratic.split(0.25);
+        assert_eq!((before.kind, after.kind), (SegmentKind::Quadratic, SegmentKind::Quadratic));
+        assert_eq!(before.flags, SegmentFlags::FIRST_IN_SUBPATH);
+        assert_eq!(after.flags, SegmentFlags::CLOSES_SUBPATH);
+        assert!((before.baseline.to() - quadratic.sample(0.25)).length() < 0.001);
+        assert!((after.sample(0.5) - quadratic.sample(0.625)).length() < 0.001);
+
+        let line = Segment::line(LineSegment2F::new(vec2f(1.0, 1.0), vec2f(3.0, 5.0)));
+        let (before, after) = line.split(0.5);
+        assert_eq!(before.baseline, LineSegment2F::new(vec2f(1.0, 1.0), vec2f(2.0, 3.0)));
+        assert_eq!(after.baseline, LineSegment2F::new(vec2f(2.0, 3.0), vec2f(3.0, 5.0)));
+
+        for segment in &[line, quadratic, cubic] {
+            let (before, after) = segment.split(0.0);
+            assert_eq!(before.baseline.vector(), vec2f(0.0, 0.0));
+            assert_eq!(after.baseline, segment.baseline);
+            assert_eq!(after.ctrl, segment.ctrl);
+            let (before, after) = segment.split(1.0);
+            assert_eq!(before.baseline, segment.baseline);
+            assert_eq!(after.baseline.from(), segment.baseline.to());
+            assert_eq!(after.baseline.vector(), vec2f(0.0, 0.0));
+            assert!(!after.sample(0.5).x().is_nan());
+        }
+    }
+}
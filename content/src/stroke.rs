@@ -10,7 +10,7 @@
 
 //! Utilities for converting path strokes to fills.
 
-use crate::outline::{ArcDirection, Contour, ContourIterFlags, Outline, PushSegmentFlags};
+use crate::outline::{self, ArcDirection, Contour, ContourIterFlags, Outline, PushSegmentFlags};
 use crate::segment::Segment;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
@@ -43,6 +43,11 @@ pub enum LineCap {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineJoin {
+    /// Extends the edges until they meet, unless the miter would reach farther than the given
+    /// limit times half the line width from the corner, in which case the corner is beveled.
+    ///
+    /// This is the same as the miter limit of SVG and canvas, which compares the length of the
+    /// miter to the full line width.
     Miter(f32),
     Bevel,
     Round,
@@ -55,13 +60,27 @@ impl<'a> OutlineStrokeToFill<'a> {
     }
 
     pub fn offset(&mut self) {
+        self.stroke(false)
+    }
+
+    /// Like `offset()`, but splits every curve of the stroke at its vertical extrema, so that the
+    /// output is the same as calling `to_monotonic()` on that of `offset()`.
+    ///
+    /// Curves are split as they are emitted, including the arcs of round joins and caps, so no
+    /// intermediate outline is built and the stroked contours aren't walked a second time.
+    pub fn offset_monotonic(&mut self) {
+        self.stroke(true)
+    }
+
+    fn stroke(&mut self, monotonic: bool) {
         let mut new_contours = vec![];
         for input in &self.input.contours {
             let closed = input.closed;
             let mut stroker = ContourStrokeToFill::new(input,
                                                        Contour::new(),
                                                        self.style.line_width * 0.5,
-                                                       self.style.line_join);
+                                                       self.style.line_join,
+                                                       monotonic);
 
             stroker.offset_forward();
             if closed {
@@ -69,14 +88,15 @@ impl<'a> OutlineStrokeToFill<'a> {
                 stroker = ContourStrokeToFill::new(input,
                                                    Contour::new(),
                                                    self.style.line_width * 0.5,
-                                                   self.style.line_join);
+                                                   self.style.line_join,
+                                                   monotonic);
             } else {
-                self.add_cap(&mut stroker.output);
+                self.add_cap(&mut stroker.output, monotonic);
             }
 
             stroker.offset_backward();
             if !closed {
-                self.add_cap(&mut stroker.output);
+                self.add_cap(&mut stroker.output, monotonic);
             }
 
             self.push_stroked_contour(&mut new_contours, stroker, closed);
@@ -105,14 +125,15 @@ impl<'a> OutlineStrokeToFill<'a> {
             stroker.output.add_join(self.style.line_width * 0.5,
                                     self.style.line_join,
                                     stroker.input.position_of(0),
-                                    final_segment);
+                                    final_segment,
+                                    stroker.monotonic);
         }
 
         stroker.output.closed = true;
         new_contours.push(stroker.output);
     }
 
-    fn add_cap(&mut self, contour: &mut Contour) {
+    fn add_cap(&mut self, contour: &mut Contour, monotonic: bool) {
         if self.style.line_cap == LineCap::Butt || contour.len() < 2 {
             return
         }
@@ -134,30 +155,61 @@ impl<'a> OutlineStrokeToFill<'a> {
             p0_index -= 1;
         }
         let gradient = (p1 - p0).normalize();
+        push_cap(contour, p1, gradient, width, self.style.line_cap, monotonic);
+    }
+}
 
-        match self.style.line_cap {
-            LineCap::Butt => unreachable!(),
-
-            LineCap::Square => {
-                let offset = gradient * (width * 0.5);
+/// Returns the cap of a stroke that ends at `end` heading in `direction`, as a closed contour,
+/// so that the two ends of a stroke can be given different caps.
+///
+/// The contour runs from one side of the stroke, around the cap, to the other side, and is closed
+/// by a line across the end. Butt caps, and ends with no direction, have no area, so they produce
+/// an empty contour.
+pub fn cap_outline(end: Vector2F, direction: Vector2F, width: f32, cap: LineCap) -> Contour {
+    let mut contour = Contour::new();
+    if cap == LineCap::Butt || direction.square_length() <= EPSILON * EPSILON {
+        return contour;
+    }
 
-                let p2 = p1 + offset;
-                let p3 = p2 + gradient.yx() * vec2f(-width, width);
-                let p4 = p3 - offset;
+    let gradient = direction.normalize();
+    let start = end + vec2f(gradient.y(), -gradient.x()) * (width * 0.5);
+    contour.push_endpoint(start);
+    push_cap(&mut contour, start, gradient, width, cap, false);
+    contour.close();
+    contour
+}
 
-                contour.push_endpoint(p2);
-                contour.push_endpoint(p3);
-                contour.push_endpoint(p4);
-            }
+// Appends a cap to `contour`, which ends at `p1` on one side of a stroke of the given width that
+// heads in the direction `gradient`. If `monotonic` is true, round caps are split at their
+// vertical extrema.
+fn push_cap(contour: &mut Contour,
+            p1: Vector2F,
+            gradient: Vector2F,
+            width: f32,
+            cap: LineCap,
+            monotonic: bool) {
+    match cap {
+        LineCap::Butt => {}
+
+        LineCap::Square => {
+            let offset = gradient * (width * 0.5);
+
+            let p2 = p1 + offset;
+            let p3 = p2 + gradient.yx() * vec2f(-width, width);
+            let p4 = p3 - offset;
+
+            contour.push_endpoint(p2);
+            contour.push_endpoint(p3);
+            contour.push_endpoint(p4);
+        }
 
-            LineCap::Round => {
-                let scale = width * 0.5;
-                let offset = gradient.yx() * vec2f(-1.0, 1.0);
-                let translation = p1 + offset * (width * 0.5);
-                let transform = Transform2F::from_scale(scale).translate(translation);
-                let chord = LineSegment2F::new(-offset, offset);
-                contour.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
-            }
+        LineCap::Round => {
+            let scale = width * 0.5;
+            let offset = gradient.yx() * vec2f(-1.0, 1.0);
+            let translation = p1 + offset * (width * 0.5);
+            let transform = Transform2F::from_scale(scale).translate(translation);
+            let chord = LineSegment2F::new(-offset, offset);
+            contour.push_stroke_arc(&transform, chord, monotonic);
         }
     }
 }
@@ -167,12 +219,15 @@ struct ContourStrokeToFill<'a> {
     output: Contour,
     radius: f32,
     join: LineJoin,
+    // Whether curves are split at their vertical extrema as they are pushed onto `output`.
+    monotonic: bool,
 }
 
 impl<'a> ContourStrokeToFill<'a> {
     #[inline]
-    fn new(input: &Contour, output: Contour, radius: f32, join: LineJoin) -> ContourStrokeToFill {
-        ContourStrokeToFill { input, output, radius, join }
+    fn new(input: &Contour, output: Contour, radius: f32, join: LineJoin, monotonic: bool)
+           -> ContourStrokeToFill<'_> {
+        ContourStrokeToFill { input, output, radius, join, monotonic }
     }
 
     fn offset_forward(&mut self) {
@@ -180,7 +235,7 @@ impl<'a> ContourStrokeToFill<'a> {
             // FIXME(pcwalton): We negate the radius here so that round end caps can be drawn
             // clockwise. Of course, we should just implement anticlockwise arcs to begin with...
             let join = if segment_index == 0 { LineJoin::Bevel } else { self.join };
-            segment.offset(-self.radius, join, &mut self.output);
+            segment.offset(-self.radius, join, self.monotonic, &mut self.output);
         }
     }
 
@@ -195,33 +250,158 @@ impl<'a> ContourStrokeToFill<'a> {
             // FIXME(pcwalton): We negate the radius here so that round end caps can be drawn
             // clockwise. Of course, we should just implement anticlockwise arcs to begin with...
             let join = if segment_index == 0 { LineJoin::Bevel } else { self.join };
-            segment.offset(-self.radius, join, &mut self.output);
+            segment.offset(-self.radius, join, self.monotonic, &mut self.output);
+        }
+    }
+}
+
+// Moves a contour sideways by `distance`, to the right of the direction of travel in a y-down
+// coordinate system if positive and to the left if negative. So a positive distance shrinks a
+// clockwise contour, with positive area like those from `Contour::from_rect()`, and grows a
+// counterclockwise one; a negative distance does the opposite.
+pub(crate) struct ContourOffsetter<'a> {
+    input: &'a Contour,
+    distance: f32,
+    join: LineJoin,
+}
+
+impl<'a> ContourOffsetter<'a> {
+    #[inline]
+    pub(crate) fn new(input: &Contour, distance: f32, join: LineJoin) -> ContourOffsetter<'_> {
+        ContourOffsetter { input, distance, join }
+    }
+
+    pub(crate) fn offset(&self) -> Contour {
+        if self.distance == 0.0 {
+            return self.input.clone();
+        }
+
+        // Round joins are always drawn clockwise, which only suits the corners that open up when
+        // offsetting to the right. So offset the reversed contour to the right instead of
+        // offsetting to the left.
+        let mut flattened = Contour::new();
+        self.input.flatten_into(TOLERANCE, &mut flattened);
+        if self.distance > 0.0 {
+            flattened.reverse();
+        }
+        let distance = -self.distance.abs();
+
+        let mut points: Vec<Vector2F> = Vec::with_capacity(flattened.len() as usize);
+        for &point in &flattened.points {
+            match points.last() {
+                Some(&last) if (point - last).square_length() < EPSILON * EPSILON => {}
+                _ => points.push(point),
+            }
+        }
+        let closed = flattened.closed;
+        if closed && points.len() > 2 {
+            let (first, last) = (points[0], points[points.len() - 1]);
+            if (first - last).square_length() < EPSILON * EPSILON {
+                points.pop();
+            }
+        }
+
+        let mut output = Contour::with_capacity(points.len());
+        if points.len() < 2 {
+            points.iter().for_each(|&point| output.push_endpoint(point));
+            output.closed = closed;
+            return output;
+        }
+
+        let edge_count = if closed { points.len() } else { points.len() - 1 };
+        let edges: Vec<LineSegment2F> = (0..edge_count).map(|edge_index| {
+            let to = points[(edge_index + 1) % points.len()];
+            LineSegment2F::new(points[edge_index], to).offset(distance)
+        }).collect();
+
+        let last_edge = edges[edges.len() - 1];
+        match trim_offset_corner(last_edge, edges[0]) {
+            Some(corner) if closed => output.push_endpoint(corner),
+            _ => output.push_endpoint(edges[0].from()),
+        }
+        for (vertex_index, pair) in edges.windows(2).enumerate() {
+            let vertex = points[vertex_index + 1];
+            self.push_corner(&mut output, distance, vertex, pair[0], pair[1], true);
+        }
+        if closed {
+            self.push_corner(&mut output, distance, points[0], last_edge, edges[0], false);
+            output.closed = true;
+        } else {
+            output.push_endpoint(last_edge.to());
+        }
+
+        if self.distance > 0.0 {
+            output.reverse();
+        }
+        output
+    }
+
+    // Connects two consecutive offset edges. Where the edges overlap, both are trimmed back to
+    // where they cross, which removes the small loop that offsetting would otherwise leave behind.
+    // Where they separate, the gap is filled with the requested join.
+    fn push_corner(&self,
+                   output: &mut Contour,
+                   distance: f32,
+                   vertex: Vector2F,
+                   prev_edge: LineSegment2F,
+                   next_edge: LineSegment2F,
+                   push_next_from: bool) {
+        if let Some(corner) = trim_offset_corner(prev_edge, next_edge) {
+            if push_next_from {
+                output.push_endpoint(corner);
+            }
+            return;
+        }
+
+        output.push_endpoint(prev_edge.to());
+        if prev_edge.vector().det(next_edge.vector()) > 0.0 && output.might_need_join(self.join) {
+            let next_tangent = LineSegment2F::new(next_edge.to(), next_edge.from());
+            output.add_join(distance, self.join, vertex, next_tangent, false);
+        }
+        if push_next_from && (next_edge.from() - prev_edge.to()).square_length() >= EPSILON {
+            output.push_endpoint(next_edge.from());
         }
     }
 }
 
+// Returns the point where two consecutive edges offset to the right cross, if they turn left and
+// overlap.
+fn trim_offset_corner(prev_edge: LineSegment2F, next_edge: LineSegment2F) -> Option<Vector2F> {
+    if prev_edge.vector().det(next_edge.vector()) >= 0.0 {
+        return None;
+    }
+    let (prev_t, next_t) = (prev_edge.intersection_t(next_edge)?,
+                            next_edge.intersection_t(prev_edge)?);
+    if (0.0..=1.0).contains(&prev_t) && (0.0..=1.0).contains(&next_t) {
+        Some(prev_edge.sample(prev_t))
+    } else {
+        None
+    }
+}
+
 trait Offset {
-    fn offset(&self, distance: f32, join: LineJoin, contour: &mut Contour);
+    fn offset(&self, distance: f32, join: LineJoin, monotonic: bool, contour: &mut Contour);
     fn add_to_contour(&self,
                       distance: f32,
                       join: LineJoin,
                       join_point: Vector2F,
+                      monotonic: bool,
                       contour: &mut Contour);
     fn offset_once(&self, distance: f32) -> Self;
     fn error_is_within_tolerance(&self, other: &Segment, distance: f32) -> bool;
 }
 
 impl Offset for Segment {
-    fn offset(&self, distance: f32, join: LineJoin, contour: &mut Contour) {
+    fn offset(&self, distance: f32, join: LineJoin, monotonic: bool, contour: &mut Contour) {
         let join_point = self.baseline.from();
         if self.baseline.square_length() < TOLERANCE * TOLERANCE {
-            self.add_to_contour(distance, join, join_point, contour);
+            self.add_to_contour(distance, join, join_point, monotonic, contour);
             return;
         }
 
         let candidate = self.offset_once(distance);
         if self.error_is_within_tolerance(&candidate, distance) {
-            candidate.add_to_contour(distance, join, join_point, contour);
+            candidate.add_to_contour(distance, join, join_point, monotonic, contour);
             return;
         }
 
@@ -229,14 +409,15 @@ impl Offset for Segment {
         debug!("... PRE-SPLIT: {:?}", self);
         let (before, after) = self.split(0.5);
         debug!("... AFTER-SPLIT: {:?} {:?}", before, after);
-        before.offset(distance, join, contour);
-        after.offset(distance, join, contour);
+        before.offset(distance, join, monotonic, contour);
+        after.offset(distance, join, monotonic, contour);
     }
 
     fn add_to_contour(&self,
                       distance: f32,
                       join: LineJoin,
                       join_point: Vector2F,
+                      monotonic: bool,
                       contour: &mut Contour) {
         // Add join if necessary.
         if contour.might_need_join(join) {
@@ -249,12 +430,13 @@ impl Offset for Segment {
                 self.ctrl.from()
             };
 
-            contour.add_join(distance, join, join_point, LineSegment2F::new(p4, p3));
+            let next_tangent = LineSegment2F::new(p4, p3);
+            contour.add_join(distance, join, join_point, next_tangent, monotonic);
         }
 
         // Push segment.
         let flags = PushSegmentFlags::UPDATE_BOUNDS | PushSegmentFlags::INCLUDE_FROM_POINT;
-        contour.push_segment(self, flags);
+        contour.push_stroke_segment(self, flags, monotonic);
     }
 
     fn offset_once(&self, distance: f32) -> Segment {
@@ -371,7 +553,8 @@ impl Contour {
                 distance: f32,
                 join: LineJoin,
                 join_point: Vector2F,
-                next_tangent: LineSegment2F) {
+                next_tangent: LineSegment2F,
+                monotonic: bool) {
         let (p0, p1) = (self.position_of_last(2), self.position_of_last(1));
         let prev_tangent = LineSegment2F::new(p0, p1);
 
@@ -400,10 +583,44 @@ impl Contour {
                 let chord_from = (prev_tangent.to() - join_point).normalize();
                 let chord_to = (next_tangent.to() - join_point).normalize();
                 let chord = LineSegment2F::new(chord_from, chord_to);
-                self.push_arc_from_unit_chord(&transform, chord, ArcDirection::CW);
+                self.push_stroke_arc(&transform, chord, monotonic);
             }
         }
     }
+
+    // Pushes a piece of a stroke. If `monotonic` is true, curves are first split at their vertical
+    // extrema, into the same cubics that `Contour::to_monotonic()` would produce.
+    fn push_stroke_segment(&mut self,
+                           segment: &Segment,
+                           mut flags: PushSegmentFlags,
+                           monotonic: bool) {
+        if !monotonic || segment.is_none() || segment.is_line() {
+            self.push_segment(segment, flags);
+            return;
+        }
+
+        for piece in outline::split_cubic_at_y_extrema(&segment.to_cubic()) {
+            self.push_segment(&piece, flags);
+            flags.remove(PushSegmentFlags::INCLUDE_FROM_POINT);
+        }
+    }
+
+    // Pushes the arc of a round join or cap, splitting it like `push_stroke_segment()` does if
+    // `monotonic` is true.
+    fn push_stroke_arc(&mut self, transform: &Transform2F, chord: LineSegment2F, monotonic: bool) {
+        if !monotonic {
+            self.push_arc_from_unit_chord(transform, chord, ArcDirection::CW);
+            return;
+        }
+
+        let mut arc = Contour::new();
+        arc.push_arc_from_unit_chord(transform, chord, ArcDirection::CW);
+        let mut flags = PushSegmentFlags::UPDATE_BOUNDS | PushSegmentFlags::INCLUDE_FROM_POINT;
+        for segment in arc.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            self.push_stroke_segment(&segment, flags, true);
+            flags.remove(PushSegmentFlags::INCLUDE_FROM_POINT);
+        }
+    }
 }
 
 impl Default for StrokeStyle {
@@ -426,3 +643,69 @@ impl Default for LineJoin {
     #[inline]
     fn default() -> LineJoin { LineJoin::Miter(10.0) }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle, cap_outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::util::EPSILON;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_cap_outline() {
+        let (end, direction) = (vec2f(10.0, 0.0), vec2f(2.0, 0.0));
+        let round = cap_outline(end, direction, 4.0, LineCap::Round);
+        assert!(round.is_closed());
+        assert_eq!(round.position_of(0), vec2f(10.0, -2.0));
+        assert!(round.last_position().unwrap().approx_eq(vec2f(10.0, 2.0), EPSILON));
+        assert!(round.tight_bounds().origin().approx_eq(vec2f(10.0, -2.0), EPSILON));
+        assert!(round.tight_bounds().lower_right().approx_eq(vec2f(12.0, 2.0), EPSILON));
+        assert!((round.area().abs() - 2.0 * PI).abs() < 0.01);
+
+        let square = cap_outline(end, direction, 4.0, LineCap::Square);
+        assert_eq!(square.points(), &[
+            vec2f(10.0, -2.0), vec2f(12.0, -2.0), vec2f(12.0, 2.0), vec2f(10.0, 2.0),
+        ]);
+
+        // Caps follow the direction of the stroke.
+        let upward = cap_outline(vec2f(0.0, 0.0), vec2f(0.0, -1.0), 2.0, LineCap::Square);
+        assert_eq!(upward.bounds(), RectF::from_points(vec2f(-1.0, -1.0), vec2f(1.0, 0.0)));
+
+        assert!(cap_outline(end, direction, 4.0, LineCap::Butt).is_empty());
+        assert!(cap_outline(end, Vector2F::zero(), 4.0, LineCap::Round).is_empty());
+    }
+
+    #[test]
+    fn test_miter_limit() {
+        // A corner this sharp has a miter about 10 times as long as the line is wide.
+        let mut spike = Contour::new();
+        spike.push_endpoint(vec2f(0.0, 0.0));
+        spike.push_endpoint(vec2f(10.0, 1.0));
+        spike.push_endpoint(vec2f(0.0, 2.0));
+        let mut outline = Outline::new();
+        outline.push_contour(spike);
+        let spike = outline;
+
+        let stroke = |line_join| {
+            let style = StrokeStyle { line_width: 1.0, line_cap: LineCap::Butt, line_join };
+            let mut stroker = OutlineStrokeToFill::new(&spike, style);
+            stroker.offset();
+            stroker.into_outline().bounds()
+        };
+        assert!(stroke(LineJoin::Miter(11.0)).max_x() > 14.5);
+        let beveled = stroke(LineJoin::Miter(4.0));
+        assert!(beveled.max_x() > 10.0 && beveled.max_x() < 10.5);
+        assert_eq!(beveled, stroke(LineJoin::Bevel));
+
+        // Offsetting outward from a sharp corner is limited in the same way.
+        let mut wedge = Contour::new();
+        wedge.push_endpoint(vec2f(0.0, 0.0));
+        wedge.push_endpoint(vec2f(0.0, 2.0));
+        wedge.push_endpoint(vec2f(10.0, 1.0));
+        wedge.close();
+        assert!(wedge.offset(0.5, LineJoin::Miter(11.0)).bounds().max_x() > 14.5);
+        assert!(wedge.offset(0.5, LineJoin::Miter(4.0)).bounds().max_x() < 10.5);
+    }
+}
@@ -0,0 +1,195 @@
+// pathfinder/content/src/triangulate.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Triangulation of filled outlines by ear clipping.
+
+use crate::outline::Outline;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2F;
+
+// Triangulates an outline made of simple, non-crossing line contours, as produced by the boolean
+// operations: outer contours have positive signed area and holes have negative signed area.
+//
+// Each hole is joined to the contour around it with a pair of coincident bridge edges, which turns
+// every outer contour and its holes into a single weakly simple polygon. Ears are then clipped off
+// one at a time. This is cubic in the number of points in the worst case.
+pub(crate) fn triangulate_simple_outline(outline: &Outline) -> Vec<[Vector2F; 3]> {
+    let mut outers = vec![];
+    let mut holes = vec![];
+    for contour in outline.contours() {
+        let points: Vec<Vector2F> = (0..contour.len()).map(|index| {
+            contour.position_of(index)
+        }).collect();
+        if points.len() < 3 {
+            continue;
+        }
+        let area = signed_area(&points);
+        if area > 0.0 {
+            outers.push((points, area));
+        } else if area < 0.0 {
+            holes.push(points);
+        }
+    }
+
+    // Assign each hole to the smallest outer contour that contains it.
+    let mut holes_of_outers: Vec<Vec<Vec<Vector2F>>> = vec![vec![]; outers.len()];
+    for hole in holes {
+        let sample_point = hole[0];
+        let mut best: Option<(usize, f32)> = None;
+        for (outer_index, &(ref outer, area)) in outers.iter().enumerate() {
+            if !polygon_contains_point(outer, sample_point) {
+                continue;
+            }
+            match best {
+                Some((_, best_area)) if best_area <= area => {}
+                _ => best = Some((outer_index, area)),
+            }
+        }
+        if let Some((outer_index, _)) = best {
+            holes_of_outers[outer_index].push(hole);
+        }
+    }
+
+    let mut triangles = vec![];
+    for ((outer, _), holes) in outers.into_iter().zip(holes_of_outers) {
+        let polygon = bridge_holes(outer, holes);
+        clip_ears(polygon, &mut triangles);
+    }
+    triangles
+}
+
+fn bridge_holes(mut polygon: Vec<Vector2F>, mut holes: Vec<Vec<Vector2F>>) -> Vec<Vector2F> {
+    // Bridge the holes reaching farthest right first, so that later bridges can't be blocked by
+    // holes that haven't been merged yet on that side.
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+
+    for hole_index in 0..holes.len() {
+        let hole = &holes[hole_index];
+        let hole_start = (0..hole.len()).max_by(|&a, &b| {
+            hole[a].x().total_cmp(&hole[b].x())
+        }).unwrap();
+        let from = hole[hole_start];
+
+        // Pick the closest polygon vertex that can be reached without crossing anything.
+        let mut candidates: Vec<usize> = (0..polygon.len()).collect();
+        candidates.sort_by(|&a, &b| {
+            let (distance_a, distance_b) = ((polygon[a] - from).square_length(),
+                                            (polygon[b] - from).square_length());
+            distance_a.total_cmp(&distance_b)
+        });
+        let bridge_end = candidates.into_iter().find(|&candidate| {
+            let bridge = LineSegment2F::new(from, polygon[candidate]);
+            !polygon_crosses_segment(&polygon, bridge) &&
+                holes[hole_index..].iter().all(|hole| !polygon_crosses_segment(hole, bridge))
+        });
+        let bridge_end = match bridge_end {
+            Some(bridge_end) => bridge_end,
+            None => continue,
+        };
+
+        // Walk out along the bridge, around the hole, and back.
+        let mut merged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+        merged.extend_from_slice(&polygon[..=bridge_end]);
+        for offset in 0..=hole.len() {
+            merged.push(hole[(hole_start + offset) % hole.len()]);
+        }
+        merged.extend_from_slice(&polygon[bridge_end..]);
+        polygon = merged;
+    }
+
+    polygon
+}
+
+fn clip_ears(mut polygon: Vec<Vector2F>, triangles: &mut Vec<[Vector2F; 3]>) {
+    let mut vertex_index = 0;
+    let mut attempts = 0;
+    while polygon.len() > 3 {
+        let count = polygon.len();
+        let prev = polygon[(vertex_index + count - 1) % count];
+        let current = polygon[vertex_index % count];
+        let next = polygon[(vertex_index + 1) % count];
+
+        // If no ear can be found, the polygon is degenerate; clip anyway rather than loop.
+        let is_ear = attempts >= count || is_ear(&polygon, prev, current, next);
+        if is_ear {
+            if (current - prev).det(next - current) != 0.0 {
+                triangles.push([prev, current, next]);
+            }
+            polygon.remove(vertex_index % count);
+            attempts = 0;
+            vertex_index %= polygon.len();
+        } else {
+            vertex_index = (vertex_index + 1) % count;
+            attempts += 1;
+        }
+    }
+
+    if (polygon[1] - polygon[0]).det(polygon[2] - polygon[1]) != 0.0 {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+}
+
+fn is_ear(polygon: &[Vector2F], prev: Vector2F, current: Vector2F, next: Vector2F) -> bool {
+    // Outer contours have positive area, so convex vertices turn the same way.
+    if (current - prev).det(next - current) <= 0.0 {
+        return false;
+    }
+    polygon.iter().all(|&point| {
+        point == prev || point == current || point == next ||
+            !triangle_contains_point(prev, current, next, point)
+    })
+}
+
+fn triangle_contains_point(a: Vector2F, b: Vector2F, c: Vector2F, point: Vector2F) -> bool {
+    (b - a).det(point - a) >= 0.0 && (c - b).det(point - b) >= 0.0 &&
+        (a - c).det(point - c) >= 0.0
+}
+
+fn signed_area(points: &[Vector2F]) -> f32 {
+    let mut double_area = 0.0;
+    for (index, &point) in points.iter().enumerate() {
+        double_area += point.det(points[(index + 1) % points.len()]);
+    }
+    double_area * 0.5
+}
+
+fn polygon_contains_point(polygon: &[Vector2F], point: Vector2F) -> bool {
+    let mut inside = false;
+    for (index, &from) in polygon.iter().enumerate() {
+        let to = polygon[(index + 1) % polygon.len()];
+        if (from.y() <= point.y()) != (to.y() <= point.y()) {
+            let x = from.x() + (point.y() - from.y()) / (to.y() - from.y()) * (to.x() - from.x());
+            if point.x() < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// Returns true if the segment properly crosses an edge of the polygon. Touching at endpoints
+// doesn't count, since bridges end on existing vertices.
+fn polygon_crosses_segment(polygon: &[Vector2F], segment: LineSegment2F) -> bool {
+    (0..polygon.len()).any(|index| {
+        let edge = LineSegment2F::new(polygon[index], polygon[(index + 1) % polygon.len()]);
+        segments_cross(edge, segment)
+    })
+}
+
+fn segments_cross(a: LineSegment2F, b: LineSegment2F) -> bool {
+    let side = |line: LineSegment2F, point: Vector2F| line.vector().det(point - line.from());
+    let (b_from_side, b_to_side) = (side(a, b.from()), side(a, b.to()));
+    let (a_from_side, a_to_side) = (side(b, a.from()), side(b, a.to()));
+    b_from_side * b_to_side < 0.0 && a_from_side * a_to_side < 0.0
+}
+
+fn max_x(points: &[Vector2F]) -> f32 {
+    points.iter().map(|point| point.x()).fold(f32::NEG_INFINITY, f32::max)
+}
@@ -16,6 +16,9 @@ use crate::util;
 use pathfinder_simd::default::F32x4;
 use std::ops::{Add, Mul, MulAssign, Sub};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub struct LineSegment2F(pub F32x4);
 
@@ -286,6 +289,23 @@ impl MulAssign<Vector2F> for LineSegment2F {
     }
 }
 
+/// Serialized as a `(from, to)` pair of points.
+#[cfg(feature = "serde")]
+impl Serialize for LineSegment2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.from(), self.to()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LineSegment2F {
+    fn deserialize<D>(deserializer: D) -> Result<LineSegment2F, D::Error>
+                      where D: Deserializer<'de> {
+        let (from, to) = <(Vector2F, Vector2F)>::deserialize(deserializer)?;
+        Ok(LineSegment2F::new(from, to))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct LineSegmentU4 {
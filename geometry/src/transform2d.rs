@@ -174,6 +174,8 @@ impl Transform2F {
         Transform2F { matrix: Matrix2x2F::default(), vector }
     }
 
+    /// Creates the product of a scale, a rotation, and a translation, in that order, so that the
+    /// transform translates points, then rotates them, then scales them.
     #[inline]
     pub fn from_scale_rotation_translation<S>(scale: S, theta: f32, translation: Vector2F)
                                               -> Transform2F where S: IntoVector2F {
@@ -183,6 +185,20 @@ impl Transform2F {
         Transform2F::from_scale(scale) * rotation * translation
     }
 
+    /// Creates the product of a translation, a rotation, and a scale, in that order, so that the
+    /// transform scales points, then rotates them, then translates them.
+    ///
+    /// This is the usual way to place an object, and the inverse of `decompose()` for transforms
+    /// without skew.
+    #[inline]
+    pub fn from_translation_rotation_scale<S>(translation: Vector2F, theta: f32, scale: S)
+                                              -> Transform2F where S: IntoVector2F {
+        let scale = scale.into_vector_2f();
+        let rotation = Transform2F::from_rotation(theta);
+        let translation = Transform2F::from_translation(translation);
+        translation * rotation * Transform2F::from_scale(scale)
+    }
+
     #[inline]
     pub fn row_major(m11: f32, m12: f32, m21: f32, m22: f32, m31: f32, m32: f32) -> Transform2F {
         Transform2F {
@@ -250,6 +266,18 @@ impl Transform2F {
         self.vector.y()
     }
 
+    /// Returns `self * other`: the transform that applies `other` and then `self`.
+    #[inline]
+    pub fn post_mul(&self, other: &Transform2F) -> Transform2F {
+        *self * *other
+    }
+
+    /// Returns `other * self`: the transform that applies `self` and then `other`.
+    #[inline]
+    pub fn pre_mul(&self, other: &Transform2F) -> Transform2F {
+        *other * *self
+    }
+
     #[inline]
     pub fn translate(&self, vector: Vector2F) -> Transform2F {
         Transform2F::from_translation(vector) * *self
@@ -296,6 +324,56 @@ impl Transform2F {
         let vector_inv = -(matrix_inv * self.vector);
         Transform2F { matrix: matrix_inv, vector: vector_inv }
     }
+
+    /// Splits this transform into a scale, followed by a skew, a rotation, and a translation.
+    ///
+    /// Unlike `rotation()` and `scale_factor()`, this handles non-uniform scales and skews. A
+    /// reflection shows up as a negative y scale. If this transform collapses the x axis, the
+    /// rotation and skew are zero and the y scale is the length of the transformed y axis.
+    pub fn decompose(&self) -> TransformComponents {
+        let (x_axis, y_axis) = (vec2f(self.m11(), self.m21()), vec2f(self.m12(), self.m22()));
+        let scale_x = x_axis.length();
+        if scale_x == 0.0 {
+            return TransformComponents {
+                translation: self.vector,
+                rotation: 0.0,
+                skew: 0.0,
+                scale: vec2f(0.0, y_axis.length()),
+            };
+        }
+
+        let det = self.matrix.det();
+        let scale_y = det / scale_x;
+        let skew = if det == 0.0 { 0.0 } else { f32::atan(x_axis.dot(y_axis) / det) };
+        TransformComponents {
+            translation: self.vector,
+            rotation: f32::atan2(x_axis.y(), x_axis.x()),
+            skew,
+            scale: vec2f(scale_x, scale_y),
+        }
+    }
+}
+
+/// The parts of an affine transform, as returned by `Transform2F::decompose()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TransformComponents {
+    pub translation: Vector2F,
+    /// The rotation angle, in radians.
+    pub rotation: f32,
+    /// The angle, in radians, by which the y axis is tilted toward the x axis before rotating.
+    pub skew: f32,
+    pub scale: Vector2F,
+}
+
+impl TransformComponents {
+    /// Puts the parts back together into a transform.
+    pub fn to_transform(&self) -> Transform2F {
+        let skew = Transform2F::row_major(1.0, self.skew.tan(), 0.0, 1.0, 0.0, 0.0);
+        Transform2F::from_translation(self.translation) *
+            Transform2F::from_rotation(self.rotation) *
+            skew *
+            Transform2F::from_scale(self.scale)
+    }
 }
 
 impl Mul<Transform2F> for Transform2F {
@@ -343,3 +421,70 @@ impl MulAssign for Transform2F {
         *self = *self * other
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::transform2d::Transform2F;
+    use crate::vector::vec2f;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_pre_mul_and_post_mul() {
+        let scale = Transform2F::from_scale(vec2f(2.0, 3.0));
+        let translation = Transform2F::from_translation(vec2f(1.0, 1.0));
+        let point = vec2f(1.0, 1.0);
+        assert_eq!(scale.post_mul(&translation) * point, vec2f(4.0, 6.0));
+        assert_eq!(scale.pre_mul(&translation) * point, vec2f(3.0, 4.0));
+        assert_eq!(scale.post_mul(&translation), scale * translation);
+        assert_eq!(scale.pre_mul(&translation), translation * scale);
+    }
+
+    #[test]
+    fn test_from_scale_rotation_translation() {
+        let translation = vec2f(10.0, 20.0);
+        let transform = Transform2F::from_scale_rotation_translation(2.0, FRAC_PI_2, translation);
+        let point = transform * vec2f(1.0, 0.0);
+        assert!((point - vec2f(-40.0, 22.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_from_translation_rotation_scale() {
+        let translation = vec2f(10.0, 20.0);
+        let transform = Transform2F::from_translation_rotation_scale(translation, FRAC_PI_2, 2.0);
+        let point = transform * vec2f(1.0, 0.0);
+        assert!((point - vec2f(10.0, 22.0)).length() < 0.0001);
+        assert_eq!(transform.translation(), translation);
+        assert!((transform.rotation() - FRAC_PI_2).abs() < 0.0001);
+        assert!((transform.scale_factor() - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let translation = vec2f(10.0, -20.0);
+        let transform = Transform2F::from_translation_rotation_scale(translation,
+                                                                     0.5,
+                                                                     vec2f(2.0, 3.0));
+        let components = transform.decompose();
+        assert_eq!(components.translation, translation);
+        assert!((components.rotation - 0.5).abs() < 0.0001);
+        assert!(components.skew.abs() < 0.0001);
+        assert!((components.scale - vec2f(2.0, 3.0)).length() < 0.0001);
+
+        // Skews and reflections survive a round trip.
+        let skew = Transform2F::row_major(1.0, 0.7, 0.0, 1.0, 0.0, 0.0);
+        let transform = Transform2F::from_rotation(-FRAC_PI_2) * skew *
+            Transform2F::from_scale(vec2f(4.0, -0.5));
+        let components = transform.decompose();
+        assert!((components.rotation + FRAC_PI_2).abs() < 0.0001);
+        assert!((components.skew - f32::atan(0.7)).abs() < 0.0001);
+        assert!((components.scale - vec2f(4.0, -0.5)).length() < 0.0001);
+        let recomposed = components.to_transform();
+        for &point in &[vec2f(1.0, 0.0), vec2f(0.0, 1.0), vec2f(-3.0, 2.0)] {
+            assert!((recomposed * point - transform * point).length() < 0.0001);
+        }
+
+        let collapsed = Transform2F::row_major(0.0, 0.0, 0.0, 5.0, 1.0, 2.0).decompose();
+        assert_eq!(collapsed.scale, vec2f(0.0, 5.0));
+        assert_eq!(collapsed.translation, vec2f(1.0, 2.0));
+    }
+}
@@ -389,6 +389,68 @@ impl Perspective {
             window_size,
         }
     }
+
+    /// Returns the perspective that maps window coordinates produced by this one back to the
+    /// original points, or `None` if this perspective collapses the z = 0 plane or the window is
+    /// empty.
+    ///
+    /// Only points in the z = 0 plane can be recovered, since those are the only ones that this
+    /// perspective is ever applied to. The inverse always has a 2×2 window.
+    pub fn inverse(&self) -> Option<Perspective> {
+        let window_size = self.window_size.to_f32();
+        if window_size.x() == 0.0 || window_size.y() == 0.0 {
+            return None;
+        }
+
+        // The homography that this perspective applies to (x, y, 1), ignoring the z row.
+        let (c0, c1, c3) = (self.transform.c0, self.transform.c1, self.transform.c3);
+        let projection = [[c0[0], c1[0], c3[0]], [c0[1], c1[1], c3[1]], [c0[3], c1[3], c3[3]]];
+        let projection_inverse = invert_3x3(&projection)?;
+
+        // Undo the window mapping on the way in, and cancel the 2×2 window of the inverse on the
+        // way out.
+        let to_ndc = [
+            [2.0 / window_size.x(), 0.0, -1.0],
+            [0.0, -2.0 / window_size.y(), 1.0],
+            [0.0, 0.0, 1.0],
+        ];
+        let from_inverse_window = [[1.0, 0.0, -1.0], [0.0, -1.0, 1.0], [0.0, 0.0, 1.0]];
+        let m = mul_3x3(&from_inverse_window, &mul_3x3(&projection_inverse, &to_ndc));
+        let transform = Transform4F::row_major(
+            m[0][0], m[0][1], 0.0, m[0][2], m[1][0], m[1][1], 0.0, m[1][2], 0.0, 0.0, 1.0, 0.0,
+            m[2][0], m[2][1], 0.0, m[2][2],
+        );
+        Some(Perspective::new(&transform, Vector2I::splat(2)))
+    }
+}
+
+fn invert_3x3(m: &[[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let cofactor = |row: usize, column: usize| {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((column + 1) % 3, (column + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let det = m[0][0] * cofactor(0, 0) + m[0][1] * cofactor(0, 1) + m[0][2] * cofactor(0, 2);
+    if det == 0.0 || !det.is_finite() {
+        return None;
+    }
+    let mut inverse = [[0.0; 3]; 3];
+    for (row, inverse_row) in inverse.iter_mut().enumerate() {
+        for (column, value) in inverse_row.iter_mut().enumerate() {
+            *value = cofactor(column, row) / det;
+        }
+    }
+    Some(inverse)
+}
+
+fn mul_3x3(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut product = [[0.0; 3]; 3];
+    for (row, product_row) in product.iter_mut().enumerate() {
+        for (column, value) in product_row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[row][k] * b[k][column]).sum();
+        }
+    }
+    product
 }
 
 impl Mul<Transform4F> for Perspective {
@@ -425,8 +487,8 @@ impl Mul<RectF> for Perspective {
 
 #[cfg(test)]
 mod test {
-    use crate::vector::Vector4F;
-    use crate::transform3d::Transform4F;
+    use crate::vector::{Vector2F, Vector2I, Vector4F};
+    use crate::transform3d::{Perspective, Transform4F};
 
     #[test]
     fn test_post_mul() {
@@ -501,4 +563,22 @@ mod test {
         let p2 = m_inv * p1;
         assert!(p0.approx_eq(p2, 0.0001));
     }
+
+    #[test]
+    fn test_perspective_inverse() {
+        let transform = Transform4F::from_perspective(1.0, 1.5, 0.1, 100.0) *
+            Transform4F::from_translation(Vector4F::new(-1.0, 0.5, -5.0, 1.0)) *
+            Transform4F::from_rotation(0.4, 0.3, 0.2);
+        let perspective = Perspective::new(&transform, Vector2I::new(640, 480));
+        let inverse = perspective.inverse().unwrap();
+        let points = [Vector2F::new(0.0, 0.0), Vector2F::new(1.0, -2.0), Vector2F::new(3.0, 4.0)];
+        for &point in &points {
+            let round_trip = inverse * (perspective * point);
+            assert!((round_trip - point).length() < 0.001);
+        }
+
+        let flat = Transform4F::from_scale(Vector4F::new(1.0, 0.0, 1.0, 1.0));
+        assert!(Perspective::new(&flat, Vector2I::new(640, 480)).inverse().is_none());
+        assert!(Perspective::new(&transform, Vector2I::new(0, 480)).inverse().is_none());
+    }
 }
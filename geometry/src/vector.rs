@@ -14,6 +14,9 @@ use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::hash::{Hash, Hasher};
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// 2D points with 32-bit floating point coordinates.
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Vector2F(pub F32x2);
@@ -145,6 +148,12 @@ impl Vector2F {
         self == Vector2F::zero()
     }
 
+    /// Returns true if both coordinates differ from those of `other` by no more than `epsilon`.
+    #[inline]
+    pub fn approx_eq(self, other: Vector2F, epsilon: f32) -> bool {
+        self.0.approx_eq(other.0, epsilon)
+    }
+
     #[inline]
     pub fn lerp(self, other: Vector2F, t: f32) -> Vector2F {
         self + (other - self) * t
@@ -169,6 +178,22 @@ impl PartialEq for Vector2F {
     }
 }
 
+/// Serialized as an `(x, y)` pair.
+#[cfg(feature = "serde")]
+impl Serialize for Vector2F {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        (self.x(), self.y()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Vector2F {
+    fn deserialize<D>(deserializer: D) -> Result<Vector2F, D::Error> where D: Deserializer<'de> {
+        let (x, y) = <(f32, f32)>::deserialize(deserializer)?;
+        Ok(vec2f(x, y))
+    }
+}
+
 impl Add<Vector2F> for Vector2F {
     type Output = Vector2F;
     #[inline]